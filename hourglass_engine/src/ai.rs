@@ -1,25 +1,168 @@
+use std::time::{Duration, Instant};
+
 use rand::Rng;
 
-use crate::{Board, Move, Piece, Player};
+use crate::{Board, GameResult, Move, Piece, Player, Tablebase, Wdl};
+
+/// The score of a position the tablebase says is won. Less than a mate, but more than any material.
+const TABLEBASE_WIN_SCORE: f32 = 1000.0;
+
+/// How far [`Board::search_limited`] searches.
+///
+/// The node and time limits are soft: they stop the search from starting another depth,
+///     but the depth that is being searched is always finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SearchLimits {
+    /// The deepest depth to search to, at least 1.
+    pub depth: u32,
+    /// Stop deepening once this many nodes have been searched.
+    pub nodes: Option<u64>,
+    /// Don't start a depth that would be expected to finish after this much time.
+    pub time: Option<Duration>,
+}
+
+impl SearchLimits {
+    /// Searches to the given depth, with no other limits.
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits {
+            depth,
+            nodes: None,
+            time: None,
+        }
+    }
+}
+
+/// The outcome of [`Board::search_limited`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchInfo {
+    /// The best move, or `None` if there are no legal moves.
+    pub best_move: Option<Move>,
+    /// The score of the best move for the player to move, in pawns.
+    pub score: f32,
+    /// The deepest depth that was searched.
+    pub depth: u32,
+    /// The number of positions searched, over every depth.
+    pub nodes: u64,
+}
 
 impl Board {
-    pub fn get_best_move<'b, 'v>(&'b self, depth: u32, scoring: fn(&Board) -> f32) -> Option<Move> {
-        let moves = self.generate_moves();
-        let (idx, _score) = self.search(depth, &moves, scoring);
-        return moves.get(idx).copied();
+    pub fn get_best_move(&self, depth: u32, scoring: fn(&Board) -> f32) -> Option<Move> {
+        self.get_best_move_with_tablebase(depth, scoring, None)
+    }
+
+    /// Gets the best move, only considering the moves that the tablebase says convert
+    ///     the fastest, and stopping the search at positions that are in the tablebase.
+    pub fn get_best_move_with_tablebase(
+        &self,
+        depth: u32,
+        scoring: fn(&Board) -> f32,
+        tablebase: Option<&Tablebase>,
+    ) -> Option<Move> {
+        let best_move = |moves: &[Move]| {
+            let (idx, _score) = self.search_with_tablebase(depth, moves, scoring, tablebase);
+            moves.get(idx).copied()
+        };
+        match tablebase.and_then(|tb| tb.root_moves(self)) {
+            Some(moves) => best_move(&moves),
+            None => self.with_moves(best_move),
+        }
+    }
+
+    pub fn search(&self, depth: u32, moves: &[Move], scoring: fn(&Board) -> f32) -> (usize, f32) {
+        self.search_with_tablebase(depth, moves, scoring, None)
+    }
+
+    /// Searches like [`Board::search`], stopping at positions that are in the tablebase.
+    pub fn search_with_tablebase(
+        &self,
+        depth: u32,
+        moves: &[Move],
+        scoring: fn(&Board) -> f32,
+        tablebase: Option<&Tablebase>,
+    ) -> (usize, f32) {
+        self.search_counting(depth, moves, scoring, tablebase, &mut 0)
+    }
+
+    /// Searches deeper and deeper until one of the limits is reached.
+    pub fn search_limited(
+        &self,
+        limits: &SearchLimits,
+        scoring: fn(&Board) -> f32,
+        tablebase: Option<&Tablebase>,
+    ) -> SearchInfo {
+        let deepen = |moves: &[Move]| self.deepen(moves, limits, scoring, tablebase);
+        match tablebase.and_then(|tb| tb.root_moves(self)) {
+            Some(moves) => deepen(&moves),
+            None => self.with_moves(deepen),
+        }
     }
 
-    pub fn search<'b, 'v>(
-        &'b self,
+    /// Searches the moves deeper and deeper, like [`Board::search_limited`].
+    fn deepen(
+        &self,
+        moves: &[Move],
+        limits: &SearchLimits,
+        scoring: fn(&Board) -> f32,
+        tablebase: Option<&Tablebase>,
+    ) -> SearchInfo {
+        let start = Instant::now();
+        let mut info = SearchInfo {
+            best_move: None,
+            score: 0.0,
+            depth: 0,
+            nodes: 0,
+        };
+        // the root counts as the only node of depth 0
+        let mut last_depth_nodes = 1;
+        for depth in 1..=limits.depth.max(1) {
+            let (depth_start, nodes_before) = (Instant::now(), info.nodes);
+            let (idx, score) =
+                self.search_counting(depth, moves, scoring, tablebase, &mut info.nodes);
+            info.best_move = moves.get(idx).copied();
+            info.score = score;
+            info.depth = depth;
+            if limits.nodes.is_some_and(|nodes| info.nodes >= nodes) {
+                break;
+            }
+
+            // the next depth is expected to grow by as much as this one did
+            let depth_nodes = info.nodes - nodes_before;
+            let growth = depth_nodes as f64 / last_depth_nodes as f64;
+            last_depth_nodes = depth_nodes;
+            if let Some(time) = limits.time {
+                let expected = depth_start.elapsed().mul_f64(growth.max(1.0));
+                if start.elapsed() + expected > time {
+                    break;
+                }
+            }
+        }
+        info
+    }
+
+    /// Like [`Board::search`], counting each position that is searched in `nodes`.
+    fn search_counting(
+        &self,
         depth: u32,
-        moves: &'v Vec<Move>,
+        moves: &[Move],
         scoring: fn(&Board) -> f32,
+        tablebase: Option<&Tablebase>,
+        nodes: &mut u64,
     ) -> (usize, f32) {
+        *nodes += 1;
         if depth == 0 {
             return (0, scoring(self));
         }
 
         if moves.is_empty() {
+            let variant_result = self.variant_result();
+            if variant_result == GameResult::win_for(self.active_color()) {
+                return (0, f32::INFINITY);
+            } else if variant_result == GameResult::win_for(!self.active_color()) {
+                return (0, f32::NEG_INFINITY);
+            } else if variant_result == GameResult::Draw {
+                return (0, 0.0);
+            }
+
             if self.is_in_check(self.active_color()) {
                 return (0, f32::NEG_INFINITY);
             } else {
@@ -31,9 +174,22 @@ impl Board {
         let mut best_score = f32::NEG_INFINITY;
 
         for (idx, umove) in moves.iter().enumerate() {
-            let mut board = self.clone();
-            let _ = board.try_move(*umove).unwrap();
-            let score = -board.search(depth - 1, &board.generate_moves(), scoring).1;
+            let mut board = *self;
+            board
+                .make_move_unchecked(*umove)
+                .expect("a generated move should be playable");
+            let score = match tablebase.and_then(|tb| tb.probe_wdl(&board)) {
+                Some(wdl) => -tablebase_score(wdl),
+                // neither player can win, however the rest of the game goes
+                None if board.is_insufficient_material() => 0.0,
+                None => {
+                    -board
+                        .with_moves(|moves| {
+                            board.search_counting(depth - 1, moves, scoring, tablebase, nodes)
+                        })
+                        .1
+                }
+            };
             if score > best_score {
                 best_score = score;
                 i = idx;
@@ -54,6 +210,16 @@ impl Board {
         } else {
             1.
         };
-        self.squares.iter().map(Piece::score_value).sum::<i32>() as f32 * current_color_mult
+        let material = self.squares.iter().map(Piece::score_value).sum::<i32>();
+        (material + self.pocket_value()) as f32 * current_color_mult
+    }
+}
+
+fn tablebase_score(wdl: Wdl) -> f32 {
+    match wdl {
+        Wdl::Win => TABLEBASE_WIN_SCORE,
+        // the fifty move rule draws these
+        Wdl::CursedWin | Wdl::Draw | Wdl::BlessedLoss => 0.0,
+        Wdl::Loss => -TABLEBASE_WIN_SCORE,
     }
 }
@@ -0,0 +1,69 @@
+use crate::Board;
+
+/// The placements of the two knights among the five squares left
+///     after the bishops and queen are placed.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+impl Board {
+    /// Creates one of the 960 starting positions of Fischer Random chess,
+    ///     numbered with the Scharnagl scheme, where `518` is the standard position.
+    ///
+    /// The board is in chess960 mode. Returns `None` if the index is not in `0..960`.
+    pub fn new_chess960(idx: usize) -> Option<Self> {
+        if idx >= 960 {
+            return None;
+        }
+
+        let mut back_rank = [None; 8];
+        let mut n = idx;
+
+        // light squared bishop on b, d, f or h; dark squared bishop on a, c, e or g
+        back_rank[(n % 4) * 2 + 1] = Some('b');
+        n /= 4;
+        back_rank[(n % 4) * 2] = Some('b');
+        n /= 4;
+
+        let mut place_on_nth_empty = |piece, nth| {
+            let file = (0..8)
+                .filter(|file| back_rank[*file].is_none())
+                .nth(nth)
+                .expect("there should be enough empty squares");
+            back_rank[file] = Some(piece);
+        };
+
+        place_on_nth_empty('q', n % 6);
+        n /= 6;
+
+        // place the second knight first so the first knight's index is not shifted
+        let (first_knight, second_knight) = KNIGHT_PLACEMENTS[n];
+        place_on_nth_empty('n', second_knight);
+        place_on_nth_empty('n', first_knight);
+
+        // the king is always between the rooks
+        place_on_nth_empty('r', 0);
+        place_on_nth_empty('k', 0);
+        place_on_nth_empty('r', 0);
+
+        let black = back_rank.iter().map(|p| p.unwrap()).collect::<String>();
+        let white = black.to_uppercase();
+        let fen = format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black, white);
+
+        let mut board = Board::empty();
+        board.set_chess960(true);
+        board
+            .load_fen(&fen)
+            .expect("a chess960 starting position should be valid");
+        Some(board)
+    }
+}
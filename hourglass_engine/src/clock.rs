@@ -0,0 +1,398 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::Player;
+
+/// Time added to a player's clock after each of their moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TimeBonus {
+    #[default]
+    None,
+    /// Fischer increment: a fixed amount of time is added.
+    Increment(Duration),
+    /// Bronstein delay: the time used is given back, up to the delay.
+    Delay(Duration),
+}
+
+/// A part of a time control, ex. 40 moves in 90 minutes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimeControlStage {
+    /// The number of moves to make in this stage, or `None` for the rest of the game.
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: TimeBonus,
+}
+
+/// The stages of a time control. When a player finishes a stage, the time for the next
+///     stage is added to their clock. The last stage repeats if it has a move count.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimeControl {
+    stages: Vec<TimeControlStage>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimeControlParseErr {
+    Empty,
+    InvalidStage { stage: usize, err_msg: &'static str },
+}
+
+impl Display for TimeControlParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControlParseErr::Empty => write!(f, "the time control has no stages"),
+            TimeControlParseErr::InvalidStage { stage, err_msg } => {
+                write!(f, "invalid time control stage {}: {}", stage + 1, err_msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimeControlParseErr {}
+
+impl TimeControl {
+    /// Creates a time control from its stages. Returns `None` if there are no stages.
+    pub fn new(stages: Vec<TimeControlStage>) -> Option<Self> {
+        (!stages.is_empty()).then_some(TimeControl { stages })
+    }
+
+    /// A single stage for the whole game, with no time added.
+    pub fn sudden_death(time: Duration) -> Self {
+        TimeControl::with_bonus(time, TimeBonus::None)
+    }
+
+    /// A single stage for the whole game, with a Fischer increment.
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        TimeControl::with_bonus(time, TimeBonus::Increment(increment))
+    }
+
+    /// A single stage for the whole game, with a Bronstein delay.
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        TimeControl::with_bonus(time, TimeBonus::Delay(delay))
+    }
+
+    fn with_bonus(time: Duration, bonus: TimeBonus) -> Self {
+        TimeControl {
+            stages: vec![TimeControlStage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+
+    pub fn stages(&self) -> &[TimeControlStage] {
+        &self.stages
+    }
+
+    /// Gets a stage by index, repeating the last stage after the end.
+    fn stage(&self, idx: usize) -> &TimeControlStage {
+        &self.stages[idx.min(self.stages.len() - 1)]
+    }
+
+    /// Parses the PGN `TimeControl` tag, ex. `40/5400+30:1800+30`. Times are in seconds.
+    ///
+    /// PGN has no way to write a delay, so only increments are read and written.
+    pub fn from_pgn_tag(tag: &str) -> Result<Self, TimeControlParseErr> {
+        parse_stages(tag, ':', 1.0)
+    }
+
+    /// Writes the time control as a PGN `TimeControl` tag.
+    pub fn to_pgn_tag(&self) -> String {
+        let stages = self.stages.iter().map(|stage| {
+            let mut output = String::new();
+            if let Some(moves) = stage.moves {
+                output += &format!("{}/", moves);
+            }
+            output += &format_secs(stage.time);
+            if let TimeBonus::Increment(increment) = stage.bonus {
+                output += &format!("+{}", format_secs(increment));
+            }
+            output
+        });
+        stages.collect::<Vec<_>>().join(":")
+    }
+}
+
+/// Parses the usual notation, ex. `5+3`, `15d10` or `40/90+30, 30+30`.
+///
+/// Stages are separated by commas. Each is an optional move count, the time in minutes,
+///     and then an increment (`+`) or delay (`d`) in seconds.
+impl FromStr for TimeControl {
+    type Err = TimeControlParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_stages(s, ',', 60.0)
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, stage) in self.stages.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            if let Some(moves) = stage.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write!(f, "{}", format_number(stage.time.as_secs_f64() / 60.0))?;
+            match stage.bonus {
+                TimeBonus::None => {}
+                TimeBonus::Increment(increment) => write!(f, "+{}", format_secs(increment))?,
+                TimeBonus::Delay(delay) => write!(f, "d{}", format_secs(delay))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_number(n: f64) -> String {
+    format!("{}", (n * 1000.0).round() / 1000.0)
+}
+
+fn format_secs(duration: Duration) -> String {
+    format_number(duration.as_secs_f64())
+}
+
+/// Parses stages, where the time is in units of `time_scale` seconds.
+fn parse_stages(
+    s: &str,
+    separator: char,
+    time_scale: f64,
+) -> Result<TimeControl, TimeControlParseErr> {
+    let mut stages = vec![];
+    for (idx, stage) in s.split(separator).map(str::trim).enumerate() {
+        if stage.is_empty() {
+            continue;
+        }
+        let err = |err_msg| TimeControlParseErr::InvalidStage {
+            stage: idx,
+            err_msg,
+        };
+
+        let (moves, rest) = match stage.split_once('/') {
+            Some((moves, rest)) => {
+                let moves = moves.parse().map_err(|_| err("invalid move count"))?;
+                if moves == 0 {
+                    return Err(err("the move count must be positive"));
+                }
+                (Some(moves), rest)
+            }
+            None => (None, stage),
+        };
+
+        let (time, bonus) = match rest.split_once(['+', 'd']) {
+            Some((time, bonus)) => {
+                let bonus = parse_secs(bonus, 1.0).ok_or(err("invalid bonus time"))?;
+                match rest.contains('+') {
+                    true => (time, TimeBonus::Increment(bonus)),
+                    false => (time, TimeBonus::Delay(bonus)),
+                }
+            }
+            None => (rest, TimeBonus::None),
+        };
+        let time = parse_secs(time, time_scale).ok_or(err("invalid time"))?;
+
+        stages.push(TimeControlStage { moves, time, bonus });
+    }
+
+    TimeControl::new(stages).ok_or(TimeControlParseErr::Empty)
+}
+
+fn parse_secs(s: &str, scale: f64) -> Option<Duration> {
+    let n = s.trim().parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(n * scale).ok()
+}
+
+/// Where a [`Clock`] gets the current time from.
+pub trait TimeSource {
+    /// Gets the time since some fixed point. This should never go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// The real time, measured from when the source was created.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        SystemTimeSource {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A time source that only moves when it is told to, for tests and simulated games.
+///
+/// Clones share the same time.
+#[derive(Clone, Debug, Default)]
+pub struct ManualTimeSource {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+/// A chess clock for two players, following a [`TimeControl`].
+#[derive(Clone, Debug)]
+pub struct Clock<T: TimeSource = SystemTimeSource> {
+    control: TimeControl,
+    source: T,
+    /// The time each player had at the start of their current or last turn.
+    remaining: [Duration; 2],
+    /// The stage each player is in, and how many moves they have made in it.
+    stage: [usize; 2],
+    stage_moves: [u32; 2],
+    active: Player,
+    /// When the active player's turn started, if the clock is running.
+    turn_start: Option<Duration>,
+    /// The player that ran out of time. The clock stops once someone does.
+    flagged: Option<Player>,
+}
+
+impl Clock {
+    /// Creates a clock that uses the real time.
+    pub fn new(control: TimeControl) -> Self {
+        Clock::with_time_source(control, SystemTimeSource::default())
+    }
+}
+
+impl<T: TimeSource> Clock<T> {
+    pub fn with_time_source(control: TimeControl, source: T) -> Self {
+        let time = control.stage(0).time;
+        Clock {
+            control,
+            source,
+            remaining: [time; 2],
+            stage: [0; 2],
+            stage_moves: [0; 2],
+            active: Player::White,
+            turn_start: None,
+            flagged: None,
+        }
+    }
+
+    pub fn time_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// Starts or resumes the clock, running down the given player's time.
+    pub fn start(&mut self, player: Player) {
+        self.stop();
+        if self.flagged.is_none() {
+            self.active = player;
+            self.turn_start = Some(self.source.now());
+        }
+    }
+
+    /// Pauses the clock.
+    pub fn stop(&mut self) {
+        if let Some(elapsed) = self.elapsed() {
+            self.use_time(elapsed);
+        }
+        self.turn_start = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.turn_start.is_some()
+    }
+
+    /// The player whose time is running, or would run if the clock was started.
+    pub fn active(&self) -> Player {
+        self.active
+    }
+
+    /// Ends the active player's turn after they move: adds their bonus time,
+    ///     moves them to the next stage if they finished this one, and starts the other
+    ///     player's time. Does nothing if a player has run out of time.
+    pub fn press(&mut self) {
+        let elapsed = self.elapsed().unwrap_or_default();
+        self.turn_start = None;
+        if !self.use_time(elapsed) {
+            return;
+        }
+
+        let idx = self.active.index();
+        let stage = *self.control.stage(self.stage[idx]);
+        match stage.bonus {
+            TimeBonus::None => {}
+            TimeBonus::Increment(increment) => self.remaining[idx] += increment,
+            TimeBonus::Delay(delay) => self.remaining[idx] += elapsed.min(delay),
+        }
+
+        self.stage_moves[idx] += 1;
+        if stage.moves == Some(self.stage_moves[idx]) {
+            self.stage[idx] += 1;
+            self.stage_moves[idx] = 0;
+            self.remaining[idx] += self.control.stage(self.stage[idx]).time;
+        }
+
+        self.active = !self.active;
+        self.turn_start = Some(self.source.now());
+    }
+
+    /// Gets the time a player has left, including the time used so far this turn.
+    pub fn remaining(&self, player: Player) -> Duration {
+        let remaining = self.remaining[player.index()];
+        match self.elapsed() {
+            Some(elapsed) if player == self.active => remaining.saturating_sub(elapsed),
+            _ => remaining,
+        }
+    }
+
+    /// Gets the player that ran out of time, if any.
+    pub fn flagged(&self) -> Option<Player> {
+        match self.flagged {
+            Some(player) => Some(player),
+            None if self.is_running() && self.remaining(self.active) == Duration::ZERO => {
+                Some(self.active)
+            }
+            None => None,
+        }
+    }
+
+    fn elapsed(&self) -> Option<Duration> {
+        let start = self.turn_start?;
+        Some(self.source.now().saturating_sub(start))
+    }
+
+    /// Takes time off the active player's clock. Returns false if it runs out.
+    fn use_time(&mut self, elapsed: Duration) -> bool {
+        let remaining = &mut self.remaining[self.active.index()];
+        if elapsed >= *remaining {
+            *remaining = Duration::ZERO;
+            self.flagged = Some(self.active);
+            return false;
+        }
+        *remaining -= elapsed;
+        true
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{Board, Move, Piece, Player, Square};
+
+/// The types of pieces that can be in a pocket, in the order that they are counted.
+pub(crate) const POCKET_PIECES: [Piece; 5] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+];
+
+fn pocket_index(piece: Piece) -> usize {
+    POCKET_PIECES
+        .iter()
+        .position(|p| *p == piece & Piece::PieceType)
+        .expect("kings can't be in a pocket")
+}
+
+impl Board {
+    /// The number of pieces of a type that a player has in their pocket, for crazyhouse.
+    pub fn pocket_count(&self, player: Player, piece: Piece) -> u8 {
+        match piece & Piece::PieceType {
+            Piece::King => 0,
+            _ => self.pockets[player.index()][pocket_index(piece)],
+        }
+    }
+
+    /// Checks if the piece on the square was promoted from a pawn, for crazyhouse.
+    ///
+    /// A promoted piece goes back to being a pawn when it is captured.
+    pub fn is_promoted(&self, square: Square) -> bool {
+        self.promoted & (1 << square.idx()) != 0
+    }
+
+    pub(crate) fn add_to_pocket(&mut self, player: Player, piece: Piece) {
+        self.pockets[player.index()][pocket_index(piece)] += 1;
+    }
+
+    pub(crate) fn set_promoted(&mut self, idx: usize) {
+        self.promoted |= 1 << idx;
+    }
+
+    /// Empties both pockets and forgets which pieces were promoted.
+    pub(crate) fn clear_pockets(&mut self) {
+        self.pockets = [[0; 5]; 2];
+        self.promoted = 0;
+    }
+
+    /// The value of white's pocket minus the value of black's pocket.
+    pub(crate) fn pocket_value(&self) -> i32 {
+        let value = |player: Player| {
+            POCKET_PIECES
+                .iter()
+                .map(|p| {
+                    let piece = *p | player.to_piece_color();
+                    piece.score_value() * self.pocket_count(player, *p) as i32
+                })
+                .sum::<i32>()
+        };
+        value(Player::White) + value(Player::Black)
+    }
+
+    /// Places a piece from the pocket on the board, switching the `active_color`.
+    pub(crate) fn drop_piece(&mut self, piece: Piece, to: usize) {
+        if piece == Piece::Pawn {
+            self.halfmove = 0;
+        } else {
+            self.halfmove += 1;
+        }
+        if self.active_color == Player::Black {
+            self.fullmove += 1;
+        }
+
+        self.en_passant = None;
+        self.squares[to] = piece | self.active_color.to_piece_color();
+        self.active_color = !self.active_color;
+    }
+
+    /// Moves pieces in and out of the pockets after a move, given the position before it.
+    pub(crate) fn update_pockets(&mut self, before: &Board, umove: Move) {
+        let mover = before.active_color;
+        if let Some(piece) = umove.dropped() {
+            self.pockets[mover.index()][pocket_index(piece)] -= 1;
+            return;
+        }
+
+        if before.is_capture(umove) {
+            let captured = before.squares[umove.target_idx()];
+            // en passant captures a pawn from another square
+            let kind = if captured == Piece::empty() || before.is_promoted(umove.to()) {
+                Piece::Pawn
+            } else {
+                captured
+            };
+            self.add_to_pocket(mover, kind);
+        }
+
+        let is_promoted = before.is_promoted(umove.from()) || umove.promote().is_some();
+        self.promoted &= !(1 << umove.origin_idx()) & !(1 << umove.target_idx());
+        if is_promoted {
+            self.set_promoted(umove.target_idx());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+use crate::{Board, Move, Piece, Player, Square, Variant};
+
+/// How [`Board::diagram`] draws the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiagramOptions {
+    /// Draw the pieces as Unicode figurines, ex. `♘`, instead of FEN letters.
+    pub unicode: bool,
+    /// A move whose `from` and `to` squares are marked with `[ ]`.
+    pub highlight: Option<Move>,
+    /// Mark the squares attacked by this player with `( )`.
+    pub attacked_by: Option<Player>,
+}
+
+/// Gets the letter of a piece as in a FEN, or its figurine.
+pub(crate) fn piece_symbol(piece: Piece, unicode: bool) -> char {
+    if piece == Piece::empty() {
+        return match unicode {
+            true => '·',
+            false => '.',
+        };
+    }
+
+    let kind = match piece & Piece::PieceType {
+        Piece::King => 0,
+        Piece::Queen => 1,
+        Piece::Rook => 2,
+        Piece::Bishop => 3,
+        Piece::Knight => 4,
+        _ => 5,
+    };
+    let symbols = match (unicode, piece.is_color(Player::White)) {
+        (false, true) => ['K', 'Q', 'R', 'B', 'N', 'P'],
+        (false, false) => ['k', 'q', 'r', 'b', 'n', 'p'],
+        (true, true) => ['♔', '♕', '♖', '♗', '♘', '♙'],
+        (true, false) => ['♚', '♛', '♜', '♝', '♞', '♟'],
+    };
+    symbols[kind]
+}
+
+impl Board {
+    /// Draws the board as text, from white's side with the coordinates around it,
+    ///     followed by the player to move.
+    ///
+    /// ```text
+    ///    a  b  c  d  e  f  g  h
+    /// 8  r  n  b  q  k  b  n  r  8
+    /// 7  p  p  p  p  p  p  p  p  7
+    /// 6  .  .  .  .  .  .  .  .  6
+    /// 5  .  .  .  .  .  .  .  .  5
+    /// 4  .  .  .  . [P] .  .  .  4
+    /// 3  .  .  .  .  .  .  .  .  3
+    /// 2  P  P  P  P [.] P  P  P  2
+    /// 1  R  N  B  Q  K  B  N  R  1
+    ///    a  b  c  d  e  f  g  h
+    /// Black to move
+    /// ```
+    pub fn diagram(&self, options: DiagramOptions) -> String {
+        let attacked = options
+            .attacked_by
+            .map(|player| self.generate_attacks(player));
+        let files = "   a  b  c  d  e  f  g  h\n";
+
+        let mut output = files.to_owned();
+        for rank in (0..8).rev() {
+            output += &format!("{} ", rank + 1);
+            for file in 0..8 {
+                let square = Square::from_idx(rank * 8 + file);
+                let highlighted = options
+                    .highlight
+                    .is_some_and(|m| m.from() == square || m.to() == square);
+                let (open, close) = match highlighted {
+                    true => ('[', ']'),
+                    false if attacked.is_some_and(|map| map[square.idx()]) => ('(', ')'),
+                    false => (' ', ' '),
+                };
+                output.push(open);
+                output.push(piece_symbol(self.piece_on(square), options.unicode));
+                output.push(close);
+            }
+            output += &format!(" {}\n", rank + 1);
+        }
+        output += files;
+
+        if self.variant == Variant::Crazyhouse {
+            output += &format!("Pocket: {}\n", self.get_fen_pocket());
+        }
+        output += match self.active_color {
+            Player::White => "White to move",
+            Player::Black => "Black to move",
+        };
+        output
+    }
+
+    /// Draws the board like its [`Display`] does, but with Unicode figurines.
+    pub fn to_unicode(&self) -> String {
+        self.diagram(DiagramOptions {
+            unicode: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Draws the board as an ASCII diagram, see [`Board::diagram`].
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.diagram(DiagramOptions::default()))
+    }
+}
@@ -0,0 +1,455 @@
+use std::{collections::HashMap, fmt::Display};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    gen_attacks::attacks_from, kpk, square_distance, Board, Piece, Player, Square, SquareSet,
+    Variant,
+};
+
+/// Added to the score of endgames that are known to be won, so the search heads for them.
+const KNOWN_WIN: f32 = 10.0;
+
+/// The kinds of pieces, in the order that they are counted and written in a signature.
+const PIECE_KINDS: [Piece; 6] = [
+    Piece::King,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
+/// Scores an endgame for the given strong side.
+type EndgameEval = fn(&Board, Player) -> f32;
+
+lazy_static! {
+    /// The endgames with their own evaluation, with white as the strong side.
+    static ref ENDGAMES: HashMap<MaterialSignature, EndgameEval> = {
+        let mut endgames = HashMap::new();
+        let mut add = |signature: &str, eval: EndgameEval| {
+            endgames.insert(MaterialSignature::parse(signature), eval);
+        };
+
+        add("KQvK", eval_kxk);
+        add("KRvK", eval_kxk);
+        add("KBNvK", eval_kbnk);
+        add("KPvK", eval_kpk);
+        add("KvK", eval_draw);
+        add("KBvK", eval_draw);
+        add("KNvK", eval_draw);
+        add("KNNvK", eval_draw);
+
+        endgames
+    };
+}
+
+/// The number of each kind of piece that each player has, ex. `KRPvKR`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaterialSignature {
+    /// The counts for white and black, in the order of `PIECE_KINDS`.
+    counts: [[u8; 6]; 2],
+}
+
+impl MaterialSignature {
+    pub fn of(board: &Board) -> Self {
+        let mut signature = MaterialSignature::default();
+        for piece in board.squares.iter() {
+            let Some(kind) = PIECE_KINDS
+                .iter()
+                .position(|k| *piece & Piece::PieceType == *k)
+            else {
+                continue;
+            };
+            let player = match piece.is_color(Player::White) {
+                true => Player::White,
+                false => Player::Black,
+            };
+            signature.counts[player.index()][kind] += 1;
+        }
+        signature
+    }
+
+    /// Gets the number of pieces of a type that a player has.
+    pub fn count(&self, player: Player, piece: Piece) -> u8 {
+        PIECE_KINDS
+            .iter()
+            .position(|k| piece & Piece::PieceType == *k)
+            .map_or(0, |kind| self.counts[player.index()][kind])
+    }
+
+    /// Swaps the pieces of white and black.
+    pub fn flipped(&self) -> Self {
+        MaterialSignature {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    /// Parses a signature like `KBNvK`. Panics if it is invalid.
+    fn parse(signature: &str) -> Self {
+        let mut result = MaterialSignature::default();
+        let (white, black) = signature.split_once('v').expect("a signature needs a 'v'");
+        for (player, pieces) in [white, black].into_iter().enumerate() {
+            for c in pieces.chars() {
+                let kind = "KQRBNP".find(c).expect("invalid piece in signature");
+                result.counts[player][kind] += 1;
+            }
+        }
+        result
+    }
+
+    /// Gets the number of pieces, other than kings and pawns, that a player has.
+    fn count_minor_and_major(&self, player: Player) -> u8 {
+        [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight]
+            .into_iter()
+            .map(|p| self.count(player, p))
+            .sum()
+    }
+}
+
+impl Display for MaterialSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (player, counts) in self.counts.iter().enumerate() {
+            if player == 1 {
+                write!(f, "v")?;
+            }
+            for (letter, count) in "KQRBNP".chars().zip(counts) {
+                for _ in 0..*count {
+                    write!(f, "{}", letter)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    /// Scores the position like [`Board::score_material`], but uses specialized evaluation
+    ///     for endgames that are known wins or draws, looked up by the material signature.
+    ///
+    /// The known endgames only apply to standard chess, so variants are scored by material.
+    pub fn score_endgames(&self) -> f32 {
+        if self.variant != Variant::Standard {
+            return self.score_material();
+        }
+        let signature = MaterialSignature::of(self);
+
+        let endgame = match ENDGAMES.get(&signature) {
+            Some(eval) => Some((*eval, Player::White)),
+            None => ENDGAMES
+                .get(&signature.flipped())
+                .map(|eval| (*eval, Player::Black)),
+        };
+        if let Some((eval, strong)) = endgame {
+            let score = eval(self, strong);
+            return match self.active_color == strong {
+                true => score,
+                false => -score,
+            };
+        }
+
+        self.score_material() * self.drawish_scale(&signature)
+    }
+
+    /// Checks if a player could checkmate the other with any series of legal moves,
+    ///     even with the other player's help.
+    ///
+    /// In variants that can be won without checkmate, this is whether the player could win.
+    ///
+    /// A position where the pawns are locked together and neither king can capture one
+    ///     is dead, so neither player has mating material.
+    pub fn has_mating_material(&self, player: Player) -> bool {
+        match self.variant {
+            Variant::Standard => {}
+            // even a lone king can win, and in antichess losing pieces is how you win
+            Variant::KingOfTheHill | Variant::RacingKings | Variant::Antichess => return true,
+            // captured pieces come back into play from the pocket
+            Variant::Crazyhouse => return true,
+            // the king wins by capturing the whole horde, and the horde has no king to lose
+            Variant::Horde => return true,
+            // any piece other than the king can give check, or explode the other king
+            Variant::ThreeCheck | Variant::Atomic => {
+                return self
+                    .squares
+                    .iter()
+                    .any(|p| p.is_color(player) && *p & Piece::PieceType != Piece::King);
+            }
+        }
+
+        if self.is_locked_pawn_position() {
+            return false;
+        }
+
+        let signature = MaterialSignature::of(self);
+        let opponent = !player;
+
+        let heavy_or_pawns = [Piece::Queen, Piece::Rook, Piece::Pawn]
+            .into_iter()
+            .any(|p| signature.count(player, p) > 0);
+        let knights = signature.count(player, Piece::Knight);
+        let bishops = signature.count(player, Piece::Bishop);
+        if heavy_or_pawns || (knights > 0 && knights + bishops >= 2) {
+            return true;
+        }
+
+        let opponent_pieces =
+            signature.count_minor_and_major(opponent) + signature.count(opponent, Piece::Pawn);
+        if knights == 1 {
+            // the other king needs a piece of its own to box it in
+            return opponent_pieces > 0;
+        }
+        if bishops == 0 {
+            return false;
+        }
+
+        // with bishops that are all on one color, the king can't be attacked on the other color,
+        //     unless there is some other piece to block it in
+        let own_color = self.squares.iter().enumerate().find_map(|(idx, p)| {
+            (*p == Piece::Bishop | player.to_piece_color()).then(|| is_light_square(idx))
+        });
+        let same_colored = self
+            .squares
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| **p & Piece::PieceType == Piece::Bishop)
+            .all(|(idx, _)| Some(is_light_square(idx)) == own_color);
+        let opponent_bishops = signature.count(opponent, Piece::Bishop);
+        !same_colored || opponent_pieces > opponent_bishops
+    }
+
+    /// Checks if neither player could checkmate the other with any series of legal moves,
+    ///     which draws the game.
+    ///
+    /// This covers a king and at most one minor piece against a lone king, bishops that are
+    ///     all on the same color, and pawns that are locked together with no way for
+    ///     either king to capture one, see [`Board::has_mating_material`].
+    pub fn is_insufficient_material(&self) -> bool {
+        !self.has_mating_material(Player::White) && !self.has_mating_material(Player::Black)
+    }
+
+    /// Checks for a position of only kings and pawns where every pawn is blocked by an enemy
+    ///     pawn and can't capture, and neither king can reach a pawn that it could capture.
+    ///
+    /// Nothing can ever be captured or give check in such a position, so it is dead.
+    fn is_locked_pawn_position(&self) -> bool {
+        if self.en_passant.is_some() {
+            return false;
+        }
+
+        let mut pawns = [SquareSet::EMPTY; 2];
+        for square in Square::all() {
+            let piece = self.piece_on(square);
+            match piece & Piece::PieceType {
+                Piece::Pawn => {
+                    let player = match piece.is_color(Player::White) {
+                        true => Player::White,
+                        false => Player::Black,
+                    };
+                    pawns[player.index()].insert(square);
+                }
+                Piece::King => {}
+                _ if piece == Piece::empty() => {}
+                _ => return false,
+            }
+        }
+
+        let mut pawn_attacks = [SquareSet::EMPTY; 2];
+        for player in [Player::White, Player::Black] {
+            let enemy_pawns = pawns[(!player).index()];
+            for pawn in pawns[player.index()] {
+                let ahead = (pawn.idx() as isize + player.forward_value() * 8) as usize;
+                let attacks = attacks_from(
+                    Piece::Pawn | player.to_piece_color(),
+                    pawn.idx(),
+                    SquareSet::EMPTY,
+                );
+                if !enemy_pawns.contains(Square::from_idx(ahead))
+                    || !(attacks & enemy_pawns).is_empty()
+                {
+                    return false;
+                }
+                pawn_attacks[player.index()] |= attacks;
+            }
+        }
+
+        let all_pawns = pawns[0] | pawns[1];
+        for player in [Player::White, Player::Black] {
+            let Some(king) = self.king_square(player) else {
+                return false;
+            };
+            let enemy = (!player).index();
+
+            // the king can walk anywhere without a pawn that the enemy pawns don't attack
+            let walkable = !(all_pawns | pawn_attacks[enemy]);
+            let mut region = SquareSet::from(king);
+            loop {
+                let mut grown = region;
+                for square in region {
+                    grown |= attacks_from(Piece::King, square.idx(), SquareSet::EMPTY) & walkable;
+                }
+                if grown == region {
+                    break;
+                }
+                region = grown;
+            }
+
+            // and capture any enemy pawn next to it that isn't defended by another pawn
+            let capturable = pawns[enemy].iter().any(|pawn| {
+                !pawn_attacks[enemy].contains(pawn)
+                    && !(attacks_from(Piece::King, pawn.idx(), SquareSet::EMPTY) & region)
+                        .is_empty()
+            });
+            if capturable {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Scales down the score of endgames that are hard to win despite the extra material.
+    fn drawish_scale(&self, signature: &MaterialSignature) -> f32 {
+        for strong in [Player::White, Player::Black] {
+            if self.is_wrong_bishop(signature, strong) {
+                return 0.0;
+            }
+        }
+
+        // opposite colored bishops, with only pawns otherwise
+        let only_bishops = [Player::White, Player::Black].into_iter().all(|p| {
+            signature.count(p, Piece::Bishop) == 1 && signature.count_minor_and_major(p) == 1
+        });
+        if only_bishops {
+            let white_bishop = self.find_piece(Piece::Bishop | Piece::White);
+            let black_bishop = self.find_piece(Piece::Bishop | Piece::Black);
+            if is_light_square(white_bishop) != is_light_square(black_bishop) {
+                let white_pawns = signature.count(Player::White, Piece::Pawn);
+                let black_pawns = signature.count(Player::Black, Piece::Pawn);
+                return match white_pawns.abs_diff(black_pawns) <= 1 {
+                    true => 0.25,
+                    false => 0.5,
+                };
+            }
+        }
+
+        1.0
+    }
+
+    /// Checks for a bishop and rook pawns against a lone king in the corner,
+    ///     where the bishop can't cover the promotion square.
+    fn is_wrong_bishop(&self, signature: &MaterialSignature, strong: Player) -> bool {
+        let weak = !strong;
+        let has_material = signature.count(strong, Piece::Bishop) == 1
+            && signature.count_minor_and_major(strong) == 1
+            && signature.count(strong, Piece::Pawn) > 0
+            && signature.count_minor_and_major(weak) == 0
+            && signature.count(weak, Piece::Pawn) == 0;
+        if !has_material {
+            return false;
+        }
+
+        let pawn = Piece::Pawn | strong.to_piece_color();
+        let files = self
+            .squares
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| **p == pawn)
+            .map(|(idx, _)| idx % 8)
+            .collect::<Vec<_>>();
+        let file = files[0];
+        if (file != 0 && file != 7) || files.iter().any(|f| *f != file) {
+            return false;
+        }
+
+        let promotion = match strong {
+            Player::White => 56 + file,
+            Player::Black => file,
+        };
+        let bishop = self.find_piece(Piece::Bishop | strong.to_piece_color());
+        is_light_square(bishop) != is_light_square(promotion)
+            && square_distance(self.find_king(weak).idx(), promotion) <= 1
+    }
+
+    fn find_piece(&self, piece: Piece) -> usize {
+        self.squares
+            .iter()
+            .position(|p| *p == piece)
+            .expect("the signature should have the piece")
+    }
+}
+
+fn is_light_square(idx: usize) -> bool {
+    (idx % 8 + idx / 8) % 2 == 1
+}
+
+/// A bonus for the weak king being near the edge of the board.
+fn push_to_edge(idx: usize) -> f32 {
+    let (file, rank) = (idx % 8, idx / 8);
+    let edge_distance = file.min(7 - file).min(rank).min(7 - rank);
+    (3 - edge_distance) as f32 * 0.2
+}
+
+/// A bonus for the kings being close to each other.
+fn push_close(a: usize, b: usize) -> f32 {
+    (7 - square_distance(a, b)) as f32 * 0.05
+}
+
+fn eval_draw(_board: &Board, _strong: Player) -> f32 {
+    0.0
+}
+
+/// A king and a major piece against a lone king: drive the king to the edge to mate it.
+fn eval_kxk(board: &Board, strong: Player) -> f32 {
+    let material = board
+        .squares
+        .iter()
+        .map(|p| p.score_value().abs())
+        .sum::<i32>();
+    let strong_king = board.find_king(strong).idx();
+    let weak_king = board.find_king(!strong).idx();
+
+    KNOWN_WIN + material as f32 + push_to_edge(weak_king) + push_close(strong_king, weak_king)
+}
+
+/// A king, bishop and knight against a lone king: it can only be mated in a corner
+///     of the same color as the bishop.
+fn eval_kbnk(board: &Board, strong: Player) -> f32 {
+    let bishop = board.find_piece(Piece::Bishop | strong.to_piece_color());
+    let corners = match is_light_square(bishop) {
+        true => [7, 56],
+        false => [0, 63],
+    };
+    let strong_king = board.find_king(strong).idx();
+    let weak_king = board.find_king(!strong).idx();
+    let corner_distance = corners
+        .iter()
+        .map(|c| square_distance(weak_king, *c))
+        .min()
+        .unwrap();
+
+    KNOWN_WIN + 6.0 + (7 - corner_distance) as f32 * 0.2 + push_close(strong_king, weak_king)
+}
+
+/// A king and pawn against a lone king, looked up in the bitbase.
+fn eval_kpk(board: &Board, strong: Player) -> f32 {
+    let pawn = board.find_piece(Piece::Pawn | strong.to_piece_color());
+    let mut squares = [
+        board.find_king(strong).idx(),
+        pawn,
+        board.find_king(!strong).idx(),
+    ];
+
+    // the bitbase has white as the strong side, with the pawn on the a-d files
+    if strong == Player::Black {
+        squares.iter_mut().for_each(|s| *s ^= 56);
+    }
+    if squares[1] % 8 > 3 {
+        squares.iter_mut().for_each(|s| *s ^= 7);
+    }
+    let [strong_king, pawn, weak_king] = squares;
+
+    if !kpk::probe(strong_king, pawn, weak_king, board.active_color == strong) {
+        return 0.0;
+    }
+    KNOWN_WIN + 1.0 + (pawn / 8) as f32 * 0.1
+}
@@ -1,12 +1,15 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use crate::{
-    idx_to_square_name,
+    back_rank,
+    crazyhouse::POCKET_PIECES,
     pieces::{CastleRights, Piece, Player},
-    square_name_to_idx, Board,
+    validate::PositionErr,
+    variant::THREE_CHECK_LIMIT,
+    Board, Move, Square, Variant, STANDARD_CASTLE_FILES,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FenPart {
     Board,
     ActiveColor,
@@ -14,9 +17,13 @@ pub enum FenPart {
     EnPassant,
     HalfMove,
     FullMove,
+    /// The number of checks given by each player in Three-check, ex. `+1+0`.
+    CheckCount,
+    /// The operations of an EPD record.
+    Operations,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FenParseErr {
     MissingComponent {
         part: FenPart,
@@ -27,6 +34,8 @@ pub enum FenParseErr {
         err_msg: &'static str,
     },
     TooManyComponents,
+    /// The fen was read, but the position is not playable.
+    InvalidPosition(PositionErr),
 }
 
 impl FenParseErr {
@@ -57,13 +66,14 @@ impl Display for FenParseErr {
                 part, char_idx, err_msg
             ),
             FenParseErr::TooManyComponents => write!(f, "too many components in the fen"),
+            FenParseErr::InvalidPosition(err) => write!(f, "invalid position: {}", err),
         }
     }
 }
 
 impl std::error::Error for FenParseErr {}
 
-fn piece_from_fen(c: char) -> Option<Piece> {
+pub(crate) fn piece_from_fen(c: char) -> Option<Piece> {
     let piece_type = match c.to_lowercase().next().unwrap() {
         'k' => Piece::King,
         'p' => Piece::Pawn,
@@ -83,17 +93,59 @@ fn piece_from_fen(c: char) -> Option<Piece> {
     Some(player_type | piece_type)
 }
 
+/// An operation from an EPD record, ex. `bm Nf3;` or `id "WAC.001";`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EpdOperation {
+    /// `bm`: the best moves in the position.
+    BestMoves(Vec<Move>),
+    /// `am`: moves that should be avoided.
+    AvoidMoves(Vec<Move>),
+    /// `id`: an identifier for the position.
+    Id(String),
+    /// `dm`: the side to move can mate in this many moves.
+    DirectMate(u32),
+    /// `ce`: the evaluation of the position in centipawns, from the view of the side to move.
+    CentipawnEval(i32),
+    /// `c0` through `c9`: comments.
+    Comment(u8, String),
+    /// Any other operation, with its operands left unparsed.
+    Other {
+        opcode: String,
+        operands: Vec<String>,
+    },
+}
+
 impl Board {
+    /// Loads a position from a fen.
+    ///
+    /// The position is checked with [`Board::validate`] after it is read.
+    ///     If the fen can't be read or the position is not valid, the board is left unchanged.
+    ///
+    /// Castle rights can be given in X-FEN or Shredder-FEN. To load a chess960 position,
+    ///     turn on chess960 mode with [`Board::set_chess960`] first.
+    ///
+    /// In Three-check, the fen may end with the number of checks each player has given,
+    ///     ex. `+1+0`. If it is left out, neither player has given a check.
     pub fn load_fen(&mut self, fen: &str) -> Result<(), FenParseErr> {
+        // the board is only changed once the whole position is read and valid
+        let mut board = *self;
+        board.parse_fen(fen)?;
+        *self = board;
+        Ok(())
+    }
+
+    fn parse_fen(&mut self, fen: &str) -> Result<(), FenParseErr> {
         use FenPart::*;
 
-        let mut split = fen.split(' ');
+        // the fields may be separated by any amount of whitespace, like in an EPD record
+        let mut split = fen.split_whitespace();
         let board = split.next().ok_or(FenParseErr::missing(Board))?;
         let active_color = split.next().ok_or(FenParseErr::missing(ActiveColor))?;
         let castling = split.next().ok_or(FenParseErr::missing(CastleRights))?;
         let en_passant = split.next().ok_or(FenParseErr::missing(EnPassant))?;
         let halfmove = split.next().ok_or(FenParseErr::missing(HalfMove))?;
         let fullmove = split.next().ok_or(FenParseErr::missing(FullMove))?;
+        let checks = split.next();
         if split.next().is_some() {
             return Err(FenParseErr::TooManyComponents);
         }
@@ -104,8 +156,9 @@ impl Board {
         self.parse_en_passant(en_passant)?;
         self.parse_halfmove(halfmove)?;
         self.parse_fullmove(fullmove)?;
+        self.parse_checks(checks)?;
 
-        Ok(())
+        self.validate().map_err(FenParseErr::InvalidPosition)
     }
 
     fn parse_active_color(&mut self, active_color: &str) -> Result<(), FenParseErr> {
@@ -123,67 +176,161 @@ impl Board {
         Ok(())
     }
 
+    /// Parses the piece placement, along with the pocket and promoted pieces in crazyhouse.
+    ///
+    /// The pocket can either be in brackets after the board, ex. `[Qn]`, or an extra rank.
     fn parse_board(&mut self, board: &str) -> Result<(), FenParseErr> {
+        let overran = |char_idx| FenParseErr::invalid(FenPart::Board, char_idx, "overran board");
+
+        self.clear_pockets();
+        let (board, pocket) = split_pocket(board);
+
         let mut file: usize = 0;
         let mut rank: usize = 7;
-        Ok(for (char_idx, c) in board.chars().enumerate() {
+        for (char_idx, c) in board.chars().enumerate() {
+            if c == '~' {
+                let promoted = (rank * 8 + file).checked_sub(1);
+                match promoted {
+                    Some(idx) if file > 0 && self.variant == Variant::Crazyhouse => {
+                        self.set_promoted(idx)
+                    }
+                    _ => {
+                        return Err(FenParseErr::invalid(
+                            FenPart::Board,
+                            char_idx,
+                            "'~' must follow a piece, in crazyhouse",
+                        ))
+                    }
+                }
+                continue;
+            }
             if c == '/' {
+                if file != 8 {
+                    return Err(FenParseErr::invalid(
+                        FenPart::Board,
+                        char_idx,
+                        "each rank must have 8 squares",
+                    ));
+                }
                 file = 0;
-                rank -= 1;
+                rank = rank.checked_sub(1).ok_or(overran(char_idx))?;
                 continue;
             }
             if let Some(num) = c.to_digit(10) {
                 let num = num as usize;
+                if num == 0 || file + num > 8 {
+                    return Err(overran(char_idx));
+                }
 
                 for idx in 0..num {
-                    let square =
-                        self.squares
-                            .get_mut(rank * 8 + file + idx)
-                            .ok_or(FenParseErr::invalid(
-                                FenPart::Board,
-                                char_idx,
-                                "overran board",
-                            ))?;
-
-                    *square = Piece::empty();
+                    self.squares[rank * 8 + file + idx] = Piece::empty();
                 }
 
-                file += num as usize;
+                file += num;
             } else {
                 let piece = piece_from_fen(c).ok_or(FenParseErr::invalid(
                     FenPart::Board,
                     char_idx,
                     "invalid char",
                 ))?;
-                let square = self
-                    .squares
-                    .get_mut(rank * 8 + file)
-                    .ok_or(FenParseErr::invalid(
-                        FenPart::Board,
-                        char_idx,
-                        "overran board",
-                    ))?;
-                *square = piece;
+                if file >= 8 {
+                    return Err(overran(char_idx));
+                }
+                self.squares[rank * 8 + file] = piece;
                 file += 1;
             }
-        })
+        }
+
+        if rank != 0 || file != 8 {
+            return Err(FenParseErr::invalid(
+                FenPart::Board,
+                board.len(),
+                "the board must have 8 ranks of 8 squares",
+            ));
+        }
+
+        if let Some(pocket) = pocket {
+            self.parse_pocket(pocket, board.len() + 1)?;
+        }
+        Ok(())
+    }
+
+    fn parse_pocket(&mut self, pocket: &str, start_idx: usize) -> Result<(), FenParseErr> {
+        if self.variant != Variant::Crazyhouse {
+            return Err(FenParseErr::invalid(
+                FenPart::Board,
+                start_idx,
+                "only crazyhouse has pockets",
+            ));
+        }
+
+        for (char_idx, c) in pocket.chars().enumerate() {
+            let piece = piece_from_fen(c)
+                .filter(|p| *p & Piece::PieceType != Piece::King)
+                .ok_or(FenParseErr::invalid(
+                    FenPart::Board,
+                    start_idx + char_idx,
+                    "invalid piece in pocket",
+                ))?;
+            let player = match piece.is_color(Player::White) {
+                true => Player::White,
+                false => Player::Black,
+            };
+            self.add_to_pocket(player, piece);
+        }
+        Ok(())
     }
 
+    /// Parses the castle rights. As well as `KQkq`, this accepts X-FEN and Shredder-FEN,
+    ///     where a right can be given by the file of its rook.
+    ///
+    /// This only sets the castle files. Chess960 mode is left as it is, see [`Board::set_chess960`].
     fn parse_castling(&mut self, castling: &str) -> Result<(), FenParseErr> {
+        self.castle_rights = CastleRights::empty();
+        self.castle_files = STANDARD_CASTLE_FILES;
+        if castling == "-" {
+            return Ok(());
+        }
+
         for (c_idx, c) in castling.chars().enumerate() {
-            match c {
-                'K' => self.castle_rights |= CastleRights::WhiteKingSide,
-                'Q' => self.castle_rights |= CastleRights::WhiteQueenSide,
-                'k' => self.castle_rights |= CastleRights::BlackKingSide,
-                'q' => self.castle_rights |= CastleRights::BlackQueenSide,
+            let player = if c.is_ascii_uppercase() {
+                Player::White
+            } else {
+                Player::Black
+            };
+            let rank = back_rank(player);
+            let king = Piece::King | player.to_piece_color();
+            let rook = Piece::Rook | player.to_piece_color();
+            let king_file = (0..8)
+                .find(|file| self.squares[rank * 8 + file] == king)
+                .unwrap_or(4);
+            let is_rook = |file: &usize| self.squares[rank * 8 + file] == rook;
+
+            // `K` and `Q` refer to the outermost rook on that side of the king
+            let (right, file) = match c.to_ascii_lowercase() {
+                'k' => (
+                    CastleRights::for_side(player, true),
+                    (king_file + 1..8).rev().find(is_rook).unwrap_or(7),
+                ),
+                'q' => (
+                    CastleRights::for_side(player, false),
+                    (0..king_file).find(is_rook).unwrap_or(0),
+                ),
+                file @ 'a'..='h' => {
+                    let file = file as usize - 'a' as usize;
+                    (CastleRights::for_side(player, file > king_file), file)
+                }
                 _ => {
                     return Err(FenParseErr::invalid(
                         FenPart::CastleRights,
                         c_idx,
-                        "character must be either 'K', 'Q', 'k', or 'q'",
+                        "character must be either 'K', 'Q', 'k', 'q', or a file letter",
                     ));
                 }
-            }
+            };
+
+            self.castle_rights |= right;
+            self.castle_files[right.index()] = file;
         }
         Ok(())
     }
@@ -194,13 +341,15 @@ impl Board {
             return Ok(());
         }
 
-        let idx = square_name_to_idx(en_passant).ok_or(FenParseErr::invalid(
-            FenPart::EnPassant,
-            0,
-            "the en passant section must be a board position or a '-'",
-        ))?;
+        let square = Square::from_str(en_passant).map_err(|_| {
+            FenParseErr::invalid(
+                FenPart::EnPassant,
+                0,
+                "the en passant section must be a board position or a '-'",
+            )
+        })?;
 
-        self.en_passant = Some(idx);
+        self.en_passant = Some(square.idx());
         Ok(())
     }
 
@@ -226,21 +375,113 @@ impl Board {
         Ok(())
     }
 
+    fn parse_checks(&mut self, checks: Option<&str>) -> Result<(), FenParseErr> {
+        let Some(checks) = checks else {
+            self.set_checks_given(0, 0);
+            return Ok(());
+        };
+        if self.variant != Variant::ThreeCheck {
+            return Err(FenParseErr::TooManyComponents);
+        }
+
+        let invalid = || {
+            FenParseErr::invalid(
+                FenPart::CheckCount,
+                0,
+                "the check count should be like '+1+0', with at most 3 checks each",
+            )
+        };
+        let (white, black) = checks
+            .strip_prefix('+')
+            .and_then(|checks| checks.split_once('+'))
+            .ok_or_else(invalid)?;
+        let white = white.parse::<u8>().map_err(|_| invalid())?;
+        let black = black.parse::<u8>().map_err(|_| invalid())?;
+        if white > THREE_CHECK_LIMIT || black > THREE_CHECK_LIMIT {
+            return Err(invalid());
+        }
+
+        self.set_checks_given(white, black);
+        Ok(())
+    }
+
+    /// Gets the fen of the position.
+    ///
+    /// In chess960 mode the castle rights are written in X-FEN,
+    ///     using the rook's file only when `K` or `Q` would be ambiguous.
     pub fn get_fen(&self) -> String {
-        let piece_placement = self.get_fen_piece_placement();
+        let castle_rights = match self.chess960 {
+            true => self.get_fen_castle_files(false),
+            false => self.castle_rights.to_fen(),
+        };
+        self.get_fen_with_castling(castle_rights)
+    }
+
+    /// Gets the fen of the position, with the castle rights in Shredder-FEN,
+    ///     which always uses the rook's file, ex. `HAha`.
+    pub fn get_shredder_fen(&self) -> String {
+        self.get_fen_with_castling(self.get_fen_castle_files(true))
+    }
+
+    fn get_fen_with_castling(&self, castle_rights: String) -> String {
+        let mut piece_placement = self.get_fen_piece_placement();
+        if self.variant == Variant::Crazyhouse {
+            piece_placement += &self.get_fen_pocket();
+        }
         let active_color = match self.active_color {
             Player::White => 'w',
             Player::Black => 'b',
         };
-        let castle_rights = self.castle_rights.to_fen();
         let en_passant = self.get_fen_en_passant();
         let half_move = self.halfmove;
         let full_move = self.fullmove;
 
-        format!(
+        let mut fen = format!(
             "{} {} {} {} {} {}",
             piece_placement, active_color, castle_rights, en_passant, half_move, full_move
-        )
+        );
+        if self.variant == Variant::ThreeCheck {
+            fen += &format!(" +{}+{}", self.checks[0], self.checks[1]);
+        }
+        fen
+    }
+
+    fn get_fen_castle_files(&self, shredder: bool) -> String {
+        let mut output = String::new();
+
+        for right in CastleRights::EACH {
+            if !self.castle_rights.has_right(right) {
+                continue;
+            }
+
+            let player = right.player();
+            let rank = back_rank(player);
+            let file = self.castle_files[right.index()];
+            let rook = Piece::Rook | player.to_piece_color();
+            let outer_files = match right.is_king_side() {
+                true => file + 1..8,
+                false => 0..file,
+            };
+            let is_outermost = !outer_files
+                .into_iter()
+                .any(|f| self.squares[rank * 8 + f] == rook);
+
+            let c = match (shredder || !is_outermost, right.is_king_side()) {
+                (true, _) => (b'a' + file as u8) as char,
+                (false, true) => 'k',
+                (false, false) => 'q',
+            };
+            match player {
+                Player::White => output.push(c.to_ascii_uppercase()),
+                Player::Black => output.push(c),
+            }
+        }
+
+        if output.is_empty() {
+            output += "-";
+        }
+
+        output
     }
 
     fn get_fen_piece_placement(&self) -> String {
@@ -271,6 +512,9 @@ impl Board {
                     if piece.is_color(Player::White) {
                         piece_name = piece_name.to_uppercase();
                     }
+                    if self.is_promoted(Square::from_idx(rank * 8 + file)) {
+                        piece_name += "~";
+                    }
 
                     output += &piece_name;
                 }
@@ -286,10 +530,288 @@ impl Board {
         output
     }
 
+    /// Gets the pocket of a crazyhouse position, ex. `[Qn]`.
+    pub(crate) fn get_fen_pocket(&self) -> String {
+        let mut output = "[".to_owned();
+        for player in [Player::White, Player::Black] {
+            for piece in POCKET_PIECES.iter().rev() {
+                let letter = match *piece {
+                    Piece::Queen => 'q',
+                    Piece::Rook => 'r',
+                    Piece::Bishop => 'b',
+                    Piece::Knight => 'n',
+                    _ => 'p',
+                };
+                let letter = match player {
+                    Player::White => letter.to_ascii_uppercase(),
+                    Player::Black => letter,
+                };
+                for _ in 0..self.pocket_count(player, *piece) {
+                    output.push(letter);
+                }
+            }
+        }
+        output + "]"
+    }
+
     fn get_fen_en_passant(&self) -> String {
-        self.en_passant
-            .map(|pos| idx_to_square_name(pos))
-            .flatten()
+        self.en_passant()
+            .map(|square| square.to_string())
             .unwrap_or("-".to_owned())
     }
 }
+
+impl Board {
+    /// Loads an EPD record. The first four fields are the same as a fen.
+    ///
+    /// SAN operands of `bm` and `am` are resolved against the position.
+    /// The halfmove and fullmove counters are taken from the `hmvc` and `fmvn`
+    ///     operations if present, and are otherwise reset to `0` and `1`.
+    pub fn load_epd(&mut self, epd: &str) -> Result<Vec<EpdOperation>, FenParseErr> {
+        // the board is only changed once the whole record is read and valid
+        let mut board = *self;
+        let operations = board.parse_epd(epd)?;
+        *self = board;
+        Ok(operations)
+    }
+
+    fn parse_epd(&mut self, epd: &str) -> Result<Vec<EpdOperation>, FenParseErr> {
+        use FenPart::*;
+
+        // the fields may be separated by any amount of whitespace
+        let mut rest = epd.trim();
+        let mut next_field = |part| {
+            let (field, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            rest = after.trim_start();
+            match field {
+                "" => Err(FenParseErr::missing(part)),
+                field => Ok(field),
+            }
+        };
+        let board = next_field(Board)?;
+        let active_color = next_field(ActiveColor)?;
+        let castling = next_field(CastleRights)?;
+        let en_passant = next_field(EnPassant)?;
+        let operations = rest;
+
+        self.parse_board(board)?;
+        self.parse_active_color(active_color)?;
+        self.parse_castling(castling)?;
+        self.parse_en_passant(en_passant)?;
+        self.halfmove = 0;
+        self.fullmove = 1;
+        self.set_checks_given(0, 0);
+        self.validate().map_err(FenParseErr::InvalidPosition)?;
+
+        let mut parsed = vec![];
+        for (char_idx, opcode, operands) in split_epd_operations(operations)? {
+            let operation = match opcode.as_str() {
+                "bm" | "am" => {
+                    let moves = operands
+                        .iter()
+                        .map(|san| self.parse_san(san))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| {
+                            FenParseErr::invalid(Operations, char_idx, "invalid SAN move")
+                        })?;
+                    if opcode == "bm" {
+                        EpdOperation::BestMoves(moves)
+                    } else {
+                        EpdOperation::AvoidMoves(moves)
+                    }
+                }
+                "id" => EpdOperation::Id(single_operand(&operands, char_idx)?.to_owned()),
+                "dm" => EpdOperation::DirectMate(
+                    single_operand(&operands, char_idx)?
+                        .parse()
+                        .map_err(|_| FenParseErr::invalid(Operations, char_idx, "invalid dm"))?,
+                ),
+                "ce" => EpdOperation::CentipawnEval(
+                    single_operand(&operands, char_idx)?
+                        .parse()
+                        .map_err(|_| FenParseErr::invalid(Operations, char_idx, "invalid ce"))?,
+                ),
+                "hmvc" => {
+                    self.parse_halfmove(single_operand(&operands, char_idx)?)?;
+                    continue;
+                }
+                "fmvn" => {
+                    self.parse_fullmove(single_operand(&operands, char_idx)?)?;
+                    continue;
+                }
+                _ => match opcode.strip_prefix('c').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if n <= 9 && opcode.len() == 2 => {
+                        EpdOperation::Comment(n, single_operand(&operands, char_idx)?.to_owned())
+                    }
+                    _ => EpdOperation::Other { opcode, operands },
+                },
+            };
+            parsed.push(operation);
+        }
+
+        Ok(parsed)
+    }
+
+    /// Gets the EPD record for this position with the given operations.
+    ///
+    /// The halfmove and fullmove counters are written as the `hmvc` and `fmvn` operations
+    ///     after the others, unless they are `0` and `1`.
+    pub fn get_epd(&self, operations: &[EpdOperation]) -> String {
+        let fen = self.get_fen();
+        let mut output = fen.splitn(5, ' ').take(4).collect::<Vec<_>>().join(" ");
+
+        for operation in operations {
+            let (opcode, operands) = match operation {
+                EpdOperation::BestMoves(moves) => ("bm".to_owned(), self.epd_san_list(moves)),
+                EpdOperation::AvoidMoves(moves) => ("am".to_owned(), self.epd_san_list(moves)),
+                EpdOperation::Id(id) => ("id".to_owned(), vec![quote_epd_string(id)]),
+                EpdOperation::DirectMate(n) => ("dm".to_owned(), vec![n.to_string()]),
+                EpdOperation::CentipawnEval(ce) => ("ce".to_owned(), vec![ce.to_string()]),
+                EpdOperation::Comment(n, comment) => {
+                    (format!("c{}", n), vec![quote_epd_string(comment)])
+                }
+                EpdOperation::Other { opcode, operands } => (
+                    opcode.clone(),
+                    operands.iter().map(|o| quote_epd_operand(o)).collect(),
+                ),
+            };
+
+            output += " ";
+            output += &opcode;
+            for operand in operands {
+                output += " ";
+                output += &operand;
+            }
+            output += ";";
+        }
+        if self.halfmove != 0 {
+            output += &format!(" hmvc {};", self.halfmove);
+        }
+        if self.fullmove != 1 {
+            output += &format!(" fmvn {};", self.fullmove);
+        }
+
+        output
+    }
+
+    fn epd_san_list(&self, moves: &[Move]) -> Vec<String> {
+        moves.iter().map(|m| self.move_to_san(*m)).collect()
+    }
+}
+
+/// Splits the pocket off of the piece placement, if there is one.
+fn split_pocket(board: &str) -> (&str, Option<&str>) {
+    if let Some(board) = board.strip_suffix(']') {
+        if let Some((placement, pocket)) = board.split_once('[') {
+            return (placement, Some(pocket));
+        }
+    }
+    if board.matches('/').count() == 8 {
+        if let Some((placement, pocket)) = board.rsplit_once('/') {
+            return (placement, Some(pocket));
+        }
+    }
+    (board, None)
+}
+
+fn single_operand(operands: &[String], char_idx: usize) -> Result<&str, FenParseErr> {
+    match operands {
+        [operand] => Ok(operand),
+        _ => Err(FenParseErr::invalid(
+            FenPart::Operations,
+            char_idx,
+            "expected exactly one operand",
+        )),
+    }
+}
+
+/// Quotes a string operand, escaping any quotes and backslashes in it with a backslash.
+fn quote_epd_string(operand: &str) -> String {
+    let escaped = operand.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+/// Quotes an operand if it is not a single word.
+fn quote_epd_operand(operand: &str) -> String {
+    if operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';' || c == '"') {
+        quote_epd_string(operand)
+    } else {
+        operand.to_owned()
+    }
+}
+
+/// Splits the operations of an EPD record into the char index, opcode and operands of each.
+fn split_epd_operations(
+    operations: &str,
+) -> Result<Vec<(usize, String, Vec<String>)>, FenParseErr> {
+    let mut output = vec![];
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut op_start = 0;
+    let mut chars = operations.char_indices();
+
+    while let Some((char_idx, c)) = chars.next() {
+        match c {
+            '"' => {
+                // quoted strings are a single operand, and may contain spaces and semicolons.
+                //     Quotes and backslashes inside them are escaped with a backslash.
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => word.push(c),
+                            None => {
+                                return Err(FenParseErr::invalid(
+                                    FenPart::Operations,
+                                    char_idx,
+                                    "unterminated string operand",
+                                ))
+                            }
+                        },
+                        Some((_, c)) => word.push(c),
+                        None => {
+                            return Err(FenParseErr::invalid(
+                                FenPart::Operations,
+                                char_idx,
+                                "unterminated string operand",
+                            ))
+                        }
+                    }
+                }
+                words.push(std::mem::take(&mut word));
+            }
+            ';' => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                if words.is_empty() {
+                    return Err(FenParseErr::invalid(
+                        FenPart::Operations,
+                        char_idx,
+                        "empty operation",
+                    ));
+                }
+                let opcode = words.remove(0);
+                output.push((op_start, opcode, std::mem::take(&mut words)));
+                op_start = char_idx + 1;
+            }
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+
+    // the last operation may be missing its semicolon
+    if !word.is_empty() {
+        words.push(word);
+    }
+    if !words.is_empty() {
+        let opcode = words.remove(0);
+        output.push((op_start, opcode, words));
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,285 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::{
+    Board, Clock, GameResult, InvalidMoveErr, Move, PgnGame, PgnMove, PgnParseErr, Piece, Player,
+    SanParseErr, TimeSource, Variant,
+};
+
+/// Hashes a position for finding repetitions. Along with the pieces, this includes
+///     the state that some variants have, like the pockets in crazyhouse.
+fn repetition_key(board: &Board) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (board.checks, board.pockets, board.promoted).hash(&mut hasher);
+    board.polyglot_key() ^ hasher.finish()
+}
+
+/// A move that was played in a [`Game`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayedMove {
+    pub umove: Move,
+    /// The move in Standard Algebraic Notation, from the position it was played in.
+    pub san: String,
+    /// The piece that the move captured, if any.
+    pub captured: Option<Piece>,
+}
+
+/// Whether a game is still going, and why it ended if not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameStatus {
+    Ongoing,
+    /// The given player won by checkmate.
+    Checkmate(Player),
+    Stalemate,
+    /// The same position came up for the third time.
+    ThreefoldRepetition,
+    /// 50 moves by each player went by without a capture or pawn move.
+    FiftyMoveRule,
+    /// The given player won because the other ran out of time.
+    Timeout(Player),
+    /// A player ran out of time, but the other could not have checkmated them.
+    TimeoutVsInsufficientMaterial,
+    /// Neither player can checkmate the other, see [`Board::is_insufficient_material`].
+    InsufficientMaterial,
+    /// The given player won by a rule of the [`Variant`](crate::Variant),
+    ///     ex. reaching the center in King of the Hill.
+    VariantWin(Player),
+    /// The game was drawn by a rule of the variant.
+    VariantDraw,
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    pub fn result(&self) -> GameResult {
+        match self {
+            GameStatus::Ongoing => GameResult::Ongoing,
+            GameStatus::Checkmate(winner)
+            | GameStatus::Timeout(winner)
+            | GameStatus::VariantWin(winner) => GameResult::win_for(*winner),
+            _ => GameResult::Draw,
+        }
+    }
+}
+
+/// A game of chess: the starting position and the moves played from it.
+///
+/// Moves can be undone and redone. Playing a new move after undoing discards the moves that
+///     could have been redone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    /// The position after each move, starting with the starting position.
+    positions: Vec<Board>,
+    /// The hashes of `positions`, for finding repetitions.
+    hashes: Vec<u64>,
+    moves: Vec<PlayedMove>,
+    /// The moves that were undone, with the most recently undone last.
+    pub(crate) undone: Vec<PlayedMove>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// Starts a game from the standard starting position.
+    pub fn new() -> Self {
+        Game::from_board(Board::new())
+    }
+
+    /// Starts a game from the given position.
+    pub fn from_board(board: Board) -> Self {
+        Game {
+            positions: vec![board],
+            hashes: vec![repetition_key(&board)],
+            moves: vec![],
+            undone: vec![],
+        }
+    }
+
+    /// Replays the main line of a PGN game.
+    pub fn from_pgn(pgn: &PgnGame) -> Result<Self, PgnParseErr> {
+        let mut game = Game::from_board(pgn.starting_board()?);
+        for (ply, pgn_move) in pgn.moves.iter().enumerate() {
+            game.play(pgn_move.umove)
+                .map_err(|err| pgn_move.illegal(ply, err))?;
+        }
+        Ok(game)
+    }
+
+    /// Gets the current position.
+    pub fn board(&self) -> &Board {
+        self.positions
+            .last()
+            .expect("there is always a starting position")
+    }
+
+    pub fn starting_board(&self) -> &Board {
+        &self.positions[0]
+    }
+
+    /// Gets the moves that led to the current position.
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves
+    }
+
+    /// Plays a move, if it is legal.
+    pub fn play(&mut self, umove: Move) -> Result<(), InvalidMoveErr> {
+        let board = *self.board();
+        let umove = board.resolve_move(umove)?;
+        let mut next = board;
+        next.make_move_unchecked(umove)?;
+
+        let captured = match board.is_capture(umove) {
+            true if board.piece_at_idx(umove.target_idx()) == Piece::empty() => {
+                Some(Piece::Pawn | (!board.active_color()).to_piece_color())
+            }
+            true => Some(board.piece_at_idx(umove.target_idx())),
+            false => None,
+        };
+        let played = PlayedMove {
+            umove,
+            san: board.move_to_san(umove),
+            captured,
+        };
+
+        // replaying the next undone move keeps the rest of them
+        match self.undone.last() {
+            Some(next_undone) if next_undone.umove == umove => {
+                self.undone.pop();
+            }
+            _ => self.undone.clear(),
+        }
+
+        self.positions.push(next);
+        self.hashes.push(repetition_key(&next));
+        self.moves.push(played);
+        Ok(())
+    }
+
+    /// Plays a move given in Standard Algebraic Notation.
+    pub fn play_san(&mut self, san: &str) -> Result<(), SanParseErr> {
+        let umove = self.board().parse_san(san)?;
+        self.play(umove)
+            .expect("a move parsed from SAN should be legal");
+        Ok(())
+    }
+
+    /// Takes back the last move. Returns the move, or `None` if there are no moves to undo.
+    pub fn undo(&mut self) -> Option<Move> {
+        let played = self.moves.pop()?;
+        self.positions.pop();
+        self.hashes.pop();
+        let umove = played.umove;
+        self.undone.push(played);
+        Some(umove)
+    }
+
+    /// Plays the last move that was undone again.
+    pub fn redo(&mut self) -> Option<Move> {
+        let umove = self.undone.last()?.umove;
+        self.play(umove).expect("an undone move should be legal");
+        Some(umove)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.moves.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Counts how many times the current position has come up, including now.
+    ///
+    /// Only positions since the last capture or pawn move can repeat.
+    pub fn repetitions(&self) -> usize {
+        let current = *self.hashes.last().unwrap();
+        let reversible = self.board().halfmove() as usize;
+        self.hashes
+            .iter()
+            .rev()
+            .take(reversible + 1)
+            .step_by(2)
+            .filter(|hash| **hash == current)
+            .count()
+    }
+
+    pub fn status(&self) -> GameStatus {
+        let board = self.board();
+        match board.variant_result() {
+            GameResult::Ongoing => {}
+            GameResult::WhiteWins => return GameStatus::VariantWin(Player::White),
+            GameResult::BlackWins => return GameStatus::VariantWin(Player::Black),
+            GameResult::Draw => return GameStatus::VariantDraw,
+        }
+
+        if board.with_moves(|moves| moves.is_empty()) {
+            return match board.is_in_check(board.active_color()) {
+                true => GameStatus::Checkmate(!board.active_color()),
+                false => GameStatus::Stalemate,
+            };
+        }
+
+        if board.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if self.repetitions() >= 3 {
+            GameStatus::ThreefoldRepetition
+        } else if board.halfmove() >= 100 {
+            GameStatus::FiftyMoveRule
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    /// Gets the status of the game, including running out of time on the clock.
+    pub fn status_with_clock<T: TimeSource>(&self, clock: &Clock<T>) -> GameStatus {
+        let status = self.status();
+        if status.is_over() {
+            return status;
+        }
+
+        match clock.flagged() {
+            Some(player) if !self.board().has_mating_material(!player) => {
+                GameStatus::TimeoutVsInsufficientMaterial
+            }
+            Some(player) => GameStatus::Timeout(!player),
+            None => GameStatus::Ongoing,
+        }
+    }
+
+    /// Converts the game to a [`PgnGame`], with the tags needed for a custom starting
+    ///     position and the result from [`Game::status`].
+    pub fn to_pgn_game(&self) -> PgnGame {
+        let mut pgn = PgnGame::default();
+
+        let start = self.starting_board();
+        if start.get_fen() != Board::new_variant(start.variant()).get_fen() {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &start.get_fen());
+        }
+        if start.variant() != Variant::Standard {
+            pgn.set_tag("Variant", start.variant().pgn_name());
+        } else if start.is_chess960() {
+            pgn.set_tag("Variant", "Chess960");
+        }
+
+        pgn.moves = self
+            .moves
+            .iter()
+            .map(|m| PgnMove::new(m.umove, m.san.clone()))
+            .collect();
+        pgn.result = self.status().result();
+        pgn
+    }
+
+    /// Writes the game in PGN export format.
+    pub fn to_pgn(&self) -> String {
+        self.to_pgn_game().to_pgn()
+    }
+}
@@ -1,9 +1,193 @@
-use crate::{squares_to_edge, Board, Direction, Piece, Player};
+use crate::{
+    square_distance, squares_to_edge, Board, Direction, Move, Piece, Player, Square, SquareSet,
+    Variant,
+};
+
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, 1),
+    (-1, 2),
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+];
+
+/// The pieces that are the only piece between a king and a slider that would attack it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pins {
+    pieces: SquareSet,
+    /// For each square, the squares that its piece can move to while staying on its pin ray.
+    ///
+    /// This is every square for pieces that aren't pinned.
+    rays: [SquareSet; 64],
+}
+
+impl Pins {
+    const NONE: Pins = Pins {
+        pieces: SquareSet::EMPTY,
+        rays: [SquareSet::FULL; 64],
+    };
+
+    /// The squares of the pinned pieces.
+    pub fn pieces(&self) -> SquareSet {
+        self.pieces
+    }
+
+    /// Gets the pin ray of the piece on the square, if it is pinned: the squares between the
+    ///     king and the pinning piece, and the pinning piece itself.
+    pub fn ray(&self, square: Square) -> Option<SquareSet> {
+        match self.pieces.contains(square) {
+            true => Some(self.rays[square.idx()]),
+            false => None,
+        }
+    }
+
+    /// The squares that the piece on the square can move to without leaving its pin ray.
+    pub(crate) fn allowed(&self, idx: usize) -> SquareSet {
+        self.rays[idx]
+    }
+}
+
+fn is_diagonal(dir: Direction) -> bool {
+    Direction::BISHOP.contains(&dir)
+}
+
+/// Checks if the piece attacks along a line in the direction.
+fn slides_along(piece: Piece, dir: Direction) -> bool {
+    match piece & Piece::PieceType {
+        Piece::Queen => true,
+        Piece::Rook => !is_diagonal(dir),
+        Piece::Bishop => is_diagonal(dir),
+        _ => false,
+    }
+}
+
+/// Gets the square a knight jump away, if it is on the board.
+fn knight_target(start: usize, dx: isize, dy: isize) -> Option<usize> {
+    let x_dir = if dx > 0 {
+        Direction::East
+    } else {
+        Direction::West
+    };
+    let y_dir = if dy > 0 {
+        Direction::North
+    } else {
+        Direction::South
+    };
+
+    let in_bounds = squares_to_edge(start, x_dir) >= dx.unsigned_abs()
+        && squares_to_edge(start, y_dir) >= dy.unsigned_abs();
+    in_bounds.then(|| (start as isize + (dy * 8) + dx) as usize)
+}
+
+/// Gets the squares that a piece on the square attacks, when only the `occupied` squares
+///     block sliding pieces.
+pub(crate) fn attacks_from(piece: Piece, start: usize, occupied: SquareSet) -> SquareSet {
+    let mut attacks = SquareSet::EMPTY;
+    match piece & Piece::PieceType {
+        Piece::Knight => {
+            for (dx, dy) in KNIGHT_MOVES {
+                if let Some(target) = knight_target(start, dx, dy) {
+                    attacks.insert(Square::from_idx(target));
+                }
+            }
+        }
+        Piece::King => {
+            for dir in Direction::ALL {
+                if squares_to_edge(start, dir) >= 1 {
+                    attacks.insert(Square::from_idx((start as isize + dir.offset()) as usize));
+                }
+            }
+        }
+        Piece::Pawn => {
+            let player = match piece.is_color(Player::White) {
+                true => Player::White,
+                false => Player::Black,
+            };
+            if squares_to_edge(start, player.forward_dir()) < 1 {
+                return attacks;
+            }
+            let forward = start as isize + player.forward_value() * 8;
+            for (side, offset) in [(Direction::West, -1), (Direction::East, 1)] {
+                if squares_to_edge(start, side) >= 1 {
+                    attacks.insert(Square::from_idx((forward + offset) as usize));
+                }
+            }
+        }
+        _ => {
+            for dir in Direction::ALL
+                .into_iter()
+                .filter(|d| slides_along(piece, *d))
+            {
+                for n in 1..=squares_to_edge(start, dir) as isize {
+                    let target = Square::from_idx((start as isize + dir.offset() * n) as usize);
+                    attacks.insert(target);
+                    if occupied.contains(target) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    attacks
+}
+
+/// Gets the direction of the line from one square through another, if they are on one.
+fn direction_to(from: usize, to: usize) -> Option<Direction> {
+    Direction::ALL.into_iter().find(|dir| {
+        (1..=squares_to_edge(from, *dir) as isize)
+            .any(|n| from as isize + dir.offset() * n == to as isize)
+    })
+}
+
+/// Gets the squares from a square to the edge of the board, in the direction of another square.
+fn ray_through(from: usize, to: usize) -> SquareSet {
+    let Some(dir) = direction_to(from, to) else {
+        return SquareSet::EMPTY;
+    };
+    (1..=squares_to_edge(from, dir) as isize)
+        .map(|n| Square::from_idx((from as isize + dir.offset() * n) as usize))
+        .collect()
+}
+
+/// Gets the squares strictly between two squares on the same line,
+///     or no squares if they are not on one.
+pub(crate) fn between(from: usize, to: usize) -> SquareSet {
+    let Some(dir) = direction_to(from, to) else {
+        return SquareSet::EMPTY;
+    };
+    (1..)
+        .map(|n| (from as isize + dir.offset() * n) as usize)
+        .take_while(|idx| *idx != to)
+        .map(Square::from_idx)
+        .collect()
+}
 
 impl Board {
+    /// Checks if the player's king is attacked.
+    ///
+    /// A player without a king, like the horde in Horde, is never in check.
+    /// There is no check in antichess. In atomic chess, a king is not in check while it is
+    ///     next to the other king, as capturing it would explode both.
     pub fn is_in_check(&self, player: Player) -> bool {
-        let king_pos = self.find_king(player);
-        self.generate_attacks(player)[king_pos]
+        match self.variant {
+            Variant::Antichess => false,
+            Variant::Atomic => {
+                let (Some(king), Some(other_king)) =
+                    (self.king_square(player), self.king_square(!player))
+                else {
+                    return false;
+                };
+                square_distance(king.idx(), other_king.idx()) > 1
+                    && self.generate_attacks(!player)[king.idx()]
+            }
+            _ => match self.king_square(player) {
+                Some(king) => self.generate_attacks(!player)[king.idx()],
+                None => false,
+            },
+        }
     }
 
     /// Gets the squares attacked by the given player
@@ -12,19 +196,174 @@ impl Board {
 
         for (idx, piece) in self.squares.iter().enumerate() {
             if piece.is_color(player) {
-                self.get_attacked_squares_for(&mut map, player, idx)
+                self.get_attacked_squares_for(&mut map, player, Square::from_idx(idx))
             }
         }
 
         map
     }
 
-    pub fn get_attacked_squares_for<'b, 'v>(
-        &'b self,
-        map: &'v mut [bool; 64],
-        player: Player,
-        idx: usize,
-    ) {
+    /// Gets the squares of the player's pieces that attack the square,
+    ///     whether or not they could legally capture on it.
+    pub fn attackers_to(&self, square: Square, player: Player) -> SquareSet {
+        let idx = square.idx();
+        let color = player.to_piece_color();
+        let mut attackers = SquareSet::EMPTY;
+
+        // look along each line for the first piece, which attacks if it slides that way
+        for dir in Direction::ALL {
+            for n in 1..=squares_to_edge(idx, dir) as isize {
+                let target = (idx as isize + dir.offset() * n) as usize;
+                let piece = self.squares[target];
+                if piece == Piece::empty() {
+                    continue;
+                }
+                if piece.is_color(player) && slides_along(piece, dir) {
+                    attackers.insert(Square::from_idx(target));
+                }
+                break;
+            }
+        }
+
+        // the other pieces attack the square from where they would be attacked from it,
+        //     with pawns looking backwards
+        let occupied = self.occupied();
+        for (attacked_by, attacker) in [
+            (Piece::Knight, Piece::Knight | color),
+            (Piece::King, Piece::King | color),
+            (
+                Piece::Pawn | (!player).to_piece_color(),
+                Piece::Pawn | color,
+            ),
+        ] {
+            for from in attacks_from(attacked_by, idx, occupied) {
+                if self.piece_on(from) == attacker {
+                    attackers.insert(from);
+                }
+            }
+        }
+
+        attackers
+    }
+
+    /// Gets the squares of the pieces giving check to the active player.
+    ///
+    /// Like [`Board::is_in_check`], this is empty in antichess, without a king,
+    ///     and in atomic chess while the kings are next to each other.
+    pub fn checkers(&self) -> SquareSet {
+        let player = self.active_color;
+        match self.king_square(player) {
+            Some(king) if self.is_in_check(player) => self.attackers_to(king, !player),
+            _ => SquareSet::EMPTY,
+        }
+    }
+
+    /// Finds the player's pieces that are pinned to their king by a slider of the other player,
+    ///     with the squares that each one can move to along its pin.
+    pub fn pinned(&self, player: Player) -> Pins {
+        match self.king_square(player) {
+            Some(king) => self.pins_on(king.idx(), !player, player),
+            None => Pins::NONE,
+        }
+    }
+
+    /// Finds the active player's pieces that would give a discovered check by moving
+    ///     off the line between one of their sliders and the other king.
+    pub fn discovered_check_candidates(&self) -> SquareSet {
+        let player = self.active_color;
+        match self.king_square(!player) {
+            Some(king) => self.pins_on(king.idx(), player, player).pieces,
+            None => SquareSet::EMPTY,
+        }
+    }
+
+    /// Finds the pieces of the `blocker` that are the only piece between the king
+    ///     and a slider of the `slider` player.
+    pub(crate) fn pins_on(&self, king: usize, slider: Player, blocker: Player) -> Pins {
+        let mut pins = Pins::NONE;
+        for dir in Direction::ALL {
+            let mut ray = SquareSet::EMPTY;
+            let mut pinned = None;
+            for n in 1..=squares_to_edge(king, dir) as isize {
+                let idx = (king as isize + dir.offset() * n) as usize;
+                let piece = self.squares[idx];
+                ray.insert(Square::from_idx(idx));
+                if piece == Piece::empty() {
+                    continue;
+                }
+
+                match pinned {
+                    None if piece.is_color(blocker) => pinned = Some(idx),
+                    Some(pinned) if piece.is_color(slider) && slides_along(piece, dir) => {
+                        pins.pieces.insert(Square::from_idx(pinned));
+                        pins.rays[pinned] = ray;
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        pins
+    }
+
+    /// Checks if a legal move puts the other player in check, without playing it.
+    ///
+    /// `discovered` is [`Board::discovered_check_candidates`], which only has to be found
+    ///     once for all the moves of a position.
+    /// Castles, en passant and moves in atomic chess change more of the board,
+    ///     so those are played out instead.
+    pub fn gives_check(&self, umove: Move, discovered: SquareSet) -> bool {
+        let player = self.active_color;
+        let Some(king) = self.king_square(!player) else {
+            return false;
+        };
+        match self.variant {
+            Variant::Antichess => return false,
+            Variant::Atomic => return self.move_gives_check(umove),
+            _ => {}
+        }
+
+        let from = umove.origin_idx();
+        let is_pawn = self.squares[from] & Piece::PieceType == Piece::Pawn;
+        let is_en_passant =
+            !umove.is_drop() && is_pawn && self.en_passant == Some(umove.target_idx());
+        if self.is_castle(umove) || is_en_passant {
+            return self.move_gives_check(umove);
+        }
+
+        let piece = match (umove.dropped(), umove.promote()) {
+            (Some(piece), _) | (None, Some(piece)) => piece | player.to_piece_color(),
+            (None, None) => self.squares[from],
+        };
+        let mut occupied = self.occupied();
+        occupied.remove(umove.from());
+        occupied.insert(umove.to());
+        if attacks_from(piece, umove.target_idx(), occupied).contains(king) {
+            return true;
+        }
+
+        // moving a piece off the line between a slider and the king uncovers the slider
+        !umove.is_drop()
+            && discovered.contains(umove.from())
+            && !ray_through(king.idx(), from).contains(umove.to())
+    }
+
+    /// Checks if a legal move puts the other player in check, by playing it.
+    fn move_gives_check(&self, umove: Move) -> bool {
+        let mut after = *self;
+        after.make_move_unchecked(umove).is_ok() && after.is_in_check(after.active_color)
+    }
+
+    /// The squares that have a piece on them.
+    pub(crate) fn occupied(&self) -> SquareSet {
+        Square::all()
+            .filter(|square| self.piece_on(*square) != Piece::empty())
+            .collect()
+    }
+
+    /// Marks the squares attacked by the player's piece on the square.
+    pub fn get_attacked_squares_for(&self, map: &mut [bool; 64], player: Player, square: Square) {
+        let idx = square.idx();
         let piece = self.piece_at_idx(idx);
 
         let piece_type = piece & Piece::PieceType;
@@ -73,35 +412,8 @@ impl Board {
     }
 
     fn get_attacked_knight(&self, map: &mut [bool; 64], player: Player, start: usize) {
-        const KNIGHT_MOVES: [(isize, isize); 8] = [
-            (-2, 1),
-            (-1, 2),
-            (1, 2),
-            (2, 1),
-            (2, -1),
-            (1, -2),
-            (-1, -2),
-            (-2, -1),
-        ];
-
-        for (dx, dy) in KNIGHT_MOVES.iter() {
-            let x_dir = if *dx > 0 {
-                Direction::East
-            } else {
-                Direction::West
-            };
-
-            let y_dir = if *dy > 0 {
-                Direction::North
-            } else {
-                Direction::South
-            };
-
-            if squares_to_edge(start, x_dir) >= dx.abs() as usize
-                && squares_to_edge(start, y_dir) >= dy.abs() as usize
-            {
-                // target square is in bounds.
-                let target = (start as isize + (dy * 8) + dx) as usize;
+        for (dx, dy) in KNIGHT_MOVES {
+            if let Some(target) = knight_target(start, dx, dy) {
                 if !self.piece_at_idx(target).is_color(player) {
                     map[target] = true;
                 }
@@ -1,22 +1,309 @@
-use log::debug;
+use crate::{castle_king_target, castle_rook_target, crazyhouse::POCKET_PIECES, Board};
+use crate::{
+    gen_attacks::{attacks_from, between},
+    squares_to_edge, CastleRights, CrazyhouseMoveList, Direction, Move, MoveList, Piece, Player,
+    Square, SquareSet, Variant,
+};
+
+/// The kinds of legal moves that [`Board::generate`] can add.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GenKind {
+    /// Every legal move, like [`Board::generate_moves`].
+    All,
+    /// Captures and promotions, for searching until the position is quiet.
+    Captures,
+    /// Every move that is not a capture or promotion, including castles and drops.
+    Quiets,
+    /// The quiet moves that give check.
+    QuietChecks,
+    /// Every legal move while in check, or no moves when not in check.
+    Evasions,
+}
+
+/// The pseudo-legal moves that the piece generators add, for one kind of move.
+#[derive(Clone, Copy, Debug)]
+struct Stage {
+    /// Adds moves onto the other player's pieces, and en passant.
+    captures: bool,
+    /// Adds moves onto empty squares that don't promote, including castles and drops.
+    quiets: bool,
+    /// Adds pawn pushes that promote.
+    promotions: bool,
+    /// The squares that pieces other than the king can move or be dropped to.
+    targets: SquareSet,
+    /// The squares that the king can move to.
+    king_targets: SquareSet,
+}
 
-use crate::Board;
-use crate::{squares_to_edge, CastleRights, Direction, Move, Piece, Player};
+impl Stage {
+    const ALL: Stage = Stage {
+        captures: true,
+        quiets: true,
+        promotions: true,
+        targets: SquareSet::FULL,
+        king_targets: SquareSet::FULL,
+    };
+
+    const CAPTURES: Stage = Stage {
+        quiets: false,
+        ..Stage::ALL
+    };
+
+    const QUIETS: Stage = Stage {
+        captures: false,
+        promotions: false,
+        ..Stage::ALL
+    };
+}
 
 impl Board {
-    pub fn generate_moves(&self) -> Vec<Move> {
-        let mut moves = Vec::new();
+    /// Replaces the moves in the list with the legal moves of a kind.
+    ///
+    /// Captures and quiets together are every legal move. Each kind only generates
+    ///     its own moves, so searching the captures first doesn't pay for the quiets.
+    pub fn generate<const N: usize>(&self, kind: GenKind, moves: &mut MoveList<N>) {
+        moves.clear();
+        if self.is_variant_end() {
+            return;
+        }
+
+        match kind {
+            GenKind::All => self.generate_moves_into(moves),
+            GenKind::Captures => {
+                self.generate_stage(moves, &Stage::CAPTURES);
+                // promoting without capturing is only allowed when there is no capture
+                if self.variant == Variant::Antichess && moves.iter().any(|m| m.is_capture()) {
+                    moves.retain(|m| m.is_capture());
+                }
+                self.retain_legal(moves);
+            }
+            GenKind::Quiets => {
+                if self.variant == Variant::Antichess && self.has_capture() {
+                    return;
+                }
+                self.generate_stage(moves, &Stage::QUIETS);
+                self.retain_legal(moves);
+            }
+            GenKind::QuietChecks => self.generate_quiet_checks(moves),
+            GenKind::Evasions => self.generate_evasions(moves),
+        }
+    }
+
+    /// Checks if the active player can capture, for the compulsory captures of antichess.
+    fn has_capture(&self) -> bool {
+        let mut captures: MoveList = MoveList::new();
+        self.generate_stage(&mut captures, &Stage::CAPTURES);
+        captures.iter().any(|m| m.is_capture())
+    }
+
+    /// Adds the legal moves out of check. The moves can only capture a single checker,
+    ///     block its line, or move the king.
+    fn generate_evasions<const N: usize>(&self, moves: &mut MoveList<N>) {
+        let checkers = self.checkers();
+        let Some(king) = self
+            .king_square(self.active_color)
+            .filter(|_| !checkers.is_empty())
+        else {
+            return;
+        };
+        // capturing next to the checker can also blow it up in atomic chess
+        if self.variant == Variant::Atomic {
+            self.generate_moves_into(moves);
+            return;
+        }
+
+        let targets = match checkers.first() {
+            Some(checker) if checkers.len() == 1 => between(king.idx(), checker.idx()) | checkers,
+            _ => SquareSet::EMPTY,
+        };
+        let stage = Stage {
+            targets,
+            ..Stage::ALL
+        };
+        self.generate_stage(moves, &stage);
+        self.retain_legal(moves);
+    }
+
+    /// Adds the legal quiet moves that give check. Only the moves that land where
+    ///     their piece would attack the other king, moves of pieces that uncover an attack,
+    ///     and castles can give check, so only those are checked with [`Board::gives_check`].
+    fn generate_quiet_checks<const N: usize>(&self, moves: &mut MoveList<N>) {
+        let player = self.active_color;
+        let Some(king) = self.king_square(!player) else {
+            return;
+        };
+        if self.variant == Variant::Antichess {
+            return;
+        }
+
+        let occupied = self.occupied();
+        let discovered = self.discovered_check_candidates();
+        let check_squares = |piece: Piece| match piece & Piece::PieceType {
+            Piece::King => SquareSet::EMPTY,
+            // the squares that a pawn attacks the king from are the ones it would attack
+            //     as a pawn of the other player
+            Piece::Pawn => attacks_from(
+                Piece::Pawn | (!player).to_piece_color(),
+                king.idx(),
+                occupied,
+            ),
+            piece => attacks_from(piece, king.idx(), occupied),
+        };
 
         for (idx, piece) in self.squares.iter().enumerate() {
-            if piece.is_color(self.active_color) {
-                self.get_moves_for(&mut moves, idx)
+            if !piece.is_color(player) {
+                continue;
             }
+            let stage = match discovered.contains(Square::from_idx(idx)) {
+                true => Stage::QUIETS,
+                // a king in atomic chess can also give check by stepping away from the other king
+                false if self.variant == Variant::Atomic => Stage {
+                    targets: check_squares(*piece),
+                    ..Stage::QUIETS
+                },
+                false => Stage {
+                    targets: check_squares(*piece),
+                    king_targets: SquareSet::EMPTY,
+                    ..Stage::QUIETS
+                },
+            };
+            self.generate_piece_moves(moves, idx, &stage);
+        }
+        if self.variant == Variant::Crazyhouse {
+            self.generate_drops_to(moves, check_squares);
         }
 
+        self.retain_legal(moves);
+        moves.retain(|m| self.gives_check(*m, discovered));
+    }
+
+    /// Generates the legal moves of the active player.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a crazyhouse position has more moves than a [`MoveList`] holds.
+    ///     [`Board::generate_moves_into`] can fill a [`CrazyhouseMoveList`] instead.
+    pub fn generate_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
         moves
     }
 
-    pub fn get_moves_for<'b, 'v>(&'b self, moves: &'v mut Vec<Move>, idx: usize) {
+    /// Replaces the moves in the list with the legal moves of the active player.
+    pub fn generate_moves_into<const N: usize>(&self, moves: &mut MoveList<N>) {
+        self.generate_pseudo_legal_into(moves);
+        self.retain_legal(moves);
+    }
+
+    /// Generates the moves of the active player without checking if they leave
+    ///     the king in check, which [`Board::is_legal`] can check for each move later.
+    ///
+    /// The other rules are still followed, like castling not passing through check,
+    ///     or captures being compulsory in antichess.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a crazyhouse position has more moves than a [`MoveList`] holds, like
+    ///     [`Board::generate_moves`].
+    pub fn generate_pseudo_legal(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.generate_pseudo_legal_into(&mut moves);
+        moves
+    }
+
+    /// Calls the function with the legal moves of the active player, in a list with room
+    ///     for the moves of the variant, so that only crazyhouse pays for the larger list.
+    pub(crate) fn with_moves<T>(&self, f: impl FnOnce(&[Move]) -> T) -> T {
+        match self.variant {
+            Variant::Crazyhouse => {
+                let mut moves = CrazyhouseMoveList::default();
+                self.generate_moves_into(&mut moves);
+                f(&moves)
+            }
+            _ => f(&self.generate_moves()),
+        }
+    }
+
+    /// Replaces the moves in the list with the pseudo-legal moves of the active player,
+    ///     like [`Board::generate_pseudo_legal`].
+    pub fn generate_pseudo_legal_into<const N: usize>(&self, moves: &mut MoveList<N>) {
+        moves.clear();
+        if self.is_variant_end() {
+            return;
+        }
+
+        self.generate_stage(moves, &Stage::ALL);
+
+        // captures are compulsory in antichess
+        if self.variant == Variant::Antichess && moves.iter().any(|m| self.is_capture(*m)) {
+            moves.retain(|m| self.is_capture(*m));
+        }
+    }
+
+    /// Adds the pseudo-legal moves of the stage, for every piece of the active player.
+    fn generate_stage<const N: usize>(&self, moves: &mut MoveList<N>, stage: &Stage) {
+        for (idx, piece) in self.squares.iter().enumerate() {
+            if piece.is_color(self.active_color) {
+                self.generate_piece_moves(moves, idx, stage)
+            }
+        }
+        if self.variant == Variant::Crazyhouse && stage.quiets {
+            self.generate_drops_to(moves, |_| stage.targets);
+        }
+    }
+
+    /// Counts the number of leaf nodes of the legal move tree to the given depth.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        self.with_moves(|moves| {
+            if depth == 1 {
+                return moves.len() as u64;
+            }
+
+            moves
+                .iter()
+                .map(|umove| {
+                    let mut board = *self;
+                    board
+                        .make_move_unchecked(*umove)
+                        .expect("a generated move should be playable");
+                    board.perft(depth - 1)
+                })
+                .sum()
+        })
+    }
+
+    /// Adds the legal moves of the piece on the given square.
+    pub fn get_moves_for<const N: usize>(&self, moves: &mut MoveList<N>, square: Square) {
+        let idx = square.idx();
+        if self.is_variant_end() {
+            return;
+        }
+        if self.variant == Variant::Antichess {
+            // whether captures are compulsory depends on the other pieces
+            moves.extend(
+                self.generate_moves()
+                    .into_iter()
+                    .filter(|m| m.origin_idx() == idx),
+            );
+            return;
+        }
+
+        let mut piece_moves: MoveList = MoveList::new();
+        self.generate_piece_moves(&mut piece_moves, idx, &Stage::ALL);
+        self.retain_legal(&mut piece_moves);
+        moves.extend(piece_moves);
+    }
+
+    fn generate_piece_moves<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        idx: usize,
+        stage: &Stage,
+    ) {
         let piece = self.piece_at_idx(idx);
 
         if !piece.is_color(self.active_color) {
@@ -25,17 +312,23 @@ impl Board {
 
         let piece_type = piece & Piece::PieceType;
         if piece.is_sliding() {
-            self.generate_sliding_moves(moves, idx, piece);
+            self.generate_sliding_moves(moves, idx, piece, stage);
         } else if piece_type == Piece::Knight {
-            self.generate_knight_moves(moves, idx);
+            self.generate_knight_moves(moves, idx, stage);
         } else if piece_type == Piece::Pawn {
-            self.generate_pawn_moves(moves, idx);
+            self.generate_pawn_moves(moves, idx, stage);
         } else if piece_type == Piece::King {
-            self.generate_king_moves(moves, idx);
+            self.generate_king_moves(moves, idx, stage);
         }
     }
 
-    fn generate_sliding_moves(&self, moves: &mut Vec<Move>, start: usize, piece: Piece) {
+    fn generate_sliding_moves<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        start: usize,
+        piece: Piece,
+        stage: &Stage,
+    ) {
         let directions = match piece & Piece::PieceType {
             Piece::Bishop => &Direction::BISHOP[..],
             Piece::Rook => &Direction::ROOK[..],
@@ -53,16 +346,26 @@ impl Board {
                     break;
                 }
 
-                self.add_move(moves, Move::from_idxs(start, target));
-
+                let allowed = stage.targets.contains(Square::from_idx(target));
                 if target_piece.is_color(!self.active_color) {
+                    if allowed && stage.captures {
+                        moves.push(Move::capture(start, target));
+                    }
                     break;
                 }
+                if allowed && stage.quiets {
+                    moves.push(Move::from_idxs(start, target));
+                }
             }
         }
     }
 
-    fn generate_knight_moves(&self, moves: &mut Vec<Move>, start: usize) {
+    fn generate_knight_moves<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        start: usize,
+        stage: &Stage,
+    ) {
         const KNIGHT_MOVES: [(isize, isize); 8] = [
             (-2, 1),
             (-1, 2),
@@ -87,39 +390,51 @@ impl Board {
                 Direction::South
             };
 
-            if squares_to_edge(start, x_dir) >= dx.abs() as usize
-                && squares_to_edge(start, y_dir) >= dy.abs() as usize
+            if squares_to_edge(start, x_dir) >= dx.unsigned_abs()
+                && squares_to_edge(start, y_dir) >= dy.unsigned_abs()
             {
                 // target square is in bounds.
                 let target = (start as isize + (dy * 8) + dx) as usize;
-                if !self.piece_at_idx(target).is_color(self.active_color) {
-                    self.add_move(moves, Move::from_idxs(start, target));
+                let target_piece = self.piece_at_idx(target);
+                if !stage.targets.contains(Square::from_idx(target)) {
+                    continue;
+                }
+                if target_piece.is_color(!self.active_color) && stage.captures {
+                    moves.push(Move::capture(start, target));
+                } else if target_piece == Piece::empty() && stage.quiets {
+                    moves.push(Move::from_idxs(start, target));
                 }
             }
         }
     }
 
-    fn generate_pawn_moves(&self, moves: &mut Vec<Move>, start: usize) {
+    fn generate_pawn_moves<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        start: usize,
+        stage: &Stage,
+    ) {
         if squares_to_edge(start, self.active_color.forward_dir()) < 1 {
             return;
         }
         let forward_target = (start as isize + self.active_color.forward_value() * 8) as usize;
 
         // pawns can take diagonally
-        if squares_to_edge(start, Direction::West) >= 1 {
-            let target = forward_target - 1;
-            if self.piece_at_idx(target).is_color(!self.active_color)
-                || self.en_passant == Some(target)
-            {
-                self.add_pawn_move(moves, Move::from_idxs(start, target))
+        for (side, target) in [
+            (Direction::West, forward_target.wrapping_sub(1)),
+            (Direction::East, forward_target + 1),
+        ] {
+            if squares_to_edge(start, side) < 1 || !stage.captures {
+                continue;
             }
-        }
-        if squares_to_edge(start, Direction::East) >= 1 {
-            let target = forward_target + 1;
-            if self.piece_at_idx(target).is_color(!self.active_color)
-                || self.en_passant == Some(target)
-            {
-                self.add_pawn_move(moves, Move::from_idxs(start, target))
+            if self.piece_at_idx(target).is_color(!self.active_color) {
+                if stage.targets.contains(Square::from_idx(target)) {
+                    self.add_pawn_move(moves, Move::capture(start, target))
+                }
+            } else if self.en_passant == Some(target) {
+                // the captured pawn is not on the target square,
+                //     so whether this gets out of check is left to the legality check
+                moves.push(Move::en_passant(start, target));
             }
         }
 
@@ -127,150 +442,168 @@ impl Board {
             return;
         }
 
-        self.add_pawn_move(moves, Move::from_idxs(start, forward_target));
+        let promotes = forward_target / 8 == 0 || forward_target / 8 == 7;
+        let pushes = match promotes {
+            true => stage.promotions,
+            false => stage.quiets,
+        };
+        if pushes && stage.targets.contains(Square::from_idx(forward_target)) {
+            self.add_pawn_move(moves, Move::from_idxs(start, forward_target));
+        }
 
         // if it is on the starting rank, it can move forward 2.
-        if (self.active_color == Player::White && start / 8 == 1)
-            || (self.active_color == Player::Black && start / 8 == 6)
+        // the horde's pawns on the back rank can as well.
+        let rank = start / 8;
+        if stage.quiets
+            && ((self.active_color == Player::White && rank <= 1)
+                || (self.active_color == Player::Black && rank >= 6))
         {
             let target = (start as isize + self.active_color.forward_value() * 16) as usize;
-            if self.piece_at_idx(target) == Piece::empty() {
+            if self.piece_at_idx(target) == Piece::empty()
+                && stage.targets.contains(Square::from_idx(target))
+            {
                 self.add_pawn_move(moves, Move::from_idxs(start, target))
             }
         }
     }
 
-    fn add_pawn_move(&self, moves: &mut Vec<Move>, umove: Move) {
-        let target_rank = umove.to / 8;
+    fn add_pawn_move<const N: usize>(&self, moves: &mut MoveList<N>, umove: Move) {
+        let target_rank = umove.target_idx() / 8;
         // if the pawn made it to the first or last rank, it needs to promote
         if target_rank == 0 || target_rank == 7 {
             for promote in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
-                self.add_move(moves, umove.with_promote(Some(promote)));
+                moves.push(umove.with_promote(Some(promote)));
+            }
+            if self.variant == Variant::Antichess {
+                moves.push(umove.with_promote(Some(Piece::King)));
             }
         } else {
-            self.add_move(moves, umove);
+            moves.push(umove);
         }
     }
 
-    fn generate_king_moves(&self, moves: &mut Vec<Move>, start: usize) {
+    fn generate_king_moves<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        start: usize,
+        stage: &Stage,
+    ) {
         for dir in Direction::ALL {
             if squares_to_edge(start, dir) >= 1 {
                 let target = (start as isize + dir.offset()) as usize;
                 let target_piece = self.piece_at_idx(target);
 
                 // Block by friendly
-                if target_piece.is_color(self.active_color) {
+                if target_piece.is_color(self.active_color)
+                    || !stage.king_targets.contains(Square::from_idx(target))
+                {
                     continue;
                 }
-                self.add_move(moves, Move::from_idxs(start, target));
+                // kings can't capture in atomic chess, as they would explode
+                if target_piece == Piece::empty() {
+                    if stage.quiets {
+                        moves.push(Move::from_idxs(start, target));
+                    }
+                } else if self.variant != Variant::Atomic && stage.captures {
+                    moves.push(Move::capture(start, target));
+                }
             }
         }
 
         // Castling
-        self.generate_king_castle_directions(moves, start, Direction::West);
-        self.generate_king_castle_directions(moves, start, Direction::East);
-    }
-
-    fn generate_king_castle_directions(&self, moves: &mut Vec<Move>, start: usize, dir: Direction) {
-        if cfg!(debug_assertions) {
-            assert!(dir == Direction::West || dir == Direction::East);
+        if stage.quiets {
+            self.generate_castles(moves, start);
         }
+    }
 
-        let (needed_castle_right, squares_in_between, king_move_range) = match (self.active_color, dir) {
-            (Player::White, Direction::West) => (CastleRights::WhiteQueenSide, -3..=-1, -2..=-1),
-            (Player::White, Direction::East) => (CastleRights::WhiteKingSide, 1..=2, 1..=2),
-            (Player::Black, Direction::West) => (CastleRights::BlackQueenSide, -3..=-1, -2..=-1),
-            (Player::Black, Direction::East) => (CastleRights::BlackKingSide, 1..=2, 1..=2),
-            _ => panic!("generate_king_castle_directions called with a direction other than `West` or `East`")
-        };
-
-        if !self.castle_rights.has_right(needed_castle_right) {
-            return;
-        }
+    fn generate_castles<const N: usize>(&self, moves: &mut MoveList<N>, king_square: usize) {
+        let player = self.active_color;
+        let mut attacked_squares = None;
 
-        if cfg!(debug_assertions) {
-            // since the player still has their castle rights,
-            //     we can do some extra checks in debug mode
-            println!("Fen: \"{}\"", self.get_fen());
-            if self.active_color == Player::White {
-                let rook_square = match needed_castle_right {
-                    CastleRights::WhiteKingSide => 7,
-                    CastleRights::WhiteQueenSide => 0,
-                    _ => panic!(
-                        "the needed castle right should be the same color as the active player"
-                    ),
-                };
-
-                // ensure that the king is on its starting square
-                assert_eq!(start, 4);
-                // ensure that the rook is still there
-                assert_eq!(self.squares[rook_square], Piece::White | Piece::Rook);
-            } else {
-                let rook_square = match needed_castle_right {
-                    CastleRights::BlackKingSide => 63,
-                    CastleRights::BlackQueenSide => 56,
-                    _ => panic!(
-                        "the needed castle right should be the same color as the active player"
-                    ),
-                };
-
-                // ensure that the king is on its starting square
-                assert_eq!(start, 60);
-                // ensure that the rook is still there
-                assert_eq!(
-                    self.squares[rook_square],
-                    Piece::Black | Piece::Rook,
-                    "Tried to castle {:?} when the piece was not a rook. Castle Rights: {:?}",
-                    needed_castle_right,
-                    self.castle_rights,
-                );
+        'sides: for is_east in [false, true] {
+            let right = CastleRights::for_side(player, is_east);
+            if !self.castle_rights.has_right(right) {
+                continue;
             }
-        }
-
-        let attacked_squares = self.generate_attacks(!self.active_color());
 
-        for i in king_move_range {
-            let idx = (start as isize + i) as usize;
-            if attacked_squares[idx] {
-                // the king may not move across an attacked square
-                //    when castling
-                return;
+            let rook_square = self.castle_rook_square(right).idx();
+            let king_target = castle_king_target(player, is_east);
+            let rook_target = castle_rook_target(player, is_east);
+
+            // every square that the king or rook move over or onto must be empty,
+            //     other than the castling king and rook themselves
+            let squares = [king_square, rook_square, king_target, rook_target];
+            let lowest = *squares.iter().min().unwrap();
+            let highest = *squares.iter().max().unwrap();
+            for idx in lowest..=highest {
+                if idx != king_square && idx != rook_square && self.squares[idx] != Piece::empty() {
+                    // a piece is in the way
+                    continue 'sides;
+                }
             }
-        }
 
-        for i in squares_in_between {
-            let idx = (start as isize + i) as usize;
-            if self.squares[idx] != Piece::empty() {
-                // a piece is in the way
-                return;
+            // the king may not castle out of, through, or into check
+            let mut king_path = king_square.min(king_target)..=king_square.max(king_target);
+            if self.variant == Variant::Atomic {
+                // the square the king ends on is checked with the legality of the move,
+                //     once the rook has moved
+                if king_path.any(|idx| {
+                    idx != king_target && self.is_atomic_king_path_attacked(idx, king_square)
+                }) {
+                    continue;
+                }
+            } else {
+                let attacked_squares =
+                    attacked_squares.get_or_insert_with(|| self.generate_attacks(!player));
+                if king_path.any(|idx| attacked_squares[idx]) {
+                    continue;
+                }
             }
-        }
-
-        let target = (start as isize + dir.offset() * 2) as usize;
-        let target_piece = self.piece_at_idx(target);
 
-        if target_piece != Piece::empty() {
-            // a piece is in the way
-            return;
+            let target = if self.chess960 {
+                rook_square
+            } else {
+                king_target
+            };
+            moves.push(Move::castle(king_square, target));
         }
+    }
 
-        self.add_move(moves, Move::from_idxs(start, target));
+    /// Adds the drops from the pocket of the active player, for crazyhouse.
+    pub(crate) fn generate_drops<const N: usize>(&self, moves: &mut MoveList<N>) {
+        self.generate_drops_to(moves, |_| SquareSet::FULL);
     }
 
-    fn add_move<'b, 'v>(&'b self, moves: &'v mut Vec<Move>, umove: Move) {
-        // you cannot move into check
-        let mut new_board = self.clone();
-        new_board.make_simple_move(umove).unwrap();
-        if new_board.generate_attacks(new_board.active_color())
-            [new_board.find_king(!new_board.active_color())]
+    /// Adds the drops of each piece onto the squares that `targets` gives for it.
+    fn generate_drops_to<const N: usize>(
+        &self,
+        moves: &mut MoveList<N>,
+        targets: impl Fn(Piece) -> SquareSet,
+    ) {
+        let player = self.active_color;
+        if POCKET_PIECES
+            .iter()
+            .all(|p| self.pocket_count(player, *p) == 0)
         {
-            debug!(
-                "You may not make the move {:?}, as you would move into check",
-                umove
-            );
             return;
         }
 
-        moves.push(umove);
+        for piece in POCKET_PIECES {
+            if self.pocket_count(player, piece) == 0 {
+                continue;
+            }
+            let targets = targets(piece);
+            for idx in 0..64 {
+                let back_rank = idx / 8 == 0 || idx / 8 == 7;
+                if self.squares[idx] != Piece::empty()
+                    || (piece == Piece::Pawn && back_rank)
+                    || !targets.contains(Square::from_idx(idx))
+                {
+                    continue;
+                }
+
+                moves.push(Move::new_drop(piece, Square::from_idx(idx)));
+            }
+        }
     }
 }
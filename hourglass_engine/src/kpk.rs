@@ -0,0 +1,171 @@
+use lazy_static::lazy_static;
+
+use crate::square_distance;
+
+/// The number of positions: white king, black king, side to move, and the pawn on files a-d
+///     and ranks 2-7.
+const KPK_SIZE: usize = 64 * 64 * 2 * 4 * 6;
+
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+lazy_static! {
+    /// One bit for each king and pawn vs king position, set if white wins.
+    static ref KPK_BITBASE: Vec<u64> = generate();
+}
+
+/// Checks if white wins a position with a white king and pawn against a black king.
+///
+/// The pawn must be on the a-d files, which is enough for any position after mirroring.
+pub(crate) fn probe(
+    white_king: usize,
+    pawn: usize,
+    black_king: usize,
+    white_to_move: bool,
+) -> bool {
+    debug_assert!(
+        pawn % 8 < 4,
+        "the pawn should be mirrored onto the a-d files"
+    );
+    let idx = index(white_to_move, white_king, black_king, pawn);
+    KPK_BITBASE[idx / 64] & (1 << (idx % 64)) != 0
+}
+
+fn index(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> usize {
+    white_king
+        | (black_king << 6)
+        | ((!white_to_move as usize) << 12)
+        | ((pawn % 8) << 13)
+        | ((6 - pawn / 8) << 15)
+}
+
+fn king_moves(square: usize) -> impl Iterator<Item = usize> {
+    (0..64).filter(move |s| square_distance(*s, square) == 1)
+}
+
+fn pawn_attacks(pawn: usize, square: usize) -> bool {
+    square / 8 == pawn / 8 + 1 && (square % 8).abs_diff(pawn % 8) == 1
+}
+
+/// A position of the bitbase, while it is being generated.
+struct KpkPosition {
+    white_to_move: bool,
+    white_king: usize,
+    black_king: usize,
+    pawn: usize,
+    result: u8,
+}
+
+impl KpkPosition {
+    fn new(idx: usize) -> Self {
+        let white_king = idx & 63;
+        let black_king = (idx >> 6) & 63;
+        let white_to_move = (idx >> 12) & 1 == 0;
+        let pawn = (6 - ((idx >> 15) & 7)) * 8 + ((idx >> 13) & 3);
+        let promotion = pawn + 8;
+
+        let result = if square_distance(white_king, black_king) <= 1
+            || white_king == pawn
+            || black_king == pawn
+            || (white_to_move && pawn_attacks(pawn, black_king))
+        {
+            INVALID
+        } else if white_to_move
+            && pawn / 8 == 6
+            && white_king != promotion
+            && (square_distance(black_king, promotion) > 1
+                || square_distance(white_king, promotion) == 1)
+        {
+            // the pawn promotes, and the new queen can't be taken
+            WIN
+        } else if !white_to_move
+            && king_moves(black_king)
+                .all(|s| square_distance(s, white_king) == 1 || pawn_attacks(pawn, s))
+        {
+            // stalemate
+            DRAW
+        } else if !white_to_move
+            && square_distance(black_king, pawn) == 1
+            && square_distance(white_king, pawn) > 1
+        {
+            // the pawn is taken
+            DRAW
+        } else {
+            UNKNOWN
+        };
+
+        KpkPosition {
+            white_to_move,
+            white_king,
+            black_king,
+            pawn,
+            result,
+        }
+    }
+
+    /// Finds the result from the results of the positions after each move.
+    fn classify(&self, results: &[u8]) -> u8 {
+        let (good, bad) = match self.white_to_move {
+            true => (WIN, DRAW),
+            false => (DRAW, WIN),
+        };
+
+        let mut outcomes = INVALID;
+        if self.white_to_move {
+            for s in king_moves(self.white_king) {
+                outcomes |= results[index(false, s, self.black_king, self.pawn)];
+            }
+
+            if self.pawn / 8 < 6 {
+                let push = self.pawn + 8;
+                outcomes |= results[index(false, self.white_king, self.black_king, push)];
+
+                let double_push = self.pawn + 16;
+                if self.pawn / 8 == 1 && push != self.white_king && push != self.black_king {
+                    outcomes |=
+                        results[index(false, self.white_king, self.black_king, double_push)];
+                }
+            }
+        } else {
+            for s in king_moves(self.black_king) {
+                outcomes |= results[index(true, self.white_king, s, self.pawn)];
+            }
+        }
+
+        if outcomes & good != 0 {
+            good
+        } else if outcomes & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            bad
+        }
+    }
+}
+
+/// Solves the bitbase by classifying every position from the positions after each move,
+///     until nothing changes.
+fn generate() -> Vec<u64> {
+    let positions = (0..KPK_SIZE).map(KpkPosition::new).collect::<Vec<_>>();
+    let mut results = positions.iter().map(|p| p.result).collect::<Vec<_>>();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (idx, position) in positions.iter().enumerate() {
+            if results[idx] == UNKNOWN {
+                results[idx] = position.classify(&results);
+                changed |= results[idx] != UNKNOWN;
+            }
+        }
+    }
+
+    let mut bitbase = vec![0; KPK_SIZE / 64];
+    for (idx, result) in results.into_iter().enumerate() {
+        if result == WIN {
+            bitbase[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+    bitbase
+}
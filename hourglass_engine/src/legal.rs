@@ -0,0 +1,110 @@
+use crate::{
+    gen_attacks::{between, Pins},
+    Board, Move, MoveList, Piece, SquareSet, Variant,
+};
+
+/// The checks and pins on the active player's king, found once per position
+///     so that each pseudo-legal move can be checked without playing it.
+pub(crate) struct KingSafety {
+    king: usize,
+    /// The squares that a piece other than the king can move to, to stop every check.
+    ///
+    /// This is every square when not in check, and no squares when in double check.
+    evasions: SquareSet,
+    pins: Pins,
+    /// The squares attacked by the other player, seeing through the king
+    ///     so that it can't step back along the line of a check.
+    attacked: [bool; 64],
+}
+
+impl Board {
+    /// Finds the checks and pins on the active player's king,
+    ///     or `None` if they have no king that could be put in check.
+    pub(crate) fn king_safety(&self) -> Option<KingSafety> {
+        let player = self.active_color;
+        let king = self.king_square(player)?;
+
+        let checkers = self.attackers_to(king, !player);
+        let evasions = match checkers.first() {
+            None => SquareSet::FULL,
+            // a single check can be blocked along its line, or the checker captured
+            Some(checker) if checkers.len() == 1 => between(king.idx(), checker.idx()) | checkers,
+            Some(_) => SquareSet::EMPTY,
+        };
+
+        let mut without_king = *self;
+        without_king.squares[king.idx()] = Piece::empty();
+        Some(KingSafety {
+            king: king.idx(),
+            evasions,
+            pins: self.pins_on(king.idx(), !player, player),
+            attacked: without_king.generate_attacks(!player),
+        })
+    }
+
+    /// Checks if a pseudo-legal move keeps the active player's king safe,
+    ///     using the checks and pins found by [`Board::king_safety`].
+    ///
+    /// Castling, en passant and king captures can't be decided from the checks and pins alone,
+    ///     so they are played out instead.
+    pub(crate) fn is_safe(&self, safety: &KingSafety, umove: Move) -> bool {
+        let to = umove.to();
+        if umove.is_drop() {
+            return safety.evasions.contains(to);
+        }
+
+        if umove.origin_idx() == safety.king {
+            // the attack map doesn't include defended pieces, so captures are played out too
+            if self.is_castle(umove) || self.squares[umove.target_idx()] != Piece::empty() {
+                return self.is_legal(umove);
+            }
+            return !safety.attacked[umove.target_idx()];
+        }
+
+        let is_pawn = self.squares[umove.origin_idx()] & Piece::PieceType == Piece::Pawn;
+        if is_pawn && self.en_passant == Some(umove.target_idx()) {
+            return self.is_legal(umove);
+        }
+
+        (safety.evasions & safety.pins.allowed(umove.origin_idx())).contains(to)
+    }
+
+    /// Removes the moves that are not legal from a list of pseudo-legal moves.
+    pub(crate) fn retain_legal<const N: usize>(&self, moves: &mut MoveList<N>) {
+        match self.variant {
+            // there is no check in antichess
+            Variant::Antichess => {}
+            // explosions and the ban on giving check need the move to be played out
+            Variant::Atomic | Variant::RacingKings => moves.retain(|m| self.is_legal(*m)),
+            _ => {
+                if let Some(safety) = self.king_safety() {
+                    moves.retain(|m| self.is_safe(&safety, *m));
+                }
+            }
+        }
+    }
+
+    /// Checks if a pseudo-legal move is legal, by playing it and looking at the king.
+    ///
+    /// The move should come from [`Board::generate_pseudo_legal`].
+    /// This is slower per move than [`Board::generate_moves`], but can be used to
+    ///     only check the moves that are actually searched.
+    pub fn is_legal(&self, umove: Move) -> bool {
+        // you cannot move into check
+        let mover = self.active_color;
+        let mut new_board = *self;
+        if new_board.make_move_unchecked(umove).is_err() {
+            return false;
+        }
+
+        // in atomic chess, you also cannot explode your own king
+        let king_exploded =
+            self.variant == Variant::Atomic && new_board.king_square(mover).is_none();
+        if king_exploded || new_board.is_in_check(mover) {
+            return false;
+        }
+
+        // you cannot give check in racing kings
+        !(self.variant == Variant::RacingKings && new_board.is_in_check(new_board.active_color))
+    }
+}
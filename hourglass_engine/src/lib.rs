@@ -1,58 +1,80 @@
 mod ai;
+mod chess960;
+mod clock;
+mod crazyhouse;
+mod display;
+mod endgame;
 mod fen;
+mod game;
 mod gen_attacks;
 mod gen_moves;
+mod kpk;
+mod legal;
+mod move_list;
+mod packed;
+mod pgn;
 mod pieces;
+mod polyglot;
+mod san;
+#[cfg(feature = "serde")]
+mod serialize;
+mod square;
+#[cfg(feature = "svg")]
+mod svg;
+mod tablebase;
+mod validate;
+mod variant;
 
 #[cfg(test)]
 mod test;
 
+use std::{fmt::Display, str::FromStr};
+
 use lazy_static::lazy_static;
 
+pub use ai::{SearchInfo, SearchLimits};
+pub use clock::{
+    Clock, ManualTimeSource, SystemTimeSource, TimeBonus, TimeControl, TimeControlParseErr,
+    TimeControlStage, TimeSource,
+};
+pub use display::DiagramOptions;
+pub use endgame::MaterialSignature;
+pub use fen::{EpdOperation, FenParseErr, FenPart};
+pub use game::{Game, GameStatus, PlayedMove};
+pub use gen_attacks::Pins;
+pub use gen_moves::GenKind;
+pub use move_list::{CrazyhouseMoveList, MoveList};
+pub use packed::{PackErr, PackedPosition, PackedReader, PackedWriter, PACKED_POSITION_SIZE};
+pub use pgn::{GameResult, PgnGame, PgnMove, PgnParseErr};
 pub use pieces::*;
-
-fn square_name_to_idx(pos: &str) -> Option<usize> {
-    let mut pos_chars = pos.chars();
-    let mut idx = 0;
-
-    match pos_chars.next()? {
-        'a' => idx += 0,
-        'b' => idx += 1,
-        'c' => idx += 2,
-        'd' => idx += 3,
-        'e' => idx += 4,
-        'f' => idx += 5,
-        'g' => idx += 6,
-        'h' => idx += 7,
-        _ => return None,
-    }
-
-    match pos_chars.next().map(|c| c.to_digit(10)) {
-        Some(Some(v)) if (1..=8).contains(&v) => idx += ((v - 1) * 8) as usize,
-        _ => return None,
+pub use polyglot::{BookEntry, BookErr, BookSelection, PolyglotBook};
+pub use san::SanParseErr;
+pub use square::{Square, SquareParseErr, SquareSet, SquareSetIter};
+#[cfg(feature = "svg")]
+pub use svg::{SvgColors, SvgOptions};
+pub use tablebase::{Tablebase, TablebaseErr, Wdl};
+pub use validate::PositionErr;
+pub use variant::Variant;
+
+/// The rook files for each castle right in standard chess.
+const STANDARD_CASTLE_FILES: [usize; 4] = [7, 0, 7, 0];
+
+/// The rank that the player's pieces start on.
+fn back_rank(player: Player) -> usize {
+    match player {
+        Player::White => 0,
+        Player::Black => 7,
     }
-
-    Some(idx)
 }
 
-fn idx_to_square_name(idx: usize) -> Option<String> {
-    if idx >= 64 {
-        return None;
-    }
-
-    let rank = ((idx / 8) + 1).to_string();
-    let file = ((idx % 8) as u8 + ('a' as u8)) as char;
-
-    Some(file.to_string() + &rank)
+/// The square that the king ends up on after castling.
+fn castle_king_target(player: Player, is_east: bool) -> usize {
+    back_rank(player) * 8 + if is_east { 6 } else { 2 }
 }
 
-fn get_rook_castle_pos(player: Player, is_east: bool) -> (usize, usize) {
-    match (player, is_east) {
-        (Player::White, false) => (0, 3),
-        (Player::White, true) => (7, 5),
-        (Player::Black, false) => (56, 59),
-        (Player::Black, true) => (63, 61),
-    }
+/// The square that the rook ends up on after castling.
+fn castle_rook_target(player: Player, is_east: bool) -> usize {
+    back_rank(player) * 8 + if is_east { 5 } else { 3 }
 }
 
 lazy_static! {
@@ -89,6 +111,11 @@ pub(crate) fn squares_to_edge(start: usize, dir: Direction) -> usize {
     NUM_SQUARES_TO_EDGE[start][dir as usize]
 }
 
+/// The number of king moves it takes to get from one square to another.
+pub(crate) fn square_distance(a: usize, b: usize) -> usize {
+    usize::max((a % 8).abs_diff(b % 8), (a / 8).abs_diff(b / 8))
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum InvalidMoveErr {
     ParseErr,
@@ -100,55 +127,266 @@ pub enum InvalidMoveErr {
     NoPromotion,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-pub struct Move {
-    from: usize,
-    to: usize,
-    promote: Option<Piece>,
+impl Display for InvalidMoveErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidMoveErr::ParseErr => write!(f, "a move is written like e2e4, e7e8q or N@f3"),
+            InvalidMoveErr::NotYourPiece => write!(f, "there is no piece of yours to move"),
+            InvalidMoveErr::IllegalMove => write!(f, "that piece cannot move there"),
+            InvalidMoveErr::NoPromotion => write!(f, "the move needs a piece to promote to"),
+        }
+    }
 }
 
+impl std::error::Error for InvalidMoveErr {}
+
+/// A move packed into 16 bits: the `from` square in bits 0-5, the `to` square in bits 6-11
+///     and the kind of move in bits 12-15, which says if it is a capture, castle,
+///     en passant, drop or promotion.
+///
+/// Moves generated by a [`Board`] have the right kind, but a move parsed without a position,
+///     like from UCI, does not know if it captures or castles. [`Board::resolve_move`] finds
+///     the generated move that it stands for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+/// The kinds of moves, stored in the top 4 bits of a [`Move`].
+mod kind {
+    pub const QUIET: u16 = 0;
+    pub const CAPTURE: u16 = 1;
+    pub const CASTLE: u16 = 2;
+    pub const EN_PASSANT: u16 = 3;
+    /// The `from` bits hold the type of the dropped piece.
+    pub const DROP: u16 = 4;
+    /// Followed by a promotion to each of [`super::PROMOTION_PIECES`].
+    pub const PROMOTE: u16 = 5;
+    /// Followed by a capturing promotion to each of [`super::PROMOTION_PIECES`].
+    pub const PROMOTE_CAPTURE: u16 = 10;
+}
+
+/// The pieces that a pawn can promote to, in the order their kinds are numbered.
+const PROMOTION_PIECES: [Piece; 5] = [
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
 impl Move {
-    pub fn to(&self) -> usize {
-        self.to
+    const fn pack(from: usize, to: usize, kind: u16) -> Self {
+        Move(from as u16 | (to as u16) << 6 | kind << 12)
+    }
+
+    /// Creates a move that doesn't know if it captures or castles, like one parsed from UCI.
+    pub fn new(from: Square, to: Square, promote: Option<Piece>) -> Self {
+        Move::pack(from.idx(), to.idx(), kind::QUIET).with_promote(promote)
     }
 
-    pub fn from(&self) -> usize {
-        self.from
+    /// Creates a move that drops a piece from the pocket onto the `to` square, for crazyhouse.
+    pub fn new_drop(piece: Piece, to: Square) -> Self {
+        let piece = piece & Piece::PieceType;
+        Move::pack(piece.bits() as usize, to.idx(), kind::DROP)
     }
 
-    pub fn from_idxs(from: usize, to: usize) -> Self {
-        Move {
-            from,
-            to,
-            promote: None,
+    pub(crate) fn from_idxs(from: usize, to: usize) -> Self {
+        Move::pack(from, to, kind::QUIET)
+    }
+
+    pub(crate) fn capture(from: usize, to: usize) -> Self {
+        Move::pack(from, to, kind::CAPTURE)
+    }
+
+    pub(crate) fn castle(from: usize, to: usize) -> Self {
+        Move::pack(from, to, kind::CASTLE)
+    }
+
+    pub(crate) fn en_passant(from: usize, to: usize) -> Self {
+        Move::pack(from, to, kind::EN_PASSANT)
+    }
+
+    fn kind(&self) -> u16 {
+        self.0 >> 12
+    }
+
+    /// The square the piece moves to, or the square a piece is dropped on.
+    pub fn to(&self) -> Square {
+        Square::from_idx(self.target_idx())
+    }
+
+    /// The square the piece moves from. For drops, this is the same as [`Move::to`].
+    pub fn from(&self) -> Square {
+        Square::from_idx(self.origin_idx())
+    }
+
+    pub(crate) fn target_idx(&self) -> usize {
+        (self.0 >> 6) as usize & 63
+    }
+
+    pub(crate) fn origin_idx(&self) -> usize {
+        match self.is_drop() {
+            true => self.target_idx(),
+            false => self.0 as usize & 63,
         }
     }
 
     // Creates a new Move with the same `to` and `from`, adding a promote piece.
     #[must_use]
     pub fn with_promote(&self, promote: Option<Piece>) -> Self {
-        Move { promote, ..*self }
+        let captures = self.is_capture();
+        let kind = match promote.and_then(|p| {
+            PROMOTION_PIECES
+                .iter()
+                .position(|piece| *piece == p & Piece::PieceType)
+        }) {
+            Some(idx) if captures => kind::PROMOTE_CAPTURE + idx as u16,
+            Some(idx) => kind::PROMOTE + idx as u16,
+            None if captures => kind::CAPTURE,
+            None if self.is_promotion() => kind::QUIET,
+            None => self.kind(),
+        };
+        Move(self.0 & 0x0fff | kind << 12)
     }
 
-    /// From a string move.
-    pub fn from_str(str: &str) -> Option<Self> {
-        if str.len() != 4 {
-            return None;
+    pub fn promote(&self) -> Option<Piece> {
+        match self.kind() {
+            kind if kind >= kind::PROMOTE_CAPTURE => {
+                Some(PROMOTION_PIECES[(kind - kind::PROMOTE_CAPTURE) as usize])
+            }
+            kind if kind >= kind::PROMOTE => {
+                Some(PROMOTION_PIECES[(kind - kind::PROMOTE) as usize])
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.kind() >= kind::PROMOTE
+    }
+
+    /// The type of piece that is dropped, if this is a drop.
+    pub fn dropped(&self) -> Option<Piece> {
+        match self.is_drop() {
+            true => Some(Piece::from_bits_truncate(self.0 as u8 & 63)),
+            false => None,
+        }
+    }
+
+    pub fn is_drop(&self) -> bool {
+        self.kind() == kind::DROP
+    }
+
+    /// Checks if the move was generated as a capture, including en passant.
+    ///
+    /// [`Board::is_capture`] also works for moves that were parsed without a position.
+    pub fn is_capture(&self) -> bool {
+        matches!(self.kind(), kind::CAPTURE | kind::EN_PASSANT)
+            || self.kind() >= kind::PROMOTE_CAPTURE
+    }
+
+    /// Checks if the move was generated as a castle.
+    pub fn is_castle(&self) -> bool {
+        self.kind() == kind::CASTLE
+    }
+
+    /// Checks if the move was generated as an en passant capture.
+    pub fn is_en_passant(&self) -> bool {
+        self.kind() == kind::EN_PASSANT
+    }
+
+    /// The move without the kinds that depend on the position: capture, castle and en passant.
+    pub(crate) fn without_flags(&self) -> Move {
+        match self.kind() {
+            kind::CAPTURE | kind::CASTLE | kind::EN_PASSANT => Move(self.0 & 0x0fff),
+            kind if kind >= kind::PROMOTE_CAPTURE => {
+                Move(self.0 & 0x0fff | (kind - kind::PROMOTE_CAPTURE + kind::PROMOTE) << 12)
+            }
+            _ => *self,
+        }
+    }
+}
+
+impl std::fmt::Debug for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind() {
+            kind::CAPTURE => " capture",
+            kind::CASTLE => " castle",
+            kind::EN_PASSANT => " en passant",
+            kind if kind >= kind::PROMOTE_CAPTURE => " capture",
+            _ => "",
+        };
+        write!(f, "Move({}{})", self, kind)
+    }
+}
+
+impl FromStr for Move {
+    type Err = InvalidMoveErr;
+
+    /// Parses a move in UCI long algebraic notation, ex. `e2e4` or `e7e8q`.
+    ///
+    /// Promoting to a king, ex. `e7e8k`, is only legal in antichess.
+    /// Drops in crazyhouse are written with the piece letter and an `@`, ex. `N@f3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() || (s.len() != 4 && s.len() != 5) {
+            return Err(InvalidMoveErr::ParseErr);
         }
 
-        let (from, to) = str.split_at(2);
-        let from = square_name_to_idx(from)?;
-        let to = square_name_to_idx(to)?;
+        if s.len() == 4 && &s[1..2] == "@" {
+            let piece = match &s[0..1] {
+                "P" => Piece::Pawn,
+                "N" => Piece::Knight,
+                "B" => Piece::Bishop,
+                "R" => Piece::Rook,
+                "Q" => Piece::Queen,
+                _ => return Err(InvalidMoveErr::ParseErr),
+            };
+            let to = Square::from_str(&s[2..4]).map_err(|_| InvalidMoveErr::ParseErr)?;
+            return Ok(Move::new_drop(piece, to));
+        }
 
-        Some(Move {
-            from,
-            to,
-            promote: None,
-        })
+        let from = Square::from_str(&s[0..2]).map_err(|_| InvalidMoveErr::ParseErr)?;
+        let to = Square::from_str(&s[2..4]).map_err(|_| InvalidMoveErr::ParseErr)?;
+        let promote = match s[4..].chars().next() {
+            None => None,
+            Some('k') => Some(Piece::King),
+            Some('n') => Some(Piece::Knight),
+            Some('b') => Some(Piece::Bishop),
+            Some('r') => Some(Piece::Rook),
+            Some('q') => Some(Piece::Queen),
+            Some(_) => return Err(InvalidMoveErr::ParseErr),
+        };
+
+        Ok(Move::new(from, to, promote))
     }
+}
+
+impl Display for Move {
+    /// Formats the move in UCI long algebraic notation.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let from = self.from();
+        let to = self.to();
+
+        if let Some(piece) = self.dropped() {
+            let letter = match piece {
+                Piece::Knight => "N",
+                Piece::Bishop => "B",
+                Piece::Rook => "R",
+                Piece::Queen => "Q",
+                _ => "P",
+            };
+            return write!(f, "{}@{}", letter, to);
+        }
+
+        write!(f, "{}{}", from, to)?;
 
-    pub fn new(from: usize, to: usize, promote: Option<Piece>) -> Self {
-        Move { from, to, promote }
+        match self.promote() {
+            Some(Piece::Knight) => write!(f, "n"),
+            Some(Piece::Bishop) => write!(f, "b"),
+            Some(Piece::Rook) => write!(f, "r"),
+            Some(Piece::Queen) => write!(f, "q"),
+            Some(Piece::King) => write!(f, "k"),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -212,6 +450,23 @@ pub struct Board {
     en_passant: Option<usize>,
     halfmove: u32,
     fullmove: u32,
+    /// The file of the rook for each castle right, indexed by [`CastleRights::index`].
+    castle_files: [usize; 4],
+    chess960: bool,
+    variant: Variant,
+    /// The number of checks given by white and black, for Three-check.
+    checks: [u8; 2],
+    /// The number of each piece that white and black have in hand, for crazyhouse,
+    ///     in the order of [`crazyhouse::POCKET_PIECES`].
+    pockets: [[u8; 5]; 2],
+    /// The squares with pieces that were promoted from pawns, for crazyhouse.
+    promoted: u64,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
 }
 
 impl Board {
@@ -231,84 +486,214 @@ impl Board {
             en_passant: None,
             halfmove: 0,
             fullmove: 1,
+            castle_files: STANDARD_CASTLE_FILES,
+            chess960: false,
+            variant: Variant::Standard,
+            checks: [0; 2],
+            pockets: [[0; 5]; 2],
+            promoted: 0,
         }
     }
 
+    /// Plays a move, if it is legal.
+    ///
+    /// The move does not need to know if it captures or castles, see [`Board::resolve_move`].
     pub fn try_move(&mut self, umove: Move) -> Result<(), InvalidMoveErr> {
+        let umove = self.resolve_move(umove)?;
+        self.make_move_unchecked(umove)
+    }
+
+    /// Finds the legal move that a move stands for, with its capture, castle
+    ///     and en passant kind filled in, like a move from [`Board::generate_moves`].
+    pub fn resolve_move(&self, umove: Move) -> Result<Move, InvalidMoveErr> {
+        if umove.is_drop() {
+            let mut drops = CrazyhouseMoveList::default();
+            if self.variant == Variant::Crazyhouse && !self.is_variant_end() {
+                self.generate_drops(&mut drops);
+                self.retain_legal(&mut drops);
+            }
+            return match drops.contains(&umove) {
+                true => Ok(umove),
+                false => Err(InvalidMoveErr::IllegalMove),
+            };
+        }
+
         // check if the player owns the piece they are trying to move
-        if self.squares[umove.from] & self.active_color.to_piece_color() == Piece::empty() {
+        if self.squares[umove.origin_idx()] & self.active_color.to_piece_color() == Piece::empty() {
             return Err(InvalidMoveErr::NotYourPiece);
         }
 
-        let mut moves = vec![];
-        self.get_moves_for(&mut moves, umove.from);
-        if !moves.iter().any(|m| *m == umove) {
-            // invalid move
-            return Err(InvalidMoveErr::IllegalMove);
+        let mut moves = MoveList::new();
+        self.get_moves_for(&mut moves, umove.from());
+        let target = umove.without_flags();
+        if let Some(resolved) = moves.iter().find(|m| m.without_flags() == target) {
+            return Ok(*resolved);
+        }
+
+        // the move is only missing the piece to promote to
+        if umove.promote().is_none()
+            && moves
+                .iter()
+                .any(|m| m.is_promotion() && m.to() == umove.to())
+        {
+            return Err(InvalidMoveErr::NoPromotion);
+        }
+        Err(InvalidMoveErr::IllegalMove)
+    }
+
+    /// Plays a move without checking that it is legal.
+    pub(crate) fn make_move_unchecked(&mut self, umove: Move) -> Result<(), InvalidMoveErr> {
+        let before = *self;
+        self.move_pieces(umove)?;
+        self.update_variant_state(&before, umove);
+        Ok(())
+    }
+
+    /// Moves the pieces and updates the castle rights, en passant square and move counters.
+    fn move_pieces(&mut self, umove: Move) -> Result<(), InvalidMoveErr> {
+        if let Some(piece) = umove.dropped() {
+            self.drop_piece(piece, umove.target_idx());
+            return Ok(());
+        }
+
+        let piece = self.squares[umove.origin_idx()];
+        let is_pawn = piece & Piece::PieceType == Piece::Pawn;
+        let is_castle = self.is_castle(umove);
+
+        // update the move counters
+        if is_pawn || (!is_castle && self.squares[umove.target_idx()] != Piece::empty()) {
+            self.halfmove = 0;
+        } else {
+            self.halfmove += 1;
+        }
+        if self.active_color == Player::Black {
+            self.fullmove += 1;
         }
 
         // handle en_passant
-        if self.en_passant == Some(umove.to) {
+        if is_pawn && self.en_passant == Some(umove.target_idx()) {
             // if the move to value matches the curren en passant-able square,
             //     take the pawn that double pushed.
-            let target = (umove.to as isize - self.active_color.forward_value() * 8) as usize;
+            let target =
+                (umove.target_idx() as isize - self.active_color.forward_value() * 8) as usize;
             self.squares[target] = Piece::empty();
         }
 
-        // record en passant
-        if self.squares[umove.from] & Piece::PieceType == Piece::Pawn
-            && (((umove.from as isize) - (umove.to as isize)).abs() == 16)
+        // record en passant, which is not possible after a horde pawn moves 2 from the back rank
+        let from_back_rank = umove.origin_idx() / 8 == back_rank(self.active_color);
+        if is_pawn
+            && !from_back_rank
+            && (((umove.origin_idx() as isize) - (umove.target_idx() as isize)).abs() == 16)
         {
             // pawn moved 2 spaces; record en passant
-            let target = (umove.to as isize - self.active_color.forward_value() * 8) as usize;
+            let target =
+                (umove.target_idx() as isize - self.active_color.forward_value() * 8) as usize;
             self.en_passant = Some(target);
         } else {
             self.en_passant = None;
         }
 
-        let is_king = self.squares[umove.from] & Piece::PieceType == Piece::King;
-        let move_dist = umove.to as isize - umove.from as isize;
+        // moving or capturing a castling rook revokes that castle right
+        for right in CastleRights::EACH {
+            let rook_square = self.castle_rook_square(right).idx();
+            if umove.origin_idx() == rook_square || umove.target_idx() == rook_square {
+                self.castle_rights.revoke(right);
+            }
+        }
+
+        if is_castle {
+            let is_east = self.castle_is_east(umove);
+            self.castle_rights.revoke_all(self.active_color);
 
-        if is_king && move_dist.abs() == 2 {
-            // this move is a castle; move the rook
-            let (rook_from, rook_to) = get_rook_castle_pos(self.active_color, move_dist > 0);
-            self.squares[rook_to] = self.squares[rook_from];
+            let (rook_from, rook_to) = self.rook_castle_squares(umove, is_east);
+            let king_to = castle_king_target(self.active_color, is_east);
+            let rook = self.squares[rook_from];
             self.squares[rook_from] = Piece::empty();
+            self.squares[umove.origin_idx()] = Piece::empty();
+            self.squares[rook_to] = rook;
+            self.squares[king_to] = piece;
+
+            self.active_color = !self.active_color;
+            return Ok(());
         }
-        if is_king {
+
+        if piece & Piece::PieceType == Piece::King {
             // moving the king revokes it's castle rights
             self.castle_rights.revoke_all(self.active_color);
         }
-        match umove.from {
-            0 => self.castle_rights.revoke(CastleRights::WhiteQueenSide),
-            7 => self.castle_rights.revoke(CastleRights::WhiteKingSide),
-            56 => self.castle_rights.revoke(CastleRights::BlackQueenSide),
-            63 => self.castle_rights.revoke(CastleRights::BlackKingSide),
-            _ => {}
-        }
-        match umove.to {
-            0 => self.castle_rights.revoke(CastleRights::WhiteQueenSide),
-            7 => self.castle_rights.revoke(CastleRights::WhiteKingSide),
-            56 => self.castle_rights.revoke(CastleRights::BlackQueenSide),
-            63 => self.castle_rights.revoke(CastleRights::BlackKingSide),
-            _ => {}
-        }
 
         // move the piece
         self.make_simple_move(umove)
     }
 
+    /// Checks if the given move is a castle.
+    ///
+    /// In chess960 mode castles are encoded as the king capturing its own rook,
+    ///     otherwise they are encoded as the king moving two squares.
+    pub fn is_castle(&self, umove: Move) -> bool {
+        let piece = self.squares[umove.origin_idx()];
+        if piece & Piece::PieceType != Piece::King {
+            return false;
+        }
+
+        if self.chess960 {
+            let own_rook = Piece::Rook | (piece & Piece::PlayerType);
+            self.squares[umove.target_idx()] == own_rook
+                && CastleRights::EACH.into_iter().any(|right| {
+                    self.castle_rights.has_right(right)
+                        && self.castle_rook_square(right) == umove.to()
+                })
+        } else {
+            (umove.target_idx() as isize - umove.origin_idx() as isize).abs() == 2
+        }
+    }
+
+    /// Checks if a castle is to the king side.
+    fn castle_is_east(&self, umove: Move) -> bool {
+        umove.target_idx() > umove.origin_idx()
+    }
+
+    /// Gets the `from` and `to` squares of the rook for a castle.
+    fn rook_castle_squares(&self, umove: Move, is_east: bool) -> (usize, usize) {
+        let player = self.active_color;
+        let rook_from = match self.chess960 {
+            true => umove.target_idx(),
+            false => self
+                .castle_rook_square(CastleRights::for_side(player, is_east))
+                .idx(),
+        };
+        (rook_from, castle_rook_target(player, is_east))
+    }
+
+    /// Gets the square of the rook that a castle right refers to.
+    pub fn castle_rook_square(&self, right: CastleRights) -> Square {
+        Square::from_idx(back_rank(right.player()) * 8 + self.castle_files[right.index()])
+    }
+
+    /// Checks if the board is in chess960 mode.
+    ///
+    /// This changes how castles are encoded, see [`Board::is_castle`].
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Turns chess960 mode on or off. Loading a fen never changes it,
+    ///     so it should be set before loading a chess960 position.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
     /// Moves a piece from the `from` square to the `to` square.
     /// Switches the `active_color`. This also handels promotions.
     fn make_simple_move(&mut self, umove: Move) -> Result<(), InvalidMoveErr> {
-        let mut resulting_piece = self.squares[umove.from];
+        let mut resulting_piece = self.squares[umove.origin_idx()];
 
-        let to_rank = umove.to / 8;
-        if self.squares[umove.from] & Piece::PieceType == Piece::Pawn
+        let to_rank = umove.target_idx() / 8;
+        if self.squares[umove.origin_idx()] & Piece::PieceType == Piece::Pawn
             && (to_rank == 0 || to_rank == 7)
         {
             // Pawn will promote
-            match umove.promote {
+            match umove.promote() {
                 None => return Err(InvalidMoveErr::NoPromotion),
                 Some(promoting_piece) => {
                     resulting_piece = promoting_piece | self.active_color.to_piece_color()
@@ -316,8 +701,8 @@ impl Board {
             }
         }
 
-        self.squares[umove.to] = resulting_piece;
-        self.squares[umove.from] = Piece::empty();
+        self.squares[umove.target_idx()] = resulting_piece;
+        self.squares[umove.origin_idx()] = Piece::empty();
 
         self.active_color = !self.active_color;
 
@@ -328,13 +713,45 @@ impl Board {
         self.active_color
     }
 
-    pub fn find_king(&self, player: Player) -> usize {
-        for (idx, piece) in self.squares.iter().enumerate() {
-            if *piece == Piece::King | player.to_piece_color() {
-                return idx;
-            }
-        }
-        panic!("God save the king.");
+    /// The number of halfmoves since the last capture or pawn move.
+    pub fn halfmove(&self) -> u32 {
+        self.halfmove
+    }
+
+    /// The number of the full move, starting at 1 and incremented after black moves.
+    pub fn fullmove(&self) -> u32 {
+        self.fullmove
+    }
+
+    pub fn castle_rights(&self) -> CastleRights {
+        self.castle_rights
+    }
+
+    /// The square that can be captured en passant, if any.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant.map(Square::from_idx)
+    }
+
+    /// Gets the square of the player's king.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the player has no king. Use [`Board::king_square`] for positions
+    ///     where that is possible, like in Horde.
+    pub fn find_king(&self, player: Player) -> Square {
+        self.king_square(player).expect("God save the king.")
+    }
+
+    /// Gets the square of the player's king, if they have one.
+    ///
+    /// A king can be exploded in atomic chess or captured in antichess,
+    ///     and the horde has no king.
+    pub fn king_square(&self, player: Player) -> Option<Square> {
+        let king = Piece::King | player.to_piece_color();
+        self.squares
+            .iter()
+            .position(|piece| *piece == king)
+            .map(Square::from_idx)
     }
 
     /// Gets the piece at the given rank and file.
@@ -344,17 +761,24 @@ impl Board {
     ///
     /// This will return `Some` as long as both the rank and file are in range.
     pub fn piece_at(&self, rank: usize, file: usize) -> Option<Piece> {
-        self.squares.get(rank * 8 + file).copied()
+        Square::from_file_rank(file, rank).map(|square| self.piece_on(square))
+    }
+
+    /// Gets the piece on the square, which is empty if there is none.
+    pub fn piece_on(&self, square: Square) -> Piece {
+        self.squares[square.idx()]
     }
 
-    pub fn piece_at_idx(&self, idx: usize) -> Piece {
+    pub(crate) fn piece_at_idx(&self, idx: usize) -> Piece {
         self.squares[idx]
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Board, Move};
+    use std::str::FromStr;
+
+    use crate::{Board, Move, Player};
 
     #[test]
     fn test_try_move() {
@@ -362,4 +786,23 @@ mod tests {
         // e2 to e4 should be a valid starting move.
         board.try_move(Move::from_str("e2e4").unwrap()).unwrap();
     }
+
+    #[test]
+    fn test_try_move_counters() {
+        let mut board = Board::new();
+        for (umove, counters) in [("g1f3", " 1 1"), ("g8f6", " 2 2"), ("e2e4", " 0 2")] {
+            board.try_move(Move::from_str(umove).unwrap()).unwrap();
+            assert!(board.get_fen().ends_with(counters), "{}", board.get_fen());
+        }
+    }
+
+    #[test]
+    fn test_is_in_check() {
+        let mut board = Board::empty();
+        board.load_fen("4k3/8/8/8/8/8/8/4K2R b - - 0 1").unwrap();
+        assert!(!board.is_in_check(Player::Black));
+        board.load_fen("4k2R/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.is_in_check(Player::Black));
+        assert!(!board.is_in_check(Player::White));
+    }
 }
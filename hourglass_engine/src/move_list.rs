@@ -0,0 +1,198 @@
+use std::{fmt::Debug, ops::Deref};
+
+use crate::Move;
+
+/// Fills the unused part of a [`MoveList`].
+const EMPTY_MOVE: Move = Move(0);
+
+/// A list with room for the moves of a crazyhouse position, as the pocket can add
+///     hundreds of drops to the moves on the board.
+pub type CrazyhouseMoveList = MoveList<512>;
+
+/// A list of moves that lives on the stack, so that generating moves doesn't allocate.
+///
+/// Each move has a score that can be used to order the moves, which is 0 unless it is set.
+///
+/// A list holds 256 moves unless it is given another capacity, as a chess position has
+///     at most 218 legal moves. Crazyhouse positions need a [`CrazyhouseMoveList`].
+#[derive(Clone, Copy)]
+pub struct MoveList<const N: usize = 256> {
+    moves: [Move; N],
+    scores: [i32; N],
+    len: usize,
+}
+
+impl MoveList {
+    pub const fn new() -> Self {
+        MoveList::empty()
+    }
+}
+
+impl<const N: usize> MoveList<N> {
+    /// The most moves that the list can hold.
+    pub const CAPACITY: usize = N;
+
+    /// Makes an empty list of any capacity. Lists of the default capacity use [`MoveList::new`],
+    ///     and other lists can use [`Default`].
+    const fn empty() -> Self {
+        MoveList {
+            moves: [EMPTY_MOVE; N],
+            scores: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Adds a move with a score of 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list already holds [`MoveList::CAPACITY`] moves.
+    pub fn push(&mut self, umove: Move) {
+        self.push_scored(umove, 0);
+    }
+
+    /// Adds a move with a score to order it by.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list already holds [`MoveList::CAPACITY`] moves.
+    pub fn push_scored(&mut self, umove: Move, score: i32) {
+        assert!(self.len < N, "the move list is full");
+        self.moves[self.len] = umove;
+        self.scores[self.len] = score;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+
+    /// Gets the score of the move at the index.
+    pub fn score(&self, idx: usize) -> i32 {
+        self.scores[..self.len][idx]
+    }
+
+    pub fn set_score(&mut self, idx: usize, score: i32) {
+        self.scores[..self.len][idx] = score;
+    }
+
+    /// Sorts the moves from the highest score to the lowest,
+    ///     keeping moves with the same score in the same order.
+    pub fn sort_by_score(&mut self) {
+        // an insertion sort, as the standard stable sort allocates
+        for i in 1..self.len {
+            let mut j = i;
+            while j > 0 && self.scores[j - 1] < self.scores[j] {
+                self.moves.swap(j - 1, j);
+                self.scores.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
+    /// Keeps only the moves that match the predicate, in the same order.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&Move) -> bool) {
+        let mut kept = 0;
+        for idx in 0..self.len {
+            if predicate(&self.moves[idx]) {
+                self.moves[kept] = self.moves[idx];
+                self.scores[kept] = self.scores[idx];
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+impl<const N: usize> Default for MoveList<N> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<const N: usize> Deref for MoveList<N> {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> Debug for MoveList<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Lists are equal when they have the same moves in the same order, ignoring the scores.
+impl<const N: usize> PartialEq for MoveList<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for MoveList<N> {}
+
+impl<const N: usize> Extend<Move> for MoveList<N> {
+    fn extend<T: IntoIterator<Item = Move>>(&mut self, iter: T) {
+        for umove in iter {
+            self.push(umove);
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<Move> for MoveList<N> {
+    fn from_iter<T: IntoIterator<Item = Move>>(iter: T) -> Self {
+        let mut list = Self::empty();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a MoveList<N> {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<const N: usize> IntoIterator for MoveList<N> {
+    type Item = Move;
+    type IntoIter = IntoIter<N>;
+
+    fn into_iter(self) -> IntoIter<N> {
+        IntoIter {
+            list: self,
+            next: 0,
+        }
+    }
+}
+
+/// An iterator over the moves of a [`MoveList`], by value.
+pub struct IntoIter<const N: usize = 256> {
+    list: MoveList<N>,
+    next: usize,
+}
+
+impl<const N: usize> Iterator for IntoIter<N> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let umove = self.list.get(self.next).copied();
+        self.next += 1;
+        umove
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len().saturating_sub(self.next);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for IntoIter<N> {}
@@ -0,0 +1,331 @@
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Read, Write},
+};
+
+use crate::{back_rank, Board, CastleRights, GameResult, Piece, Player, PositionErr, Variant};
+
+/// The number of bytes in a packed position.
+pub const PACKED_POSITION_SIZE: usize = 32;
+
+/// The piece code of a rook that can still castle, before the color bit.
+const CASTLE_ROOK: u8 = 7;
+/// The bit of a piece code that is set for black pieces.
+const BLACK_CODE: u8 = 8;
+/// The en passant byte when there is no en passant square.
+const NO_EN_PASSANT: u8 = 64;
+
+#[derive(Debug)]
+pub enum PackErr {
+    Io(std::io::Error),
+    /// There are more pieces on the board than there is space for, as in Horde.
+    TooManyPieces(usize),
+    /// The variant has state that isn't packed, like the pockets in crazyhouse.
+    UnsupportedVariant(Variant),
+    /// The halfmove or fullmove counter is too large to pack.
+    CounterTooLarge(u32),
+    /// The stream ended partway through a position.
+    Truncated(usize),
+    InvalidPieceCode(u8),
+    InvalidEnPassant(u8),
+    InvalidResult(u8),
+    InvalidVariant(u8),
+    InvalidPosition(PositionErr),
+}
+
+impl Display for PackErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackErr::Io(err) => write!(f, "failed to access the positions: {}", err),
+            PackErr::TooManyPieces(count) => {
+                write!(f, "{} pieces is more than the 32 that can be packed", count)
+            }
+            PackErr::UnsupportedVariant(variant) => {
+                write!(f, "positions from {} can't be packed", variant)
+            }
+            PackErr::CounterTooLarge(count) => {
+                write!(f, "the move counter {} is too large to pack", count)
+            }
+            PackErr::Truncated(len) => write!(
+                f,
+                "the last position is {} bytes instead of {}",
+                len, PACKED_POSITION_SIZE
+            ),
+            PackErr::InvalidPieceCode(code) => write!(f, "invalid piece code {}", code),
+            PackErr::InvalidEnPassant(square) => write!(f, "invalid en passant square {}", square),
+            PackErr::InvalidResult(result) => write!(f, "invalid result {}", result),
+            PackErr::InvalidVariant(variant) => write!(f, "invalid variant {}", variant),
+            PackErr::InvalidPosition(err) => write!(f, "invalid position: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PackErr {}
+
+impl From<std::io::Error> for PackErr {
+    fn from(err: std::io::Error) -> Self {
+        PackErr::Io(err)
+    }
+}
+
+/// A position for a training set, along with how it was scored and how the game ended.
+///
+/// It packs into [`PACKED_POSITION_SIZE`] bytes, as:
+/// - bytes 0-7: the occupied squares, as a little-endian bitboard with a1 as bit 0.
+/// - bytes 8-23: a 4-bit code for each occupied square from a1 to h8, low nibble first.
+///   The code is the kind of piece from [`Piece::PieceType`], plus 8 for black.
+///   A rook that can still castle has the kind 7, which also holds chess960 castle rights.
+/// - byte 24: the en passant square, or 64 for none, with bit 7 set if black is to move.
+/// - byte 25: the halfmove clock.
+/// - bytes 26-27: the fullmove number.
+/// - bytes 28-29: the score.
+/// - byte 30: the result, as 0 for a black win, 1 for a draw, 2 for a white win
+///   and 3 if it's unknown.
+/// - byte 31: the index of the variant in [`Variant::ALL`] in bits 0-2, bit 3 set for chess960,
+///   and the checks given by white and black in Three-check in bits 4-5 and 6-7.
+///
+/// Crazyhouse positions and Horde positions with more than 32 pieces can't be packed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PackedPosition {
+    pub board: Board,
+    /// The score of the position in centipawns, from white's point of view.
+    pub score: i16,
+    pub result: GameResult,
+}
+
+impl PackedPosition {
+    pub fn to_bytes(&self) -> Result<[u8; PACKED_POSITION_SIZE], PackErr> {
+        let board = &self.board;
+        if board.variant == Variant::Crazyhouse {
+            return Err(PackErr::UnsupportedVariant(board.variant));
+        }
+
+        let mut bytes = [0; PACKED_POSITION_SIZE];
+        let mut occupied = 0u64;
+        let mut count = 0;
+        for (idx, piece) in board.squares.iter().enumerate() {
+            if *piece == Piece::empty() {
+                continue;
+            }
+            if count == 32 {
+                let total = board
+                    .squares
+                    .iter()
+                    .filter(|p| **p != Piece::empty())
+                    .count();
+                return Err(PackErr::TooManyPieces(total));
+            }
+            occupied |= 1 << idx;
+            bytes[8 + count / 2] |= piece_code(board, idx) << (4 * (count % 2));
+            count += 1;
+        }
+        bytes[0..8].copy_from_slice(&occupied.to_le_bytes());
+
+        let en_passant = board.en_passant.map_or(NO_EN_PASSANT, |idx| idx as u8);
+        let black_to_move = match board.active_color {
+            Player::White => 0,
+            Player::Black => 0x80,
+        };
+        bytes[24] = en_passant | black_to_move;
+        bytes[25] =
+            u8::try_from(board.halfmove).map_err(|_| PackErr::CounterTooLarge(board.halfmove))?;
+        let fullmove =
+            u16::try_from(board.fullmove).map_err(|_| PackErr::CounterTooLarge(board.fullmove))?;
+        bytes[26..28].copy_from_slice(&fullmove.to_le_bytes());
+        bytes[28..30].copy_from_slice(&self.score.to_le_bytes());
+        bytes[30] = match self.result {
+            GameResult::BlackWins => 0,
+            GameResult::Draw => 1,
+            GameResult::WhiteWins => 2,
+            GameResult::Ongoing => 3,
+        };
+
+        let variant = Variant::ALL
+            .iter()
+            .position(|v| *v == board.variant)
+            .expect("every variant is in Variant::ALL") as u8;
+        bytes[31] = variant
+            | (board.chess960 as u8) << 3
+            | board.checks[0].min(3) << 4
+            | board.checks[1].min(3) << 6;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8; PACKED_POSITION_SIZE]) -> Result<Self, PackErr> {
+        let mut board = Board::empty();
+        let flags = bytes[31];
+        board.variant = *Variant::ALL
+            .get(flags as usize & 0b111)
+            .filter(|variant| **variant != Variant::Crazyhouse)
+            .ok_or(PackErr::InvalidVariant(flags & 0b111))?;
+        board.chess960 = flags & 0b1000 != 0;
+        board.checks = [(flags >> 4) & 0b11, flags >> 6];
+
+        let occupied = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        if occupied.count_ones() > 32 {
+            return Err(PackErr::TooManyPieces(occupied.count_ones() as usize));
+        }
+        // there is at most one castling rook for each side of each player
+        let mut castle_rooks = [None; 4];
+        let mut castle_rook_count = 0;
+        for (count, idx) in (0..64).filter(|idx| occupied & 1 << idx != 0).enumerate() {
+            let code = (bytes[8 + count / 2] >> (4 * (count % 2))) & 0xF;
+            let player = match code & BLACK_CODE {
+                0 => Player::White,
+                _ => Player::Black,
+            };
+            let kind = match code & !BLACK_CODE {
+                CASTLE_ROOK => {
+                    let slot = castle_rooks
+                        .get_mut(castle_rook_count)
+                        .ok_or(PackErr::InvalidPieceCode(code))?;
+                    *slot = Some((idx, player, code));
+                    castle_rook_count += 1;
+                    Piece::Rook
+                }
+                1..=6 => Piece::from_bits_retain(code & !BLACK_CODE),
+                _ => return Err(PackErr::InvalidPieceCode(code)),
+            };
+            board.squares[idx] = kind | player.to_piece_color();
+        }
+
+        // the castle rights come from where each rook is compared to its king
+        for (idx, player, code) in castle_rooks.into_iter().flatten() {
+            let rank = back_rank(player);
+            let king = Piece::King | player.to_piece_color();
+            let king_file = (0..8).find(|file| board.squares[rank * 8 + file] == king);
+            let (Some(king_file), true) = (king_file, idx / 8 == rank) else {
+                return Err(PackErr::InvalidPieceCode(code));
+            };
+            let right = CastleRights::for_side(player, idx % 8 > king_file);
+            board.castle_rights |= right;
+            board.castle_files[right.index()] = idx % 8;
+        }
+
+        board.active_color = match bytes[24] & 0x80 {
+            0 => Player::White,
+            _ => Player::Black,
+        };
+        board.en_passant = match bytes[24] & 0x7F {
+            NO_EN_PASSANT => None,
+            idx @ 0..=63 => Some(idx as usize),
+            idx => return Err(PackErr::InvalidEnPassant(idx)),
+        };
+        board.halfmove = bytes[25] as u32;
+        board.fullmove = u16::from_le_bytes([bytes[26], bytes[27]]) as u32;
+        board.validate().map_err(PackErr::InvalidPosition)?;
+
+        let score = i16::from_le_bytes([bytes[28], bytes[29]]);
+        let result = match bytes[30] {
+            0 => GameResult::BlackWins,
+            1 => GameResult::Draw,
+            2 => GameResult::WhiteWins,
+            3 => GameResult::Ongoing,
+            result => return Err(PackErr::InvalidResult(result)),
+        };
+        Ok(PackedPosition {
+            board,
+            score,
+            result,
+        })
+    }
+}
+
+/// Gets the 4-bit code of the piece on a square, see [`PackedPosition`].
+fn piece_code(board: &Board, idx: usize) -> u8 {
+    let piece = board.squares[idx];
+    let player = match piece.is_color(Player::White) {
+        true => Player::White,
+        false => Player::Black,
+    };
+    let can_castle = piece & Piece::PieceType == Piece::Rook
+        && CastleRights::EACH.iter().any(|right| {
+            right.player() == player
+                && board.castle_rights.has_right(*right)
+                && board.castle_rook_square(*right).idx() == idx
+        });
+    let kind = match can_castle {
+        true => CASTLE_ROOK,
+        false => (piece & Piece::PieceType).bits(),
+    };
+    match player {
+        Player::White => kind,
+        Player::Black => kind | BLACK_CODE,
+    }
+}
+
+/// Writes packed positions one after another to a stream, ex. a file.
+#[derive(Debug)]
+pub struct PackedWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PackedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        PackedWriter { writer }
+    }
+
+    pub fn write(&mut self, position: &PackedPosition) -> Result<(), PackErr> {
+        self.writer.write_all(&position.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), PackErr> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads packed positions from a stream, one at a time.
+///
+/// Reading stops at the end of the stream, or after the first error.
+#[derive(Debug)]
+pub struct PackedReader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> PackedReader<R> {
+    pub fn new(reader: R) -> Self {
+        PackedReader {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Reads the next position, or `None` at the end of the stream.
+    pub fn read(&mut self) -> Result<Option<PackedPosition>, PackErr> {
+        let mut bytes = [0; PACKED_POSITION_SIZE];
+        let mut len = 0;
+        while len < PACKED_POSITION_SIZE {
+            match self.reader.read(&mut bytes[len..]) {
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        match len {
+            0 => Ok(None),
+            PACKED_POSITION_SIZE => PackedPosition::from_bytes(&bytes).map(Some),
+            _ => Err(PackErr::Truncated(len)),
+        }
+    }
+}
+
+impl<R: Read> Iterator for PackedReader<R> {
+    type Item = Result<PackedPosition, PackErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
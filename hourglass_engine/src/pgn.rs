@@ -0,0 +1,675 @@
+use std::fmt::Display;
+
+use crate::{fen::FenParseErr, san::SanParseErr, Board, InvalidMoveErr, Move, Player, Variant};
+
+/// The maximum line length used when writing movetext.
+const PGN_LINE_WIDTH: usize = 80;
+
+/// The tags that every exported game has, in the order they are exported.
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game is still going, or the result is unknown.
+    #[default]
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+
+    pub fn from_pgn(result: &str) -> Option<Self> {
+        match result {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Ongoing),
+            _ => None,
+        }
+    }
+
+    /// The result where the given player won.
+    pub fn win_for(player: Player) -> Self {
+        match player {
+            Player::White => GameResult::WhiteWins,
+            Player::Black => GameResult::BlackWins,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnParseErr {
+    InvalidTag {
+        line: usize,
+    },
+    UnterminatedComment {
+        line: usize,
+    },
+    /// A `(` without a matching `)`, or the other way around.
+    UnbalancedVariation {
+        line: usize,
+    },
+    UnexpectedToken {
+        line: usize,
+        token: String,
+    },
+    InvalidFen(FenParseErr),
+    /// The `Variant` tag is not a variant that is supported.
+    UnknownVariant(String),
+    InvalidMove {
+        line: usize,
+        san: String,
+        err: SanParseErr,
+    },
+    /// A move of the main line that can't be played, which can happen after the moves
+    ///     of a game are changed.
+    IllegalMove {
+        ply: usize,
+        san: String,
+        err: InvalidMoveErr,
+    },
+}
+
+impl Display for PgnParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnParseErr::InvalidTag { line } => write!(f, "invalid tag pair on line {}", line),
+            PgnParseErr::UnterminatedComment { line } => {
+                write!(f, "comment starting on line {} is never closed", line)
+            }
+            PgnParseErr::UnbalancedVariation { line } => {
+                write!(f, "unbalanced variation on line {}", line)
+            }
+            PgnParseErr::UnexpectedToken { line, token } => {
+                write!(f, "unexpected token \"{}\" on line {}", token, line)
+            }
+            PgnParseErr::InvalidFen(err) => write!(f, "invalid FEN tag: {}", err),
+            PgnParseErr::UnknownVariant(variant) => write!(f, "unknown variant \"{}\"", variant),
+            PgnParseErr::InvalidMove { line, san, err } => {
+                write!(f, "invalid move \"{}\" on line {}: {}", san, line, err)
+            }
+            PgnParseErr::IllegalMove { ply, san, err } => {
+                write!(f, "illegal move \"{}\" at ply {}: {}", san, ply, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnParseErr {}
+
+/// A move in the movetext of a game, along with its annotations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnMove {
+    pub umove: Move,
+    pub san: String,
+    /// Numeric Annotation Glyphs. The suffixes `!`, `?`, `!!`, `??`, `!?` and `?!`
+    ///     are stored as the NAGs 1 through 6.
+    pub nags: Vec<u8>,
+    /// Comments that follow the move.
+    pub comments: Vec<String>,
+    /// Alternatives to this move. Each variation starts from the position before this move.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(umove: Move, san: String) -> Self {
+        PgnMove {
+            umove,
+            san,
+            nags: vec![],
+            comments: vec![],
+            variations: vec![],
+        }
+    }
+
+    /// The error for this move not being playable at the given ply of the main line.
+    pub(crate) fn illegal(&self, ply: usize, err: InvalidMoveErr) -> PgnParseErr {
+        PgnParseErr::IllegalMove {
+            ply,
+            san: self.san.clone(),
+            err,
+        }
+    }
+}
+
+/// A single game from a PGN file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    /// The tag pairs in the order they appeared.
+    pub tags: Vec<(String, String)>,
+    /// Comments that come before the first move.
+    pub comments: Vec<String>,
+    /// The main line.
+    pub moves: Vec<PgnMove>,
+    pub result: GameResult,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    OpenVariation,
+    CloseVariation,
+    Result(GameResult),
+    San(String),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    at_line_start: bool,
+    /// A suffix annotation that was split off of the previous move.
+    pending_nag: Option<(usize, u8)>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(pgn: &'a str) -> Self {
+        Lexer {
+            chars: pgn.chars().peekable(),
+            line: 1,
+            at_line_start: true,
+            pending_nag: None,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.at_line_start = c == '\n';
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_line(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    fn lex_tag(&mut self) -> Result<Token, PgnParseErr> {
+        let line = self.line;
+        let err = PgnParseErr::InvalidTag { line };
+
+        let mut name = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '"' {
+                break;
+            }
+            name.push(c);
+            self.bump();
+        }
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+        if name.is_empty() || self.bump() != Some('"') {
+            return Err(err);
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\\') => value.push(self.bump().ok_or(err.clone())?),
+                Some('"') => break,
+                Some('\n') | None => return Err(err),
+                Some(c) => value.push(c),
+            }
+        }
+
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+        if self.bump() != Some(']') {
+            return Err(err);
+        }
+
+        Ok(Token::Tag(name, value))
+    }
+
+    /// Gets the next token, along with the line it started on.
+    fn next_token(&mut self) -> Result<Option<(usize, Token)>, PgnParseErr> {
+        if let Some((line, nag)) = self.pending_nag.take() {
+            return Ok(Some((line, Token::Nag(nag))));
+        }
+
+        loop {
+            if self.at_line_start && self.chars.peek() == Some(&'%') {
+                // escape mechanism; the rest of the line is ignored
+                self.skip_line();
+                continue;
+            }
+
+            let line = self.line;
+            let c = match self.bump() {
+                None => return Ok(None),
+                Some(c) if c.is_whitespace() => continue,
+                Some(c) => c,
+            };
+
+            let token = match c {
+                '[' => self.lex_tag()?,
+                '{' => {
+                    let mut text = String::new();
+                    loop {
+                        match self.bump() {
+                            Some('}') => break,
+                            Some(c) => text.push(c),
+                            None => return Err(PgnParseErr::UnterminatedComment { line }),
+                        }
+                    }
+                    Token::Comment(text.trim().to_owned())
+                }
+                ';' => Token::Comment(self.skip_line().trim().to_owned()),
+                '(' => Token::OpenVariation,
+                ')' => Token::CloseVariation,
+                '$' => {
+                    let mut digits = String::new();
+                    while let Some(&c) = self.chars.peek() {
+                        if !c.is_ascii_digit() {
+                            break;
+                        }
+                        digits.push(c);
+                        self.bump();
+                    }
+                    let nag = digits.parse().map_err(|_| PgnParseErr::UnexpectedToken {
+                        line,
+                        token: format!("${}", digits),
+                    })?;
+                    Token::Nag(nag)
+                }
+                _ => {
+                    let mut word = c.to_string();
+                    while let Some(&c) = self.chars.peek() {
+                        if c.is_whitespace() || "{}()[];$".contains(c) {
+                            break;
+                        }
+                        word.push(c);
+                        self.bump();
+                    }
+
+                    match self.classify_word(line, word)? {
+                        Some(token) => token,
+                        None => continue,
+                    }
+                }
+            };
+
+            return Ok(Some((line, token)));
+        }
+    }
+
+    /// Turns a word of movetext into a token. Move numbers yield `None`.
+    fn classify_word(&mut self, line: usize, word: String) -> Result<Option<Token>, PgnParseErr> {
+        if let Some(result) = GameResult::from_pgn(&word) {
+            return Ok(Some(Token::Result(result)));
+        }
+
+        // move numbers, ex. `12.` or `12...`, possibly attached to the move like `12.e4`
+        let mut word = word.as_str();
+        if !word.starts_with("0-0") {
+            let number_len = word.len();
+            word = word.trim_start_matches(|c: char| c.is_ascii_digit());
+            word = word.trim_start_matches('.');
+            if word.is_empty() {
+                return Ok(None);
+            }
+            if number_len != word.len() && !word.starts_with(char::is_alphabetic) {
+                return Err(PgnParseErr::UnexpectedToken {
+                    line,
+                    token: word.to_owned(),
+                });
+            }
+        }
+
+        // split off any suffix annotations, ex. `e4!?`
+        let san_len = word.trim_end_matches(['!', '?']).len();
+        let (san, suffix) = word.split_at(san_len);
+        let nag = match suffix {
+            "" => None,
+            "!" => Some(1),
+            "?" => Some(2),
+            "!!" => Some(3),
+            "??" => Some(4),
+            "!?" => Some(5),
+            "?!" => Some(6),
+            _ => {
+                return Err(PgnParseErr::UnexpectedToken {
+                    line,
+                    token: word.to_owned(),
+                })
+            }
+        };
+        if san.is_empty() {
+            return Ok(nag.map(Token::Nag));
+        }
+
+        // the suffix is handed back as a separate token on the next call
+        if let Some(nag) = nag {
+            self.pending_nag = Some((line, nag));
+        }
+        Ok(Some(Token::San(san.to_owned())))
+    }
+}
+
+/// A line of moves that is being parsed.
+struct LineState {
+    /// The position before the last move of the line.
+    before: Board,
+    board: Board,
+    moves: Vec<PgnMove>,
+    /// Comments that came before the first move of the line.
+    comments: Vec<String>,
+}
+
+impl LineState {
+    fn new(board: Board) -> Self {
+        LineState {
+            before: board,
+            board,
+            moves: vec![],
+            comments: vec![],
+        }
+    }
+}
+
+impl PgnGame {
+    /// Parses every game in a PGN file.
+    ///
+    /// Each move is replayed on a [`Board`] to make sure that it is legal.
+    pub fn parse_all(pgn: &str) -> Result<Vec<PgnGame>, PgnParseErr> {
+        let mut lexer = Lexer::new(pgn);
+        let mut games = vec![];
+
+        while let Some(game) = PgnGame::parse_next(&mut lexer)? {
+            games.push(game);
+        }
+
+        Ok(games)
+    }
+
+    /// Parses the first game in a PGN string.
+    pub fn parse(pgn: &str) -> Result<PgnGame, PgnParseErr> {
+        PgnGame::parse_next(&mut Lexer::new(pgn)).map(Option::unwrap_or_default)
+    }
+
+    fn parse_next(lexer: &mut Lexer) -> Result<Option<PgnGame>, PgnParseErr> {
+        let mut game = PgnGame::default();
+        let mut lines: Vec<LineState> = vec![];
+        let mut has_content = false;
+
+        while let Some((line, token)) = lexer.next_token()? {
+            has_content = true;
+
+            let token = match token {
+                Token::Tag(name, value) if lines.is_empty() => {
+                    game.tags.push((name, value));
+                    continue;
+                }
+                token => token,
+            };
+
+            if lines.is_empty() {
+                lines.push(LineState::new(game.starting_board()?));
+            }
+            let is_variation = lines.len() > 1;
+            let current = lines.last_mut().unwrap();
+
+            match token {
+                Token::Tag(..) => {
+                    return Err(PgnParseErr::UnexpectedToken {
+                        line,
+                        token: "[".to_owned(),
+                    })
+                }
+                Token::Comment(comment) => match current.moves.last_mut() {
+                    Some(last) => last.comments.push(comment),
+                    None => current.comments.push(comment),
+                },
+                Token::Nag(nag) => match current.moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => {
+                        return Err(PgnParseErr::UnexpectedToken {
+                            line,
+                            token: format!("${}", nag),
+                        })
+                    }
+                },
+                Token::San(san) => {
+                    let umove =
+                        current
+                            .board
+                            .parse_san(&san)
+                            .map_err(|err| PgnParseErr::InvalidMove {
+                                line,
+                                san: san.clone(),
+                                err,
+                            })?;
+                    let mut pgn_move = PgnMove::new(umove, current.board.move_to_san(umove));
+                    if is_variation && current.moves.is_empty() {
+                        // comments at the start of a variation are kept with its first move
+                        pgn_move.comments.append(&mut current.comments);
+                    }
+
+                    current.before = current.board;
+                    current
+                        .board
+                        .try_move(umove)
+                        .expect("a parsed SAN move should be legal");
+                    current.moves.push(pgn_move);
+                }
+                Token::OpenVariation => {
+                    if current.moves.is_empty() {
+                        return Err(PgnParseErr::UnbalancedVariation { line });
+                    }
+                    let before = current.before;
+                    lines.push(LineState::new(before));
+                }
+                Token::CloseVariation => {
+                    if lines.len() < 2 {
+                        return Err(PgnParseErr::UnbalancedVariation { line });
+                    }
+                    let variation = lines.pop().unwrap();
+                    let parent = lines.last_mut().unwrap();
+                    parent
+                        .moves
+                        .last_mut()
+                        .expect("a variation is only started after a move")
+                        .variations
+                        .push(variation.moves);
+                }
+                Token::Result(result) => {
+                    if lines.len() > 1 {
+                        return Err(PgnParseErr::UnbalancedVariation { line });
+                    }
+                    game.result = result;
+                    break;
+                }
+            }
+        }
+
+        if !has_content {
+            return Ok(None);
+        }
+        if lines.len() > 1 {
+            return Err(PgnParseErr::UnbalancedVariation { line: lexer.line });
+        }
+        if let Some(main_line) = lines.pop() {
+            game.comments = main_line.comments;
+            game.moves = main_line.moves;
+        }
+
+        Ok(Some(game))
+    }
+
+    /// Gets the value of a tag.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of a tag, replacing it if it already exists.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+    }
+
+    /// Gets the position that the game starts from. This is the `FEN` tag if there is one,
+    ///     played with the rules of the `Variant` tag.
+    pub fn starting_board(&self) -> Result<Board, PgnParseErr> {
+        let (variant, chess960) = match self.tag("Variant") {
+            None => (Variant::Standard, false),
+            Some(name) if name.eq_ignore_ascii_case("chess960") => (Variant::Standard, true),
+            Some(name) => (
+                Variant::from_name(name)
+                    .ok_or_else(|| PgnParseErr::UnknownVariant(name.to_owned()))?,
+                false,
+            ),
+        };
+
+        match self.tag("FEN") {
+            Some(fen) => {
+                let mut board = Board::empty();
+                board.set_variant(variant);
+                board.set_chess960(chess960);
+                board.load_fen(fen).map_err(PgnParseErr::InvalidFen)?;
+                Ok(board)
+            }
+            None => Ok(Board::new_variant(variant)),
+        }
+    }
+
+    /// Gets the position at the end of the main line.
+    pub fn final_board(&self) -> Result<Board, PgnParseErr> {
+        let mut board = self.starting_board()?;
+        for (ply, pgn_move) in self.moves.iter().enumerate() {
+            board
+                .try_move(pgn_move.umove)
+                .map_err(|err| pgn_move.illegal(ply, err))?;
+        }
+        Ok(board)
+    }
+
+    /// Writes the game in PGN export format.
+    ///
+    /// The Seven Tag Roster is always written first, filling in missing tags with `?`.
+    /// The movetext is wrapped to 80 columns.
+    /// A `}` can't be written inside a comment, so it is replaced with `]`.
+    pub fn to_pgn(&self) -> String {
+        let mut output = String::new();
+
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.to_pgn(),
+                "Date" => self.tag(name).unwrap_or("????.??.??"),
+                _ => self.tag(name).unwrap_or("?"),
+            };
+            output += &format_tag(name, value);
+        }
+        for (name, value) in self.tags.iter() {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                output += &format_tag(name, value);
+            }
+        }
+        output += "\n";
+
+        let mut tokens = vec![];
+        for comment in self.comments.iter() {
+            tokens.push(format_comment(comment));
+        }
+
+        let start = self.starting_board().unwrap_or_default();
+        let ply = (start.fullmove.max(1) as usize - 1) * 2
+            + (start.active_color == Player::Black) as usize;
+        write_line(&mut tokens, &self.moves, ply, !self.comments.is_empty());
+        tokens.push(self.result.to_pgn().to_owned());
+
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len != 0 && line_len + 1 + token.len() > PGN_LINE_WIDTH {
+                output += "\n";
+                line_len = 0;
+            }
+            if line_len != 0 {
+                output += " ";
+                line_len += 1;
+            }
+            output += &token;
+            line_len += token.len();
+        }
+        output += "\n";
+
+        output
+    }
+}
+
+fn format_tag(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+/// PGN has no way to escape a `}` inside a comment, so it is written as `]` instead.
+fn format_comment(comment: &str) -> String {
+    format!("{{{}}}", comment.replace('}', "]"))
+}
+
+/// Writes the tokens for a line of moves. `ply` is the number of halfmoves played before the
+///     first move of the line, counting from the start of the game.
+fn write_line(tokens: &mut Vec<String>, moves: &[PgnMove], mut ply: usize, mut needs_number: bool) {
+    needs_number = needs_number || ply % 2 == 1;
+
+    for pgn_move in moves {
+        let move_number = ply / 2 + 1;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", move_number));
+        } else if needs_number {
+            tokens.push(format!("{}...", move_number));
+        }
+        tokens.push(pgn_move.san.clone());
+        needs_number = false;
+
+        for nag in pgn_move.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+        for comment in pgn_move.comments.iter() {
+            tokens.push(format_comment(comment));
+            needs_number = true;
+        }
+        for variation in pgn_move.variations.iter() {
+            let start = tokens.len();
+            write_line(tokens, variation, ply, true);
+            if tokens.len() > start {
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+            needs_number = true;
+        }
+
+        ply += 1;
+    }
+}
+
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_pgn())
+    }
+}
@@ -22,7 +22,7 @@ bitflags! {
     }
 
     // Bitflags representing the castle rights for both players.
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct CastleRights: u8 {
         const WhiteKingSide = 1<<0;
         const WhiteQueenSide = 1<<1;
@@ -32,6 +32,43 @@ bitflags! {
 }
 
 impl CastleRights {
+    /// Each of the single castle rights, in the order of [`CastleRights::index`].
+    pub const EACH: [CastleRights; 4] = [
+        CastleRights::WhiteKingSide,
+        CastleRights::WhiteQueenSide,
+        CastleRights::BlackKingSide,
+        CastleRights::BlackQueenSide,
+    ];
+
+    /// Gets the castle right for a player castling to the east (king side) or west (queen side).
+    pub fn for_side(player: Player, is_east: bool) -> CastleRights {
+        match (player, is_east) {
+            (Player::White, true) => CastleRights::WhiteKingSide,
+            (Player::White, false) => CastleRights::WhiteQueenSide,
+            (Player::Black, true) => CastleRights::BlackKingSide,
+            (Player::Black, false) => CastleRights::BlackQueenSide,
+        }
+    }
+
+    /// The index of a single castle right.
+    pub fn index(&self) -> usize {
+        self.bits().trailing_zeros() as usize
+    }
+
+    /// The player a single castle right belongs to.
+    pub fn player(&self) -> Player {
+        if self.intersects(CastleRights::WhiteKingSide | CastleRights::WhiteQueenSide) {
+            Player::White
+        } else {
+            Player::Black
+        }
+    }
+
+    /// Checks if a single castle right is to the king side.
+    pub fn is_king_side(&self) -> bool {
+        self.intersects(CastleRights::WhiteKingSide | CastleRights::BlackKingSide)
+    }
+
     pub fn has_right(&self, rhs: CastleRights) -> bool {
         *self & rhs != CastleRights::empty()
     }
@@ -75,7 +112,8 @@ impl CastleRights {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
     White,
     Black,
@@ -96,6 +134,14 @@ impl Player {
         }
     }
 
+    /// The index of the player in arrays that hold something for each player, white first.
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Player::White => 0,
+            Player::Black => 1,
+        }
+    }
+
     pub(crate) fn forward_value(&self) -> isize {
         match self {
             Player::White => 1,
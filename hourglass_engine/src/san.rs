@@ -0,0 +1,236 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Board, Move, Piece, Square};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SanParseErr {
+    /// The string is not valid SAN.
+    InvalidSyntax,
+    /// No legal move matches the SAN.
+    IllegalMove,
+    /// More than one legal move matches the SAN.
+    AmbiguousMove,
+}
+
+impl Display for SanParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SanParseErr::InvalidSyntax => write!(f, "invalid SAN syntax"),
+            SanParseErr::IllegalMove => write!(f, "no legal move matches the SAN"),
+            SanParseErr::AmbiguousMove => write!(f, "more than one legal move matches the SAN"),
+        }
+    }
+}
+
+impl std::error::Error for SanParseErr {}
+
+fn piece_letter(piece: Piece) -> Option<char> {
+    match piece & Piece::PieceType {
+        Piece::King => Some('K'),
+        Piece::Queen => Some('Q'),
+        Piece::Rook => Some('R'),
+        Piece::Bishop => Some('B'),
+        Piece::Knight => Some('N'),
+        _ => None,
+    }
+}
+
+fn piece_from_letter(c: char) -> Option<Piece> {
+    match c {
+        'K' => Some(Piece::King),
+        'Q' => Some(Piece::Queen),
+        'R' => Some(Piece::Rook),
+        'B' => Some(Piece::Bishop),
+        'N' => Some(Piece::Knight),
+        _ => None,
+    }
+}
+
+impl Board {
+    /// Checks if the given move captures a piece, including en passant.
+    pub fn is_capture(&self, umove: Move) -> bool {
+        let is_pawn = self.squares[umove.origin_idx()] & Piece::PieceType == Piece::Pawn;
+        if self.is_castle(umove) {
+            return false;
+        }
+        self.squares[umove.target_idx()] != Piece::empty()
+            || (is_pawn && self.en_passant == Some(umove.target_idx()))
+    }
+
+    /// Gets the Standard Algebraic Notation for a move, ex. `Nbd2`, `exd5`, `O-O` or `e8=Q+`.
+    /// Drops in crazyhouse are written like `N@f3`, or `@e4` for pawns.
+    ///
+    /// The move should be legal in this position.
+    pub fn move_to_san(&self, umove: Move) -> String {
+        let mut san = String::new();
+        let piece = self.squares[umove.origin_idx()];
+
+        if let Some(dropped) = umove.dropped() {
+            if let Some(letter) = piece_letter(dropped) {
+                san.push(letter);
+            }
+            san.push('@');
+            san += &umove.to().to_string();
+        } else if self.is_castle(umove) {
+            if umove.target_idx() > umove.origin_idx() {
+                san += "O-O";
+            } else {
+                san += "O-O-O";
+            }
+        } else if let Some(letter) = piece_letter(piece) {
+            san.push(letter);
+
+            // find other pieces of the same type that could move to the same square
+            let others = self.with_moves(|moves| {
+                moves
+                    .iter()
+                    .copied()
+                    .filter(|m| {
+                        m.target_idx() == umove.target_idx() && m.origin_idx() != umove.origin_idx()
+                    })
+                    .filter(|m| self.squares[m.origin_idx()] == piece)
+                    .collect::<Vec<_>>()
+            });
+
+            if !others.is_empty() {
+                let from = umove.from();
+                let same_file = others.iter().any(|m| m.from().file() == from.file());
+                let same_rank = others.iter().any(|m| m.from().rank() == from.rank());
+
+                if !same_file {
+                    san.push(from.file_char());
+                } else if !same_rank {
+                    san.push(from.rank_char());
+                } else {
+                    san += &from.to_string();
+                }
+            }
+
+            if self.is_capture(umove) {
+                san += "x";
+            }
+            san += &umove.to().to_string();
+        } else {
+            // pawn move
+            if self.is_capture(umove) {
+                san.push(umove.from().file_char());
+                san += "x";
+            }
+            san += &umove.to().to_string();
+
+            if let Some(letter) = umove.promote().and_then(piece_letter) {
+                san.push('=');
+                san.push(letter);
+            }
+        }
+
+        let mut board = *self;
+        if board.try_move(umove).is_ok() && board.is_in_check(board.active_color) {
+            if board.with_moves(|moves| moves.is_empty()) {
+                san += "#";
+            } else {
+                san += "+";
+            }
+        }
+
+        san
+    }
+
+    /// Parses a move in Standard Algebraic Notation in the context of this position.
+    ///
+    /// Check, mate and annotation suffixes are ignored.
+    /// Castles may be written with either the letter `O` or the digit `0`.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanParseErr> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        if san.is_empty() || !san.is_ascii() {
+            return Err(SanParseErr::InvalidSyntax);
+        }
+
+        self.with_moves(|moves| self.find_san_move(san, moves))
+    }
+
+    /// Finds the move that a move in Standard Algebraic Notation stands for, without its suffixes.
+    fn find_san_move(&self, san: &str, moves: &[Move]) -> Result<Move, SanParseErr> {
+        if matches!(san, "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+            let is_east = san.len() == 3;
+            return moves
+                .iter()
+                .copied()
+                .find(|m| self.is_castle(*m) && (m.target_idx() > m.origin_idx()) == is_east)
+                .ok_or(SanParseErr::IllegalMove);
+        }
+
+        if let Some((piece, to)) = san.split_once('@') {
+            let piece = match piece {
+                "" | "P" => Piece::Pawn,
+                letter => letter
+                    .chars()
+                    .next()
+                    .and_then(piece_from_letter)
+                    .filter(|_| letter.len() == 1)
+                    .ok_or(SanParseErr::InvalidSyntax)?,
+            };
+            let to = Square::from_str(to).map_err(|_| SanParseErr::InvalidSyntax)?;
+            return moves
+                .iter()
+                .copied()
+                .find(|m| m.dropped() == Some(piece) && m.to() == to)
+                .ok_or(SanParseErr::IllegalMove);
+        }
+
+        let mut chars = san.chars().peekable();
+        let piece_type = match chars.peek().copied().and_then(piece_from_letter) {
+            Some(piece) => {
+                chars.next();
+                piece
+            }
+            None => Piece::Pawn,
+        };
+
+        // everything else but the promotion is squares, disambiguation, or the capture marker
+        let mut rest = chars.collect::<String>();
+        let mut promote = None;
+        if piece_type == Piece::Pawn {
+            if let Some(letter) = rest.chars().last().and_then(piece_from_letter) {
+                promote = Some(letter);
+                rest.pop();
+                if rest.ends_with('=') {
+                    rest.pop();
+                }
+            }
+        }
+
+        let rest = rest.replace('x', "");
+        if rest.len() < 2 || rest.len() > 4 {
+            return Err(SanParseErr::InvalidSyntax);
+        }
+        let (disambiguation, to) = rest.split_at(rest.len() - 2);
+        let to = Square::from_str(to).map_err(|_| SanParseErr::InvalidSyntax)?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in disambiguation.chars() {
+            match c {
+                'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some(c as usize - '1' as usize),
+                _ => return Err(SanParseErr::InvalidSyntax),
+            }
+        }
+
+        let mut candidates = moves.iter().copied().filter(|m| {
+            m.to() == to
+                && self.squares[m.origin_idx()] & Piece::PieceType == piece_type
+                && from_file.is_none_or(|file| m.from().file() == file)
+                && from_rank.is_none_or(|rank| m.from().rank() == rank)
+                && m.promote() == promote
+                && !self.is_castle(*m)
+        });
+
+        let umove = candidates.next().ok_or(SanParseErr::IllegalMove)?;
+        if candidates.next().is_some() {
+            return Err(SanParseErr::AmbiguousMove);
+        }
+
+        Ok(umove)
+    }
+}
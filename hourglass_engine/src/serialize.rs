@@ -0,0 +1,206 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! Values that have a standard text form are serialized as strings: moves in UCI notation,
+//!     pieces and castle rights as in a FEN, squares by name and results as in a PGN.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    display::piece_symbol, fen::piece_from_fen, Board, CastleRights, Game, GameResult, Move, Piece,
+    Square, Variant,
+};
+
+fn serialize_display<T: Display, S: Serializer>(value: &T, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(value)
+}
+
+fn deserialize_from_str<'de, T, D>(d: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    s.parse().map_err(D::Error::custom)
+}
+
+/// A deserialized move has no flags, like a parsed one. Use [`Board::resolve_move`]
+///     to get the move that is played on a board.
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_from_str(d)
+    }
+}
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        deserialize_from_str(d)
+    }
+}
+
+/// A piece is serialized as its letter in a FEN, ex. `N` for a white knight.
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_char(piece_symbol(*self, false))
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let c = char::deserialize(d)?;
+        piece_from_fen(c).ok_or_else(|| D::Error::custom(format!("invalid piece '{}'", c)))
+    }
+}
+
+/// Castle rights are serialized as in a FEN, ex. `KQkq` or `-`.
+impl Serialize for CastleRights {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.to_fen())
+    }
+}
+
+impl<'de> Deserialize<'de> for CastleRights {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        if s == "-" {
+            return Ok(CastleRights::empty());
+        }
+        s.chars().try_fold(CastleRights::empty(), |rights, c| {
+            let right = match c {
+                'K' => CastleRights::WhiteKingSide,
+                'Q' => CastleRights::WhiteQueenSide,
+                'k' => CastleRights::BlackKingSide,
+                'q' => CastleRights::BlackQueenSide,
+                _ => return Err(D::Error::custom(format!("invalid castle right '{}'", c))),
+            };
+            Ok(rights | right)
+        })
+    }
+}
+
+/// A variant is serialized by its UCI name, and can be deserialized from any name
+///     that [`Variant::from_name`] accepts.
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.uci_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        Variant::from_name(&s).ok_or_else(|| D::Error::custom(format!("unknown variant \"{}\"", s)))
+    }
+}
+
+/// A result is serialized as in a PGN, ex. `1-0` or `*`.
+impl Serialize for GameResult {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.to_pgn())
+    }
+}
+
+impl<'de> Deserialize<'de> for GameResult {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        GameResult::from_pgn(&s)
+            .ok_or_else(|| D::Error::custom(format!("invalid result \"{}\"", s)))
+    }
+}
+
+/// The serialized form of a [`Board`]. The fen holds the position, but not the rules
+///     it is played with.
+#[derive(Serialize, Deserialize)]
+struct BoardRecord {
+    fen: String,
+    #[serde(default)]
+    variant: Variant,
+    #[serde(default)]
+    chess960: bool,
+}
+
+/// A board is serialized as its fen, along with its variant and whether it is chess960,
+///     ex. `{"fen": "...", "variant": "chess", "chess960": false}`.
+///
+/// Only the fen is required when deserializing.
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        BoardRecord {
+            fen: self.get_fen(),
+            variant: self.variant(),
+            chess960: self.is_chess960(),
+        }
+        .serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let record = BoardRecord::deserialize(d)?;
+        let mut board = Board::empty();
+        board.set_variant(record.variant);
+        board.set_chess960(record.chess960);
+        board.load_fen(&record.fen).map_err(D::Error::custom)?;
+        Ok(board)
+    }
+}
+
+/// The serialized form of a [`Game`].
+#[derive(Serialize, Deserialize)]
+struct GameRecord {
+    start: Board,
+    moves: Vec<Move>,
+    /// The moves that can be redone, with the next one first.
+    #[serde(default)]
+    undone: Vec<Move>,
+}
+
+/// A game is serialized as its starting position and the moves played from it,
+///     along with the moves that were undone and can be redone.
+///
+/// The moves are replayed when deserializing, so an illegal move is an error.
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        GameRecord {
+            start: *self.starting_board(),
+            moves: self.moves().iter().map(|played| played.umove).collect(),
+            undone: self
+                .undone
+                .iter()
+                .rev()
+                .map(|played| played.umove)
+                .collect(),
+        }
+        .serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let record = GameRecord::deserialize(d)?;
+        let mut game = Game::from_board(record.start);
+        for umove in record.moves.iter().chain(record.undone.iter()) {
+            game.play(*umove).map_err(|err| {
+                D::Error::custom(format!("move {} can't be played: {}", umove, err))
+            })?;
+        }
+        for _ in &record.undone {
+            game.undo();
+        }
+        Ok(game)
+    }
+}
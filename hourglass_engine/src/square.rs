@@ -0,0 +1,321 @@
+use std::{
+    fmt::Display,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
+    str::FromStr,
+};
+
+/// One of the 64 squares of the board, from a1 = 0 to h8 = 63.
+///
+/// Squares go along the 1st rank first, so a2 is 8 and b2 is 9.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SquareParseErr {
+    /// A square name is a file letter and a rank number, ex. `e4`.
+    InvalidLength,
+    InvalidFile,
+    InvalidRank,
+}
+
+impl Display for SquareParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SquareParseErr::InvalidLength => write!(f, "a square is a file and a rank, ex. e4"),
+            SquareParseErr::InvalidFile => write!(f, "the file must be from a to h"),
+            SquareParseErr::InvalidRank => write!(f, "the rank must be from 1 to 8"),
+        }
+    }
+}
+
+impl std::error::Error for SquareParseErr {}
+
+impl Square {
+    pub const A1: Square = Square(0);
+    pub const B1: Square = Square(1);
+    pub const C1: Square = Square(2);
+    pub const D1: Square = Square(3);
+    pub const E1: Square = Square(4);
+    pub const F1: Square = Square(5);
+    pub const G1: Square = Square(6);
+    pub const H1: Square = Square(7);
+    pub const A2: Square = Square(8);
+    pub const B2: Square = Square(9);
+    pub const C2: Square = Square(10);
+    pub const D2: Square = Square(11);
+    pub const E2: Square = Square(12);
+    pub const F2: Square = Square(13);
+    pub const G2: Square = Square(14);
+    pub const H2: Square = Square(15);
+    pub const A3: Square = Square(16);
+    pub const B3: Square = Square(17);
+    pub const C3: Square = Square(18);
+    pub const D3: Square = Square(19);
+    pub const E3: Square = Square(20);
+    pub const F3: Square = Square(21);
+    pub const G3: Square = Square(22);
+    pub const H3: Square = Square(23);
+    pub const A4: Square = Square(24);
+    pub const B4: Square = Square(25);
+    pub const C4: Square = Square(26);
+    pub const D4: Square = Square(27);
+    pub const E4: Square = Square(28);
+    pub const F4: Square = Square(29);
+    pub const G4: Square = Square(30);
+    pub const H4: Square = Square(31);
+    pub const A5: Square = Square(32);
+    pub const B5: Square = Square(33);
+    pub const C5: Square = Square(34);
+    pub const D5: Square = Square(35);
+    pub const E5: Square = Square(36);
+    pub const F5: Square = Square(37);
+    pub const G5: Square = Square(38);
+    pub const H5: Square = Square(39);
+    pub const A6: Square = Square(40);
+    pub const B6: Square = Square(41);
+    pub const C6: Square = Square(42);
+    pub const D6: Square = Square(43);
+    pub const E6: Square = Square(44);
+    pub const F6: Square = Square(45);
+    pub const G6: Square = Square(46);
+    pub const H6: Square = Square(47);
+    pub const A7: Square = Square(48);
+    pub const B7: Square = Square(49);
+    pub const C7: Square = Square(50);
+    pub const D7: Square = Square(51);
+    pub const E7: Square = Square(52);
+    pub const F7: Square = Square(53);
+    pub const G7: Square = Square(54);
+    pub const H7: Square = Square(55);
+    pub const A8: Square = Square(56);
+    pub const B8: Square = Square(57);
+    pub const C8: Square = Square(58);
+    pub const D8: Square = Square(59);
+    pub const E8: Square = Square(60);
+    pub const F8: Square = Square(61);
+    pub const G8: Square = Square(62);
+    pub const H8: Square = Square(63);
+
+    /// Gets the square at the index, or `None` if it is not less than 64.
+    pub const fn new(idx: usize) -> Option<Square> {
+        match idx < 64 {
+            true => Some(Square(idx as u8)),
+            false => None,
+        }
+    }
+
+    /// Gets the square on the file and rank, which are 0-indexed.
+    pub const fn from_file_rank(file: usize, rank: usize) -> Option<Square> {
+        match file < 8 && rank < 8 {
+            true => Some(Square((rank * 8 + file) as u8)),
+            false => None,
+        }
+    }
+
+    /// Gets the square at an index that is known to be on the board.
+    pub(crate) const fn from_idx(idx: usize) -> Square {
+        debug_assert!(idx < 64);
+        Square(idx as u8)
+    }
+
+    /// Every square, from a1 to h8.
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+
+    pub const fn idx(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The file of the square, from 0 for the a-file to 7 for the h-file.
+    pub const fn file(self) -> usize {
+        self.0 as usize % 8
+    }
+
+    /// The rank of the square, from 0 for the 1st rank to 7 for the 8th rank.
+    pub const fn rank(self) -> usize {
+        self.0 as usize / 8
+    }
+
+    pub fn file_char(self) -> char {
+        (b'a' + self.file() as u8) as char
+    }
+
+    pub fn rank_char(self) -> char {
+        (b'1' + self.rank() as u8) as char
+    }
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> usize {
+        square.idx()
+    }
+}
+
+impl FromStr for Square {
+    type Err = SquareParseErr;
+
+    /// Parses a square name, ex. `e4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let &[file, rank] = s.as_bytes() else {
+            return Err(SquareParseErr::InvalidLength);
+        };
+        if !(b'a'..=b'h').contains(&file) {
+            return Err(SquareParseErr::InvalidFile);
+        }
+        if !(b'1'..=b'8').contains(&rank) {
+            return Err(SquareParseErr::InvalidRank);
+        }
+        Ok(Square((rank - b'1') * 8 + (file - b'a')))
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.file_char(), self.rank_char())
+    }
+}
+
+impl std::fmt::Debug for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// A set of squares, with one bit for each square.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SquareSet(u64);
+
+impl SquareSet {
+    pub const EMPTY: SquareSet = SquareSet(0);
+    pub const FULL: SquareSet = SquareSet(u64::MAX);
+
+    /// Creates a set from its bits, where bit `n` is the square with index `n`.
+    pub const fn from_bits(bits: u64) -> Self {
+        SquareSet(bits)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & (1 << square.0) != 0
+    }
+
+    pub fn insert(&mut self, square: Square) {
+        self.0 |= 1 << square.0;
+    }
+
+    pub fn remove(&mut self, square: Square) {
+        self.0 &= !(1 << square.0);
+    }
+
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The lowest square in the set, if it isn't empty.
+    pub fn first(self) -> Option<Square> {
+        match self.is_empty() {
+            true => None,
+            false => Some(Square(self.0.trailing_zeros() as u8)),
+        }
+    }
+
+    /// Iterates over the squares from a1 to h8.
+    pub fn iter(self) -> SquareSetIter {
+        SquareSetIter(self.0)
+    }
+}
+
+impl From<Square> for SquareSet {
+    fn from(square: Square) -> Self {
+        SquareSet(1 << square.0)
+    }
+}
+
+impl BitOr for SquareSet {
+    type Output = SquareSet;
+
+    fn bitor(self, rhs: SquareSet) -> SquareSet {
+        SquareSet(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for SquareSet {
+    fn bitor_assign(&mut self, rhs: SquareSet) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for SquareSet {
+    type Output = SquareSet;
+
+    fn bitand(self, rhs: SquareSet) -> SquareSet {
+        SquareSet(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for SquareSet {
+    fn bitand_assign(&mut self, rhs: SquareSet) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Not for SquareSet {
+    type Output = SquareSet;
+
+    fn not(self) -> SquareSet {
+        SquareSet(!self.0)
+    }
+}
+
+impl FromIterator<Square> for SquareSet {
+    fn from_iter<T: IntoIterator<Item = Square>>(iter: T) -> Self {
+        let mut set = SquareSet::EMPTY;
+        for square in iter {
+            set.insert(square);
+        }
+        set
+    }
+}
+
+impl IntoIterator for SquareSet {
+    type Item = Square;
+    type IntoIter = SquareSetIter;
+
+    fn into_iter(self) -> SquareSetIter {
+        self.iter()
+    }
+}
+
+impl std::fmt::Debug for SquareSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the squares of a [`SquareSet`].
+pub struct SquareSetIter(u64);
+
+impl Iterator for SquareSetIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        let square = SquareSet(self.0).first()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for SquareSetIter {}
@@ -0,0 +1,336 @@
+use std::fmt::Write;
+
+use crate::{Board, Move, Piece, Player, Square};
+
+/// The size of a square, in the units of the SVG's `viewBox`.
+const SQUARE_SIZE: f32 = 45.0;
+/// The space around the board for the coordinates.
+const MARGIN: f32 = 15.0;
+
+/// The kinds of pieces, in the order that their shapes are defined.
+const PIECE_KINDS: [(Piece, &str); 6] = [
+    (Piece::Pawn, "pawn"),
+    (Piece::Knight, "knight"),
+    (Piece::Bishop, "bishop"),
+    (Piece::Rook, "rook"),
+    (Piece::Queen, "queen"),
+    (Piece::King, "king"),
+];
+
+/// The outlines of a piece, filled with the piece's color, in a 45 by 45 square.
+fn piece_outlines(piece: Piece) -> &'static [&'static str] {
+    match piece & Piece::PieceType {
+        Piece::Pawn => &[
+            "M 22.5,9 L 26,10.5 L 27.5,14 L 26,17.5 L 23.5,19 L 27,21 L 29,36 L 16,36 L 18,21 L 21.5,19 L 19,17.5 L 17.5,14 L 19,10.5 Z",
+            "M 12,39 L 33,39 L 33,36 L 12,36 Z",
+        ],
+        Piece::Knight => &[
+            "M 14,39 L 34,39 L 33,27 L 30,18 L 26,12 L 25,7 L 22,10.5 L 19.5,8 L 18.5,12.5 L 14,17 L 10,25 L 12,28.5 L 16,27 L 21,23.5 L 19,29 L 15,33 Z",
+        ],
+        Piece::Bishop => &[
+            "M 22.5,7 L 27,11 L 29.5,17 L 29,25 L 31,33 L 14,33 L 16,25 L 15.5,17 L 18,11 Z",
+            "M 11,39 L 34,39 L 34,35 L 11,35 Z",
+        ],
+        Piece::Rook => &[
+            "M 11,39 L 34,39 L 34,36 L 31,36 L 31,17 L 34,17 L 34,10 L 30,10 L 30,13 L 26,13 L 26,10 L 19,10 L 19,13 L 15,13 L 15,10 L 11,10 L 11,17 L 14,17 L 14,36 L 11,36 Z",
+        ],
+        Piece::Queen => &[
+            "M 9,13 L 14,31 L 31,31 L 36,13 L 29,24 L 27,10 L 22.5,23 L 18,10 L 16,24 Z",
+            "M 12,39 L 33,39 L 31,31 L 14,31 Z",
+        ],
+        _ => &[
+            "M 21,4 L 24,4 L 24,7 L 27,7 L 27,10 L 24,10 L 24,14 L 21,14 L 21,10 L 18,10 L 18,7 L 21,7 Z",
+            "M 22.5,14 L 29,15 L 34,19 L 34,26 L 31,33 L 14,33 L 11,26 L 11,19 L 16,15 Z",
+            "M 12,39 L 33,39 L 31,33 L 14,33 Z",
+        ],
+    }
+}
+
+/// The details of a piece, drawn in the other player's color so they show up on the piece.
+fn piece_details(piece: Piece) -> &'static str {
+    match piece & Piece::PieceType {
+        Piece::Knight => r#"<circle cx="20" cy="14.5" r="1.5"/>"#,
+        Piece::Bishop => r#"<path d="M 22.5,15 L 22.5,24 M 18.5,19.5 L 26.5,19.5" fill="none"/>"#,
+        Piece::Queen => {
+            r#"<circle cx="9" cy="12" r="2"/><circle cx="18" cy="9" r="2"/><circle cx="27" cy="9" r="2"/><circle cx="36" cy="12" r="2"/>"#
+        }
+        _ => "",
+    }
+}
+
+/// The colors of an SVG diagram, as CSS colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgColors {
+    pub light_square: String,
+    pub dark_square: String,
+    /// Drawn over the squares of the last move.
+    pub last_move: String,
+    pub arrow: String,
+    pub circle: String,
+    pub coordinates: String,
+}
+
+/// The same squares as the GUI.
+impl Default for SvgColors {
+    fn default() -> Self {
+        SvgColors {
+            light_square: "#E5F2FF".to_owned(),
+            dark_square: "#3399E5".to_owned(),
+            last_move: "#FFE066".to_owned(),
+            arrow: "#15781B".to_owned(),
+            circle: "#15781B".to_owned(),
+            coordinates: "#333333".to_owned(),
+        }
+    }
+}
+
+/// How [`Board::to_svg`] draws the board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgOptions {
+    pub colors: SvgColors,
+    /// The width and height of the image, in pixels.
+    pub size: u32,
+    /// Write the files and ranks around the board.
+    pub coordinates: bool,
+    /// Draw the board from black's side.
+    pub flipped: bool,
+    pub last_move: Option<Move>,
+    /// Arrows drawn from the first square to the second.
+    pub arrows: Vec<(Square, Square)>,
+    pub circles: Vec<Square>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            colors: SvgColors::default(),
+            size: 400,
+            coordinates: true,
+            flipped: false,
+            last_move: None,
+            arrows: Vec::new(),
+            circles: Vec::new(),
+        }
+    }
+}
+
+impl SvgOptions {
+    fn margin(&self) -> f32 {
+        match self.coordinates {
+            true => MARGIN,
+            false => 0.0,
+        }
+    }
+
+    /// Gets the top left corner of a square in the `viewBox`.
+    fn corner(&self, square: Square) -> (f32, f32) {
+        let (column, row) = match self.flipped {
+            false => (square.file(), 7 - square.rank()),
+            true => (7 - square.file(), square.rank()),
+        };
+        (
+            self.margin() + column as f32 * SQUARE_SIZE,
+            self.margin() + row as f32 * SQUARE_SIZE,
+        )
+    }
+
+    fn center(&self, square: Square) -> (f32, f32) {
+        let (x, y) = self.corner(square);
+        (x + SQUARE_SIZE / 2.0, y + SQUARE_SIZE / 2.0)
+    }
+}
+
+fn piece_id(piece: Piece) -> String {
+    let color = match piece.is_color(Player::White) {
+        true => "white",
+        false => "black",
+    };
+    let (_, name) = PIECE_KINDS
+        .iter()
+        .find(|(kind, _)| *kind == piece & Piece::PieceType)
+        .expect("a piece on the board has a kind");
+    format!("{}-{}", color, name)
+}
+
+impl Board {
+    /// Draws the board as a self-contained SVG image, with the pieces as vector shapes.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let colors = &options.colors;
+        let full_size = 8.0 * SQUARE_SIZE + 2.0 * options.margin();
+        let mut svg = String::new();
+
+        // writing to a string can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" viewBox="0 0 {} {}" width="{}" height="{}">"#,
+            full_size, full_size, options.size, options.size
+        );
+
+        // each kind of piece on the board is defined once, and used on its squares
+        svg += "<defs>\n";
+        for player in [Player::White, Player::Black] {
+            let (fill, detail) = match player {
+                Player::White => ("#FFFFFF", "#000000"),
+                Player::Black => ("#000000", "#FFFFFF"),
+            };
+            for (kind, _) in PIECE_KINDS {
+                let piece = kind | player.to_piece_color();
+                if !self.squares.contains(&piece) {
+                    continue;
+                }
+                let _ = write!(
+                    svg,
+                    r##"<g id="{}" fill="{}" stroke="#000000" stroke-width="1.5" stroke-linejoin="round">"##,
+                    piece_id(piece),
+                    fill
+                );
+                for outline in piece_outlines(piece) {
+                    let _ = write!(svg, r#"<path d="{}"/>"#, outline);
+                }
+                let details = piece_details(piece);
+                if !details.is_empty() {
+                    let _ = write!(
+                        svg,
+                        r#"<g fill="{0}" stroke="{0}">{1}</g>"#,
+                        detail, details
+                    );
+                }
+                svg += "</g>\n";
+            }
+        }
+        svg += "</defs>\n";
+
+        if options.coordinates {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="0" y="0" width="{0}" height="{0}" fill="{1}"/>"#,
+                full_size, colors.light_square
+            );
+        }
+
+        for square in Square::all() {
+            let (x, y) = options.corner(square);
+            let color = match (square.file() + square.rank()) % 2 == 0 {
+                true => &colors.dark_square,
+                false => &colors.light_square,
+            };
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, SQUARE_SIZE, SQUARE_SIZE, color
+            );
+        }
+
+        if let Some(last_move) = options.last_move {
+            let mut squares = vec![last_move.to()];
+            if !last_move.is_drop() {
+                squares.push(last_move.from());
+            }
+            for square in squares {
+                let (x, y) = options.corner(square);
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.6"/>"#,
+                    x, y, SQUARE_SIZE, SQUARE_SIZE, colors.last_move
+                );
+            }
+        }
+
+        if options.coordinates {
+            let _ = write!(
+                svg,
+                r#"<g fill="{}" font-family="sans-serif" font-size="10" text-anchor="middle">"#,
+                colors.coordinates
+            );
+            for idx in 0..8 {
+                let (x, _) = options.center(Square::from_idx(idx));
+                let (_, y) = options.center(Square::from_idx(idx * 8));
+                let square = Square::from_idx(idx * 9);
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}">{}</text><text x="{}" y="{}">{}</text>"#,
+                    x,
+                    full_size - 4.0,
+                    square.file_char(),
+                    MARGIN / 2.0,
+                    y + 3.5,
+                    square.rank_char()
+                );
+            }
+            svg += "</g>\n";
+        }
+
+        for square in Square::all() {
+            let piece = self.piece_on(square);
+            if piece == Piece::empty() {
+                continue;
+            }
+            let (x, y) = options.corner(square);
+            let _ = writeln!(
+                svg,
+                r##"<use xlink:href="#{}" transform="translate({}, {})"/>"##,
+                piece_id(piece),
+                x,
+                y
+            );
+        }
+
+        for square in &options.circles {
+            let (x, y) = options.center(*square);
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="3" stroke-opacity="0.8"/>"#,
+                x,
+                y,
+                SQUARE_SIZE / 2.0 - 2.0,
+                colors.circle
+            );
+        }
+
+        for (from, to) in &options.arrows {
+            svg += &arrow(options, *from, *to);
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+/// Draws an arrow between the centers of two squares, as a line with a triangle at the end.
+fn arrow(options: &SvgOptions, from: Square, to: Square) -> String {
+    const HEAD_LENGTH: f32 = 15.0;
+    const HEAD_WIDTH: f32 = 12.0;
+
+    let (x1, y1) = options.center(from);
+    let (x2, y2) = options.center(to);
+    let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+    if length == 0.0 {
+        return String::new();
+    }
+
+    // along the arrow, and across it
+    let (dx, dy) = ((x2 - x1) / length, (y2 - y1) / length);
+    let (nx, ny) = (-dy, dx);
+    let (base_x, base_y) = (x2 - dx * HEAD_LENGTH, y2 - dy * HEAD_LENGTH);
+    let half = HEAD_WIDTH / 2.0;
+
+    format!(
+        concat!(
+            r#"<g fill="{0}" stroke="{0}" opacity="0.8">"#,
+            r#"<line x1="{1:.1}" y1="{2:.1}" x2="{3:.1}" y2="{4:.1}" stroke-width="6" stroke-linecap="round"/>"#,
+            r#"<polygon points="{5:.1},{6:.1} {7:.1},{8:.1} {9:.1},{10:.1}" stroke="none"/>"#,
+            "</g>\n"
+        ),
+        options.colors.arrow,
+        x1,
+        y1,
+        base_x,
+        base_y,
+        x2,
+        y2,
+        base_x + nx * half,
+        base_y + ny * half,
+        base_x - nx * half,
+        base_y - ny * half,
+    )
+}
@@ -0,0 +1,1242 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use lazy_static::lazy_static;
+
+use crate::{square_distance, Board, MaterialSignature, Move, MoveList, Piece, Player, Variant};
+
+/// The first bytes of a WDL table.
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+/// The first bytes of a DTZ table.
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+/// The most pieces that a Syzygy table can have.
+const MAX_PIECES: usize = 7;
+
+/// A DTZ table stores black to move, instead of white to move.
+const FLAG_STM: u8 = 1;
+/// The DTZ values go through a map for each outcome.
+const FLAG_MAPPED: u8 = 2;
+/// The DTZ values of wins are in plies, instead of moves.
+const FLAG_WIN_PLIES: u8 = 4;
+/// The DTZ values of losses are in plies, instead of moves.
+const FLAG_LOSS_PLIES: u8 = 8;
+/// The DTZ maps hold 16 bit values.
+const FLAG_WIDE: u8 = 16;
+/// Every position has the same value, so there is no compressed data.
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// The number of ways to place three unique pieces, with the first in the a1-d1-d4 triangle.
+const UNIQUE_PIECES_SIZE: u64 = 31332;
+/// The number of ways to place two kings, with the first in the a1-d1-d4 triangle.
+const KINGS_SIZE: u64 = 462;
+
+/// The game theoretical outcome of a position for the side to move, with perfect play.
+///
+/// A cursed win can't be forced before the fifty move rule draws the game,
+///     and a blessed loss is one that the fifty move rule saves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    /// Gets the outcome from its value in a WDL table.
+    fn from_value(value: u16) -> Option<Wdl> {
+        match value {
+            0 => Some(Wdl::Loss),
+            1 => Some(Wdl::BlessedLoss),
+            2 => Some(Wdl::Draw),
+            3 => Some(Wdl::CursedWin),
+            4 => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+
+    /// 1 for wins, -1 for losses and 0 for draws.
+    fn signum(self) -> i32 {
+        (self as i32 - 2).signum()
+    }
+}
+
+impl std::ops::Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TablebaseErr {
+    Io(std::io::Error),
+    /// The directory doesn't have any `.rtbw` or `.rtbz` files.
+    NoTables(PathBuf),
+}
+
+impl Display for TablebaseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TablebaseErr::Io(err) => write!(f, "failed to read the tablebase: {}", err),
+            TablebaseErr::NoTables(path) => {
+                write!(f, "no Syzygy tables in \"{}\"", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TablebaseErr {}
+
+impl From<std::io::Error> for TablebaseErr {
+    fn from(err: std::io::Error) -> Self {
+        TablebaseErr::Io(err)
+    }
+}
+
+/// Squares are `rank * 8 + file`, so this is positive above the a1-h8 diagonal,
+///     negative below it, and 0 on it.
+fn off_diagonal(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+/// Flips a square over the a1-h8 diagonal.
+fn flip_diagonal(square: usize) -> usize {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+/// The lookup tables for turning positions into indexes.
+struct IndexMaps {
+    /// Numbers the squares below the a1-h8 diagonal 0-27.
+    b1h1h7: [u64; 64],
+    /// Numbers the squares in the a1-d1-d4 triangle 0-9, with the diagonal last.
+    a1d1d4: [u64; 64],
+    /// Numbers the legal placements of two kings, with the first in the a1-d1-d4 triangle,
+    ///     indexed by the first king's number in `a1d1d4` and the second king's square.
+    kings: [[u64; 64]; 10],
+    /// The number of ways to choose `k` of `n` things, indexed by `k` and `n`.
+    binomial: [[u64; 64]; MAX_PIECES],
+    /// Numbers the squares a pawn can be on 0-47, so that the highest number is the
+    ///     pawn nearest to an edge, and of those, the one on the lowest rank.
+    pawns: [u64; 64],
+    /// Where the index of the leading pawns starts, indexed by the number of leading pawns
+    ///     and the square of the leading one.
+    lead_pawn_idx: [[u64; 64]; 6],
+    /// The number of ways to place the leading pawns, indexed by the number
+    ///     of leading pawns and the file of the leading one.
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+lazy_static! {
+    static ref MAPS: IndexMaps = {
+        let mut maps = IndexMaps {
+            b1h1h7: [0; 64],
+            a1d1d4: [0; 64],
+            kings: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES],
+            pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        for (code, square) in (0..64).filter(|s| off_diagonal(*s) < 0).enumerate() {
+            maps.b1h1h7[square] = code as u64;
+        }
+
+        let mut code = 0;
+        let mut diagonal = vec![];
+        for square in (0..28).filter(|s| s % 8 < 4) {
+            match off_diagonal(square) {
+                0 => diagonal.push(square),
+                off if off < 0 => {
+                    maps.a1d1d4[square] = code;
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+        for square in diagonal {
+            maps.a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // placements with both kings on the diagonal come last
+        let mut code = 0;
+        let mut both_on_diagonal = vec![];
+        for idx in 0..10 {
+            // b1 is the only square numbered 0
+            let first = (0..28)
+                .find(|s| s % 8 < 4 && maps.a1d1d4[*s] == idx && (idx != 0 || *s == 1))
+                .unwrap();
+            for second in 0..64 {
+                if square_distance(first, second) <= 1 {
+                    continue;
+                }
+                if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                    continue;
+                }
+                if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                    both_on_diagonal.push((idx, second));
+                } else {
+                    maps.kings[idx as usize][second] = code;
+                    code += 1;
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            maps.kings[idx as usize][second] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                let with = if k > 0 { maps.binomial[k - 1][n - 1] } else { 0 };
+                let without = if k < n { maps.binomial[k][n - 1] } else { 0 };
+                maps.binomial[k][n] = with + without;
+            }
+        }
+
+        let mut available = 47;
+        for lead_pawns in 1..6 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        maps.pawns[square] = available;
+                        maps.pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    maps.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += maps.binomial[lead_pawns - 1][maps.pawns[square] as usize];
+                }
+                maps.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+
+        maps
+    };
+}
+
+/// Gets the code of a piece in a table: 1-6 for pawn, knight, bishop, rook, queen and king,
+///     plus 8 for black.
+fn piece_code(piece: Piece) -> u8 {
+    let kind = match piece & Piece::PieceType {
+        Piece::Pawn => 1,
+        Piece::Knight => 2,
+        Piece::Bishop => 3,
+        Piece::Rook => 4,
+        Piece::Queen => 5,
+        _ => 6,
+    };
+    match piece.is_color(Player::White) {
+        true => kind,
+        false => kind | 8,
+    }
+}
+
+/// What a table holds, from its name, ex. `KRPvKR`.
+#[derive(Clone, Debug)]
+struct TableInfo {
+    piece_count: usize,
+    has_pawns: bool,
+    /// Whether a player has exactly one of some kind of piece other than the king.
+    has_unique_pieces: bool,
+    /// The number of pawns of the leading color, which has the fewest pawns
+    ///     but at least one, and the number of pawns of the other color.
+    pawn_count: [usize; 2],
+    /// Whether both players have the same pieces.
+    symmetric: bool,
+}
+
+impl TableInfo {
+    fn parse(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        let sides = [white, black];
+        let valid = |side: &str| {
+            side.starts_with('K')
+                && side.matches('K').count() == 1
+                && side.chars().all(|c| "KQRBNP".contains(c))
+        };
+        if !sides.iter().all(|side| valid(side)) || name.len() - 1 > MAX_PIECES {
+            return None;
+        }
+
+        let count = |side: &str, c: char| side.matches(c).count();
+        let has_unique_pieces = sides
+            .iter()
+            .any(|side| "QRBNP".chars().any(|c| count(side, c) == 1));
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count = match white_leads {
+            true => [white_pawns, black_pawns],
+            false => [black_pawns, white_pawns],
+        };
+
+        Some(TableInfo {
+            piece_count: name.len() - 1,
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces,
+            pawn_count,
+            symmetric: white == black,
+        })
+    }
+
+    /// The number of files the leading pawn is split over: a-d with pawns, or just one without.
+    fn files(&self) -> usize {
+        match self.has_pawns {
+            true => 4,
+            false => 1,
+        }
+    }
+}
+
+/// How the pieces of one side of a table are ordered and grouped to index a position.
+#[derive(Clone, Copy, Debug, Default)]
+struct Encoding {
+    /// The code of each piece, in the order they are encoded.
+    pieces: [u8; MAX_PIECES],
+    /// The number of pieces in each group, ending with 0.
+    group_len: [usize; MAX_PIECES + 1],
+    /// What the index of each group is multiplied by. After the last group
+    ///     comes the size of the table.
+    group_idx: [u64; MAX_PIECES + 1],
+}
+
+impl Encoding {
+    /// Groups the pieces, which start with the leading pawns if there are any.
+    ///
+    /// The first group and the remaining pawns are placed in the index at the
+    ///     positions given by `order`, and the other groups fill in around them.
+    fn new(
+        info: &TableInfo,
+        pieces: [u8; MAX_PIECES],
+        order: [u8; 2],
+        file: usize,
+    ) -> Option<Self> {
+        let mut encoding = Encoding {
+            pieces,
+            ..Default::default()
+        };
+
+        // the first group has the leading pawns, or the kings and a unique piece if there is one
+        let mut first_len: i32 = match (info.has_pawns, info.has_unique_pieces) {
+            (true, _) => 0,
+            (false, true) => 3,
+            (false, false) => 2,
+        };
+        let mut n = 0;
+        encoding.group_len[0] = 1;
+        for i in 1..info.piece_count {
+            first_len -= 1;
+            if first_len > 0 || pieces[i] == pieces[i - 1] {
+                encoding.group_len[n] += 1;
+            } else {
+                n += 1;
+                encoding.group_len[n] = 1;
+            }
+        }
+        n += 1;
+
+        let pawns_on_both_sides = info.has_pawns && info.pawn_count[1] > 0;
+        let mut next = 1 + pawns_on_both_sides as usize;
+        let mut free_squares = 64 - encoding.group_len[0];
+        if pawns_on_both_sides {
+            free_squares -= encoding.group_len[1];
+        }
+
+        let mut idx = 1;
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                encoding.group_idx[0] = idx;
+                idx = idx.checked_mul(match (info.has_pawns, info.has_unique_pieces) {
+                    (true, _) => *MAPS.lead_pawns_size.get(encoding.group_len[0])?.get(file)?,
+                    (false, true) => UNIQUE_PIECES_SIZE,
+                    (false, false) => KINGS_SIZE,
+                })?;
+            } else if k == order[1] as usize {
+                encoding.group_idx[1] = idx;
+                let free_squares = 48 - encoding.group_len[0];
+                idx = idx.checked_mul(MAPS.binomial.get(encoding.group_len[1])?[free_squares])?;
+            } else {
+                let len = *encoding.group_len.get(next).filter(|len| **len > 0)?;
+                encoding.group_idx[next] = idx;
+                idx = idx.checked_mul(MAPS.binomial.get(len)?[free_squares])?;
+                free_squares = free_squares.checked_sub(len)?;
+                next += 1;
+            }
+            k += 1;
+        }
+        encoding.group_idx[n] = idx;
+
+        Some(encoding)
+    }
+
+    /// The number of positions in the table.
+    fn size(&self) -> u64 {
+        let groups = self.group_len.iter().position(|len| *len == 0).unwrap();
+        self.group_idx[groups]
+    }
+
+    /// Gets the index of a position, from the squares and codes of its pieces,
+    ///     which start with the leading pawns.
+    fn index(
+        &self,
+        info: &TableInfo,
+        squares: &mut [usize; MAX_PIECES],
+        pieces: &mut [u8; MAX_PIECES],
+        lead_pawns: usize,
+    ) -> u64 {
+        let size = info.piece_count;
+
+        // put the pieces in the same order as the table
+        for i in lead_pawns..size - 1 {
+            if let Some(j) = (i + 1..size).find(|j| self.pieces[i] == pieces[*j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // mirror the board so the first piece is on the a-d files
+        if squares[0] % 8 > 3 {
+            squares[..size].iter_mut().for_each(|s| *s ^= 7);
+        }
+
+        let mut idx;
+        if info.has_pawns {
+            idx = MAPS.lead_pawn_idx[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|s| MAPS.pawns[*s]);
+            for (i, square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += MAPS.binomial[i][MAPS.pawns[*square] as usize];
+            }
+        } else {
+            // without pawns, the board can also be flipped to put the first piece
+            //     on ranks 1-4 and below the a1-h8 diagonal
+            if squares[0] / 8 > 3 {
+                squares[..size].iter_mut().for_each(|s| *s ^= 56);
+            }
+            for i in 0..self.group_len[0] {
+                match off_diagonal(squares[i]) {
+                    0 => continue,
+                    off if off > 0 => squares[i..size]
+                        .iter_mut()
+                        .for_each(|s| *s = flip_diagonal(*s)),
+                    _ => {}
+                }
+                break;
+            }
+
+            let [first, second, third] = [squares[0], squares[1], squares[2]];
+            let rank = |square: usize| (square / 8) as u64;
+            idx = if info.has_unique_pieces {
+                let adjust1 = (second > first) as u64;
+                let adjust2 = (third > first) as u64 + (third > second) as u64;
+                if off_diagonal(first) != 0 {
+                    (MAPS.a1d1d4[first] * 63 + second as u64 - adjust1) * 62 + third as u64
+                        - adjust2
+                } else if off_diagonal(second) != 0 {
+                    (6 * 63 + rank(first) * 28 + MAPS.b1h1h7[second]) * 62 + third as u64 - adjust2
+                } else if off_diagonal(third) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + rank(first) * 7 * 28
+                        + (rank(second) - adjust1) * 28
+                        + MAPS.b1h1h7[third]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + rank(first) * 7 * 6
+                        + (rank(second) - adjust1) * 6
+                        + (rank(third) - adjust2)
+                }
+            } else {
+                MAPS.kings[MAPS.a1d1d4[first] as usize][second]
+            };
+        }
+        idx *= self.group_idx[0];
+
+        // the other groups are indexed by the squares left over by the groups before them
+        let mut remaining_pawns = info.has_pawns && info.pawn_count[1] > 0;
+        let mut start = self.group_len[0];
+        let mut next = 1;
+        while self.group_len[next] != 0 {
+            let len = self.group_len[next];
+            squares[start..start + len].sort();
+            let mut group = 0;
+            for i in 0..len {
+                let square = squares[start + i];
+                let adjust = squares[..start].iter().filter(|s| square > **s).count();
+                let free = square - adjust - 8 * remaining_pawns as usize;
+                group += MAPS.binomial[i + 1][free];
+            }
+            remaining_pawns = false;
+            idx += group * self.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        idx
+    }
+}
+
+/// Where a position is stored in a table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TableIndex {
+    /// The side to move, after flipping the colors to match the table.
+    side: usize,
+    /// The file of the leading pawn, mirrored onto the a-d files.
+    file: usize,
+    idx: u64,
+}
+
+/// Finds where a position is stored in a table.
+///
+/// The tables have the stronger side as white, so if `black_stronger` is set, the colors
+///     are swapped and the board is flipped. Tables where both sides have the same
+///     pieces only store white to move, so those positions are flipped as well.
+fn table_index<'a>(
+    info: &TableInfo,
+    board: &Board,
+    black_stronger: bool,
+    encoding: impl Fn(usize, usize) -> &'a Encoding,
+) -> TableIndex {
+    let flip = black_stronger || (info.symmetric && board.active_color == Player::Black);
+    let (flip_color, flip_square) = match flip {
+        true => (8, 56),
+        false => (0, 0),
+    };
+    let side = flip as usize ^ board.active_color.index();
+
+    let mut squares = [0; MAX_PIECES];
+    let mut pieces = [0; MAX_PIECES];
+    let mut size = 0;
+    let mut file = 0;
+
+    // the leading pawns come first, with the leading pawn at the very start
+    let lead_code = encoding(0, 0).pieces[0];
+    if info.has_pawns {
+        for (square, piece) in board.squares.iter().enumerate() {
+            if piece_code(*piece) ^ flip_color == lead_code {
+                squares[size] = square ^ flip_square;
+                pieces[size] = lead_code;
+                size += 1;
+            }
+        }
+        let lead = (0..size).max_by_key(|i| (MAPS.pawns[squares[*i]], -(*i as i32)));
+        squares.swap(0, lead.unwrap());
+        file = (squares[0] % 8).min(7 - squares[0] % 8);
+    }
+    let lead_pawns = size;
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        let code = piece_code(*piece) ^ flip_color;
+        if *piece != Piece::empty() && !(info.has_pawns && code == lead_code) {
+            squares[size] = square ^ flip_square;
+            pieces[size] = code;
+            size += 1;
+        }
+    }
+
+    let idx = encoding(side, file).index(info, &mut squares, &mut pieces, lead_pawns);
+    TableIndex { side, file, idx }
+}
+
+fn read_u16_le(bytes: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(pos..pos + 2)?.try_into().unwrap(),
+    ))
+}
+
+fn read_u32_le(bytes: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(pos..pos + 4)?.try_into().unwrap(),
+    ))
+}
+
+/// Reads a big-endian value from the compressed data, which is padded with zeros past its end.
+fn read_be(bytes: &[u8], pos: usize, len: usize) -> u64 {
+    (0..len).fold(0, |value, i| {
+        (value << 8) | *bytes.get(pos + i).unwrap_or(&0) as u64
+    })
+}
+
+/// The compressed values for one side to move and file of a table.
+///
+/// The values are split into blocks of canonical Huffman codes, where each code stands
+///     for a symbol that expands into one or more values, by Re-Pair compression.
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    encoding: Encoding,
+    flags: u8,
+    /// The shortest code, or the only value if every position has the same value.
+    min_len: u8,
+    block_size: usize,
+    /// The number of values between entries of the sparse index.
+    span: u64,
+    /// Where the first symbol of each code length is, shortest first.
+    lowest_sym: usize,
+    /// The lowest code of each length, left-aligned to 64 bits, shortest first.
+    base64: Vec<u64>,
+    /// Where the left and right halves of each symbol are, 12 bits each.
+    btree: usize,
+    /// The number of values each symbol expands into, minus one.
+    sym_len: Vec<u8>,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    data: usize,
+    num_blocks: usize,
+    /// Where the DTZ map for each outcome starts.
+    map_idx: [usize; 4],
+}
+
+impl PairsData {
+    /// Reads the sizes and the Huffman code, returning the position after them.
+    fn read_sizes(&mut self, bytes: &[u8], mut pos: usize) -> Option<usize> {
+        self.flags = *bytes.get(pos)?;
+        pos += 1;
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            self.min_len = *bytes.get(pos)?;
+            return Some(pos + 1);
+        }
+
+        let header = bytes.get(pos..pos + 9)?;
+        self.block_size = 1usize.checked_shl(header[0] as u32)?;
+        self.span = 1u64.checked_shl(header[1] as u32)?;
+        self.sparse_index_size = self.encoding.size().div_ceil(self.span) as usize;
+        let padding = header[2] as usize;
+        self.num_blocks = read_u32_le(bytes, pos + 3)? as usize;
+        self.block_lengths_size = self.num_blocks + padding;
+        let max_len = header[7];
+        self.min_len = header[8];
+        pos += 9;
+        if max_len < self.min_len || self.min_len == 0 {
+            return None;
+        }
+
+        // the codes of each length follow the codes of the next longer length,
+        //     so the lowest code of a length comes from the one after it
+        self.lowest_sym = pos;
+        let lengths = (max_len - self.min_len + 1) as usize;
+        self.base64 = vec![0; lengths];
+        let lowest = |i: usize| read_u16_le(bytes, self.lowest_sym + 2 * i).map(u64::from);
+        for i in (0..lengths - 1).rev() {
+            let codes = lowest(i)?.wrapping_sub(lowest(i + 1)?);
+            self.base64[i] = self.base64[i + 1].wrapping_add(codes) / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base = base
+                .checked_shl(64 - i as u32 - self.min_len as u32)
+                .unwrap_or(0);
+        }
+        pos += 2 * lengths;
+
+        let symbols = read_u16_le(bytes, pos)? as usize;
+        pos += 2;
+        self.btree = pos;
+        bytes.get(pos..pos + 3 * symbols)?;
+        let mut sym_len = vec![None; symbols];
+        for sym in 0..symbols {
+            self.set_sym_len(bytes, sym, &mut sym_len)?;
+        }
+        self.sym_len = sym_len.into_iter().map(Option::unwrap).collect();
+
+        Some(pos + 3 * symbols + (symbols & 1))
+    }
+
+    /// Finds the number of values a symbol expands into, minus one.
+    fn set_sym_len(&self, bytes: &[u8], sym: usize, sym_len: &mut [Option<u8>]) -> Option<u8> {
+        if let Some(len) = sym_len.get(sym)? {
+            return Some(*len);
+        }
+        // the tree has no cycles, and marking the symbol stops a bad table from looping
+        sym_len[sym] = Some(0);
+
+        let (left, right) = self.children(bytes, sym);
+        let len = match right {
+            0xfff => 0,
+            _ => {
+                let left = self.set_sym_len(bytes, left, sym_len)?;
+                let right = self.set_sym_len(bytes, right, sym_len)?;
+                left.checked_add(right)?.checked_add(1)?
+            }
+        };
+        sym_len[sym] = Some(len);
+        Some(len)
+    }
+
+    /// Gets the symbols that a symbol is made of, or for a single value,
+    ///     the value and `0xfff`.
+    fn children(&self, bytes: &[u8], sym: usize) -> (usize, usize) {
+        let pos = self.btree + 3 * sym;
+        let [a, b, c] = [bytes[pos], bytes[pos + 1], bytes[pos + 2]].map(usize::from);
+        (((b & 0xf) << 8) | a, (c << 4) | (b >> 4))
+    }
+
+    /// Gets the value at an index.
+    fn decompress(&self, bytes: &[u8], idx: u64) -> Option<u16> {
+        if self.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(self.min_len as u16);
+        }
+
+        // the sparse index points to the middle of each span,
+        //     and from there the blocks are walked to the one with the index
+        let entry = self.sparse_index + 6 * (idx / self.span) as usize;
+        let mut block = read_u32_le(bytes, entry)? as usize;
+        let mut offset = read_u16_le(bytes, entry + 4)? as i64;
+        offset += (idx % self.span) as i64 - (self.span / 2) as i64;
+
+        let block_len = |block: usize| {
+            if block >= self.block_lengths_size {
+                return None;
+            }
+            read_u16_le(bytes, self.block_lengths + 2 * block).map(i64::from)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_len(block)? + 1;
+        }
+        while offset > block_len(block)? {
+            offset -= block_len(block)? + 1;
+            block += 1;
+        }
+
+        // read codes until reaching the symbol with the value
+        let mut pos = self.data + block * self.block_size;
+        let mut buf = read_be(bytes, pos, 8);
+        let mut buf_len = 64;
+        pos += 8;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buf < self.base64[len] {
+                len += 1;
+            }
+            let code_len = len as u32 + self.min_len as u32;
+            sym = ((buf - self.base64[len]) >> (64 - code_len)) as usize;
+            sym += read_u16_le(bytes, self.lowest_sym + 2 * len)? as usize;
+
+            let values = *self.sym_len.get(sym)? as i64 + 1;
+            if offset < values {
+                break;
+            }
+            offset -= values;
+            buf <<= code_len;
+            buf_len -= code_len;
+            if buf_len <= 32 {
+                buf_len += 32;
+                buf |= read_be(bytes, pos, 4) << (64 - buf_len);
+                pos += 4;
+            }
+        }
+
+        // expand the symbol down to the value
+        while self.sym_len[sym] != 0 {
+            let (left, right) = self.children(bytes, sym);
+            let left_values = self.sym_len[left] as i64 + 1;
+            if offset < left_values {
+                sym = left;
+            } else {
+                offset -= left_values;
+                sym = right;
+            }
+        }
+        Some(self.children(bytes, sym).0 as u16)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+/// A WDL or DTZ table that has been read into memory.
+struct Table {
+    bytes: Vec<u8>,
+    /// The compressed values for each file, and each side to move
+    ///     unless only one is stored.
+    pairs: Vec<Vec<PairsData>>,
+    /// Where the DTZ maps start.
+    map: usize,
+}
+
+impl Debug for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Table")
+            .field("len", &self.bytes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Table {
+    fn parse(bytes: Vec<u8>, info: &TableInfo, kind: TableKind) -> Option<Table> {
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if bytes.get(..4)? != magic || (bytes.get(4)? & 2 != 0) != info.has_pawns {
+            return None;
+        }
+        let mut pos = 5;
+
+        let sides = match kind == TableKind::Wdl && !info.symmetric {
+            true => 2,
+            false => 1,
+        };
+        let pawns_on_both_sides = info.has_pawns && info.pawn_count[1] > 0;
+        let mut pairs = vec![];
+        for file in 0..info.files() {
+            let order = *bytes.get(pos)?;
+            let order2 = match pawns_on_both_sides {
+                true => *bytes.get(pos + 1)?,
+                false => 0xff,
+            };
+            pos += 1 + pawns_on_both_sides as usize;
+
+            let mut file_pairs = vec![];
+            for side in 0..sides {
+                let mut pieces = [0; MAX_PIECES];
+                for (k, piece) in pieces.iter_mut().enumerate().take(info.piece_count) {
+                    let byte = *bytes.get(pos + k)?;
+                    *piece = if side == 0 { byte & 0xf } else { byte >> 4 };
+                }
+                let order = match side {
+                    0 => [order & 0xf, order2 & 0xf],
+                    _ => [order >> 4, order2 >> 4],
+                };
+                file_pairs.push(PairsData {
+                    encoding: Encoding::new(info, pieces, order, file)?,
+                    ..Default::default()
+                });
+            }
+            pos += info.piece_count;
+            pairs.push(file_pairs);
+        }
+
+        pos += pos & 1;
+        for d in pairs.iter_mut().flatten() {
+            pos = d.read_sizes(&bytes, pos)?;
+        }
+
+        let map = pos;
+        if kind == TableKind::Dtz {
+            for d in pairs.iter_mut().flatten() {
+                if d.flags & FLAG_MAPPED == 0 {
+                    continue;
+                }
+                for map_idx in d.map_idx.iter_mut() {
+                    if d.flags & FLAG_WIDE != 0 {
+                        pos += pos & 1;
+                        *map_idx = (pos - map) / 2 + 1;
+                        pos += 2 * read_u16_le(&bytes, pos)? as usize + 2;
+                    } else {
+                        *map_idx = pos - map + 1;
+                        pos += *bytes.get(pos)? as usize + 1;
+                    }
+                }
+            }
+            pos += pos & 1;
+        }
+
+        for d in pairs.iter_mut().flatten() {
+            d.sparse_index = pos;
+            pos += 6 * d.sparse_index_size;
+        }
+        for d in pairs.iter_mut().flatten() {
+            d.block_lengths = pos;
+            pos += 2 * d.block_lengths_size;
+        }
+        for d in pairs.iter_mut().flatten() {
+            pos = pos.next_multiple_of(64);
+            d.data = pos;
+            pos += d.num_blocks * d.block_size;
+        }
+        if pos > bytes.len() {
+            return None;
+        }
+
+        Some(Table { bytes, pairs, map })
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &PairsData {
+        let file = &self.pairs[file];
+        &file[side.min(file.len() - 1)]
+    }
+
+    /// Turns a value from a DTZ table into the number of plies until zeroing.
+    fn map_dtz(&self, d: &PairsData, mut value: u16, wdl: Wdl) -> Option<i32> {
+        if d.flags & FLAG_MAPPED != 0 {
+            let map_idx = d.map_idx[match wdl {
+                Wdl::Loss => 1,
+                Wdl::BlessedLoss => 3,
+                Wdl::CursedWin => 2,
+                _ => 0,
+            }] + value as usize;
+            value = match d.flags & FLAG_WIDE != 0 {
+                true => read_u16_le(&self.bytes, self.map + 2 * map_idx)?,
+                false => *self.bytes.get(self.map + map_idx)? as u16,
+            };
+        }
+
+        let mut plies = value as i32;
+        let in_moves = match wdl {
+            Wdl::Win => d.flags & FLAG_WIN_PLIES == 0,
+            Wdl::Loss => d.flags & FLAG_LOSS_PLIES == 0,
+            _ => true,
+        };
+        if in_moves {
+            plies *= 2;
+        }
+        Some(plies + 1)
+    }
+}
+
+/// The files of a table, which are read the first time they are probed.
+#[derive(Debug)]
+struct TableFiles {
+    info: TableInfo,
+    wdl_path: Option<PathBuf>,
+    dtz_path: Option<PathBuf>,
+    wdl: OnceLock<Option<Table>>,
+    dtz: OnceLock<Option<Table>>,
+}
+
+impl TableFiles {
+    fn table(&self, kind: TableKind) -> Option<&Table> {
+        let (path, table) = match kind {
+            TableKind::Wdl => (&self.wdl_path, &self.wdl),
+            TableKind::Dtz => (&self.dtz_path, &self.dtz),
+        };
+        table
+            .get_or_init(|| {
+                let path = path.as_ref()?;
+                let table = std::fs::read(path)
+                    .ok()
+                    .and_then(|bytes| Table::parse(bytes, &self.info, kind));
+                if table.is_none() {
+                    log::warn!("failed to read the table \"{}\"", path.display());
+                }
+                table
+            })
+            .as_ref()
+    }
+}
+
+/// Syzygy endgame tablebases, read from directories of `.rtbw` (WDL) and `.rtbz` (DTZ) files.
+///
+/// The tables give the win, draw or loss (WDL) of a position, and the distance to
+///     zeroing (DTZ) to convert it. Only standard chess positions without castle rights
+///     can be probed. Each table is read into memory the first time it is probed.
+#[derive(Debug, Default)]
+pub struct Tablebase {
+    /// The tables, keyed by their name, ex. `KRPvKR`.
+    tables: HashMap<String, TableFiles>,
+    max_pieces: usize,
+}
+
+impl Tablebase {
+    /// A tablebase without any tables.
+    pub fn new() -> Self {
+        Tablebase::default()
+    }
+
+    /// Opens the tables in a directory.
+    pub fn open(directory: impl AsRef<Path>) -> Result<Self, TablebaseErr> {
+        let mut tablebase = Tablebase::new();
+        tablebase.add_directory(directory)?;
+        Ok(tablebase)
+    }
+
+    /// Adds the tables in a directory. If a table is in more than one directory,
+    ///     the first one it was found in is used.
+    pub fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<(), TablebaseErr> {
+        let mut found = false;
+        for entry in std::fs::read_dir(directory.as_ref())? {
+            let path = entry?.path();
+            let kind = match path.extension().and_then(|e| e.to_str()) {
+                Some("rtbw") => TableKind::Wdl,
+                Some("rtbz") => TableKind::Dtz,
+                _ => continue,
+            };
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some(info) = TableInfo::parse(name) else {
+                continue;
+            };
+
+            found = true;
+            self.max_pieces = self.max_pieces.max(info.piece_count);
+            let files = self
+                .tables
+                .entry(name.to_owned())
+                .or_insert_with(|| TableFiles {
+                    info,
+                    wdl_path: None,
+                    dtz_path: None,
+                    wdl: OnceLock::new(),
+                    dtz: OnceLock::new(),
+                });
+            match kind {
+                TableKind::Wdl => files.wdl_path.get_or_insert(path),
+                TableKind::Dtz => files.dtz_path.get_or_insert(path),
+            };
+        }
+
+        match found {
+            true => Ok(()),
+            false => Err(TablebaseErr::NoTables(directory.as_ref().to_owned())),
+        }
+    }
+
+    /// The most pieces of any table.
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Gets the outcome of the position for the side to move,
+    ///     or `None` if the position is not in the tablebase.
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        self.search(board, false).map(|(wdl, _)| wdl)
+    }
+
+    /// Gets the signed distance to zeroing of the position: the number of plies until a
+    ///     capture, pawn move or mate, positive when the side to move wins and negative
+    ///     when it loses. Draws are 0, and a checkmated position is -1.
+    ///
+    /// Cursed wins and blessed losses are 100 plies further from 0. Like with any Syzygy
+    ///     probe, the distance can be one ply longer than the fastest way to zero.
+    pub fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let (wdl, zeroing_is_best) = self.search(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing_is_best {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        if let Some(dtz) = self.probe_dtz_table(board, wdl)? {
+            return Some(dtz);
+        }
+
+        // the table only stores the other side to move, so look one ply ahead
+        let mut best: Option<i32> = None;
+        for umove in board.generate_moves() {
+            let zeroing = is_zeroing(board, umove);
+            let mut next = *board;
+            next.make_move_unchecked(umove).ok()?;
+
+            let mut dtz = match zeroing {
+                true => -dtz_before_zeroing(self.search(&next, false)?.0),
+                false => -self.probe_dtz(&next)?,
+            };
+            if dtz == 1 && is_checkmate(&next) {
+                best = Some(1);
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz.signum() == wdl.signum() && best.is_none_or(|best| dtz < best) {
+                best = Some(dtz);
+            }
+        }
+        Some(best.unwrap_or(-1))
+    }
+
+    /// Gets the legal moves that keep the best outcome, taking the fifty move rule
+    ///     into account. When winning, only the moves that zero the fastest are kept,
+    ///     and when losing, only the ones that hold out the longest.
+    ///
+    /// Returns `None` if the position is not in the tablebase.
+    pub fn root_moves(&self, board: &Board) -> Option<MoveList> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let mut moves = MoveList::new();
+        for umove in board.generate_moves() {
+            let mut next = *board;
+            next.make_move_unchecked(umove).ok()?;
+
+            // the plies until zeroing after the move, counted from before it
+            let mut dtz = match is_zeroing(board, umove) {
+                true => dtz_before_zeroing(-self.probe_wdl(&next)?),
+                false => {
+                    let dtz = -self.probe_dtz(&next)?;
+                    dtz + dtz.signum()
+                }
+            };
+            if dtz == 2 && is_checkmate(&next) {
+                dtz = 1;
+            }
+
+            let plies = dtz.abs() + board.halfmove as i32;
+            let rank = match dtz.signum() {
+                1 if plies <= 100 => 10_000 - dtz,
+                1 => 5_000 - dtz,
+                -1 if plies <= 100 => -10_000 - dtz,
+                -1 => -5_000 - dtz,
+                _ => 0,
+            };
+            moves.push_scored(umove, rank);
+        }
+
+        let best = (0..moves.len()).map(|idx| moves.score(idx)).max();
+        moves.sort_by_score();
+        let kept = (0..moves.len())
+            .take_while(|idx| Some(moves.score(*idx)) == best)
+            .count();
+        Some(moves.iter().take(kept).copied().collect())
+    }
+
+    fn can_probe(&self, board: &Board) -> bool {
+        let pieces = board
+            .squares
+            .iter()
+            .filter(|p| **p != Piece::empty())
+            .count();
+        board.variant() == Variant::Standard
+            && board.castle_rights.is_empty()
+            && pieces <= self.max_pieces
+    }
+
+    /// Gets the outcome of a position, searching the captures (and pawn moves, if
+    ///     `check_zeroing` is set) as the tables may not store the right value when
+    ///     one of them is best. Also returns whether a zeroing move is best.
+    fn search(&self, board: &Board, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let moves = board.generate_moves();
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for umove in moves.iter() {
+            let searched_move = match check_zeroing {
+                true => is_zeroing(board, *umove),
+                false => board.is_capture(*umove),
+            };
+            if !searched_move {
+                continue;
+            }
+            searched += 1;
+
+            let mut next = *board;
+            next.make_move_unchecked(*umove).ok()?;
+            let wdl = -self.search(&next, false)?.0;
+            if wdl > best {
+                best = wdl;
+                if wdl == Wdl::Win {
+                    return Some((wdl, true));
+                }
+            }
+        }
+
+        // the tables don't store positions with en passant, and may store anything
+        //     where a capture is best, so searching every move is more reliable
+        let searched_all = searched > 0 && searched == moves.len();
+        let wdl = match searched_all {
+            true => best,
+            false => self.probe_wdl_table(board)?,
+        };
+        match best >= wdl {
+            true => Some((best, best > Wdl::Draw || searched_all)),
+            false => Some((wdl, false)),
+        }
+    }
+
+    /// Finds the table for a position, and whether black has the pieces of white in it.
+    fn find_table(&self, board: &Board) -> Option<(&TableFiles, bool)> {
+        let signature = MaterialSignature::of(board);
+        match self.tables.get(&signature.to_string()) {
+            Some(files) => Some((files, false)),
+            None => Some((self.tables.get(&signature.flipped().to_string())?, true)),
+        }
+    }
+
+    fn probe_wdl_table(&self, board: &Board) -> Option<Wdl> {
+        // two kings are a draw, and have no table
+        if board
+            .squares
+            .iter()
+            .filter(|p| **p != Piece::empty())
+            .count()
+            == 2
+        {
+            return Some(Wdl::Draw);
+        }
+
+        let (files, black_stronger) = self.find_table(board)?;
+        let table = files.table(TableKind::Wdl)?;
+        let index = table_index(&files.info, board, black_stronger, |side, file| {
+            &table.pairs(side, file).encoding
+        });
+        let d = table.pairs(index.side, index.file);
+        Wdl::from_value(d.decompress(&table.bytes, index.idx)?)
+    }
+
+    /// Gets the distance to zeroing from the DTZ table, or `Some(None)` if the table
+    ///     only stores the other side to move.
+    fn probe_dtz_table(&self, board: &Board, wdl: Wdl) -> Option<Option<i32>> {
+        let (files, black_stronger) = self.find_table(board)?;
+        let table = files.table(TableKind::Dtz)?;
+        let index = table_index(&files.info, board, black_stronger, |side, file| {
+            &table.pairs(side, file).encoding
+        });
+
+        let d = table.pairs(index.side, index.file);
+        let stored_side = (d.flags & FLAG_STM) as usize;
+        // symmetric tables without pawns look the same from both sides
+        let both_sides = files.info.symmetric && !files.info.has_pawns;
+        if stored_side != index.side && !both_sides {
+            return Some(None);
+        }
+
+        let dtz = table.map_dtz(d, d.decompress(&table.bytes, index.idx)?, wdl)?;
+        let dtz = match wdl {
+            Wdl::CursedWin | Wdl::BlessedLoss => dtz + 100,
+            _ => dtz,
+        };
+        Some(Some(dtz * wdl.signum()))
+    }
+}
+
+/// The distance to zeroing of a position where a zeroing move is best.
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Loss => -1,
+        Wdl::BlessedLoss => -101,
+        Wdl::Draw => 0,
+        Wdl::CursedWin => 101,
+        Wdl::Win => 1,
+    }
+}
+
+/// Checks if a move resets the fifty move counter.
+fn is_zeroing(board: &Board, umove: Move) -> bool {
+    board.is_capture(umove) || board.squares[umove.origin_idx()] & Piece::PieceType == Piece::Pawn
+}
+
+fn is_checkmate(board: &Board) -> bool {
+    board.is_in_check(board.active_color) && board.generate_moves().is_empty()
+}
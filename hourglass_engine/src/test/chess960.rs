@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use crate::{Board, CastleRights, Move, Square};
+
+// From https://www.chessprogramming.org/Chess960_Perft_Results
+const PERFT_POSITIONS: [(&str, u64); 6] = [
+    (
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        12189,
+    ),
+    (
+        "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        18002,
+    ),
+    (
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        10471,
+    ),
+    (
+        "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        13440,
+    ),
+    (
+        "q1bnrkr1/ppppp2p/2n2p2/4b1p1/2NP4/8/PPP1PPPP/QNB1RRKB w ge - 1 9",
+        24566,
+    ),
+    (
+        "qnr1bkrb/pppp2pp/3np3/5p2/8/P2P2P1/NPP1PP1P/QN1RBKRB w GDg - 3 9",
+        26895,
+    ),
+];
+
+#[test]
+fn test_chess960_perft() {
+    for (fen, nodes) in PERFT_POSITIONS {
+        let mut board = Board::empty();
+        board.set_chess960(true);
+        board.load_fen(fen).unwrap();
+        assert_eq!(board.perft(3), nodes, "perft failed for \"{}\"", fen);
+        assert_eq!(board.get_shredder_fen(), fen);
+    }
+}
+
+#[test]
+fn test_starting_positions() {
+    let standard = Board::new_chess960(518).unwrap();
+    assert!(standard.is_chess960());
+    assert_eq!(standard.get_fen(), Board::new().get_fen());
+    assert_eq!(
+        standard.get_shredder_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    );
+
+    assert_eq!(
+        Board::new_chess960(0).unwrap().get_fen(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    );
+    assert_eq!(
+        Board::new_chess960(959).unwrap().get_fen(),
+        "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+    );
+    assert!(Board::new_chess960(960).is_none());
+
+    let mut fens = (0..960)
+        .map(|idx| Board::new_chess960(idx).unwrap().get_fen())
+        .collect::<Vec<_>>();
+    fens.sort();
+    fens.dedup();
+    assert_eq!(fens.len(), 960);
+}
+
+#[test]
+fn test_chess960_castling() {
+    let mut board = Board::empty();
+    board.set_chess960(true);
+    board
+        .load_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1")
+        .unwrap();
+
+    // the king captures its own rook to castle
+    let castle = board.resolve_move(Move::from_str("e1b1").unwrap()).unwrap();
+    assert!(castle.is_castle());
+    assert!(board.generate_moves().contains(&castle));
+    assert_eq!(board.move_to_san(castle), "O-O-O");
+    board.try_move(castle).unwrap();
+    assert_eq!(board.get_fen(), "1r2k1r1/8/8/8/8/8/8/2KR2R1 b kq - 1 1");
+    assert_eq!(
+        board.get_shredder_fen(),
+        "1r2k1r1/8/8/8/8/8/8/2KR2R1 b gb - 1 1"
+    );
+    assert_eq!(
+        board.castle_rights(),
+        CastleRights::BlackKingSide | CastleRights::BlackQueenSide
+    );
+
+    // an inner rook needs its file in X-FEN
+    board
+        .load_fen("4k3/8/8/8/8/8/8/R1R1K2R w KC - 0 1")
+        .unwrap();
+    assert_eq!(
+        board.castle_rook_square(CastleRights::WhiteQueenSide),
+        Square::C1
+    );
+    assert_eq!(board.get_fen(), "4k3/8/8/8/8/8/8/R1R1K2R w KC - 0 1");
+}
+
+#[test]
+fn test_loading_keeps_chess960_mode() {
+    // rook files in the castle rights don't turn on chess960 mode
+    let mut board = Board::empty();
+    board
+        .load_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1")
+        .unwrap();
+    assert!(!board.is_chess960());
+    assert_eq!(
+        board.castle_rook_square(CastleRights::WhiteQueenSide),
+        Square::B1
+    );
+
+    // so a standard fen loaded afterwards castles the standard way
+    board
+        .load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+        .unwrap();
+    assert!(!board.is_chess960());
+    assert_eq!(
+        board.castle_rook_square(CastleRights::WhiteKingSide),
+        Square::H1
+    );
+    let castle = board.resolve_move(Move::from_str("e1g1").unwrap()).unwrap();
+    assert!(castle.is_castle());
+    assert_eq!(castle.to_string(), "e1g1");
+
+    // and a board in chess960 mode stays in it
+    board.set_chess960(true);
+    board
+        .load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+        .unwrap();
+    assert!(board.is_chess960());
+    let castle = board.resolve_move(Move::from_str("e1h1").unwrap()).unwrap();
+    assert!(castle.is_castle());
+    assert_eq!(castle.to_string(), "e1h1");
+}
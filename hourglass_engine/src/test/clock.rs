@@ -0,0 +1,150 @@
+use std::{str::FromStr, time::Duration};
+
+use crate::{
+    Board, Clock, Game, GameStatus, ManualTimeSource, Player, TimeBonus, TimeControl,
+    TimeControlParseErr,
+};
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn manual_clock(control: &str) -> (Clock<ManualTimeSource>, ManualTimeSource) {
+    let time = ManualTimeSource::new();
+    let clock = Clock::with_time_source(TimeControl::from_str(control).unwrap(), time.clone());
+    (clock, time)
+}
+
+#[test]
+fn test_parse_time_control() {
+    let control = TimeControl::from_str("40/90+30, 30+30").unwrap();
+    assert_eq!(control.stages().len(), 2);
+    assert_eq!(control.stages()[0].moves, Some(40));
+    assert_eq!(control.stages()[0].time, secs(90 * 60));
+    assert_eq!(control.stages()[1].moves, None);
+    assert_eq!(control.stages()[1].bonus, TimeBonus::Increment(secs(30)));
+    assert_eq!(control.to_string(), "40/90+30, 30+30");
+    assert_eq!(control.to_pgn_tag(), "40/5400+30:1800+30");
+    assert_eq!(TimeControl::from_pgn_tag("40/5400+30:1800+30"), Ok(control));
+
+    let control = TimeControl::from_str("15d10").unwrap();
+    assert_eq!(control, TimeControl::bronstein(secs(15 * 60), secs(10)));
+    assert_eq!(TimeControl::from_str("0.5").unwrap().to_string(), "0.5");
+
+    assert_eq!(TimeControl::from_str(""), Err(TimeControlParseErr::Empty));
+    assert!(matches!(
+        TimeControl::from_str("5+3, x"),
+        Err(TimeControlParseErr::InvalidStage { stage: 1, .. })
+    ));
+    assert!(TimeControl::from_str("0/5").is_err());
+}
+
+#[test]
+fn test_fischer_increment() {
+    let (mut clock, time) = manual_clock("1+2");
+    clock.start(Player::White);
+
+    time.advance(secs(10));
+    assert_eq!(clock.remaining(Player::White), secs(50));
+    clock.press();
+    assert_eq!(clock.remaining(Player::White), secs(52));
+    assert_eq!(clock.active(), Player::Black);
+
+    time.advance(secs(5));
+    clock.stop();
+    time.advance(secs(100));
+    assert_eq!(clock.remaining(Player::Black), secs(55));
+    assert_eq!(clock.flagged(), None);
+}
+
+#[test]
+fn test_bronstein_delay() {
+    let (mut clock, time) = manual_clock("1d5");
+    clock.start(Player::White);
+
+    // the time used is given back, up to the delay
+    time.advance(secs(3));
+    clock.press();
+    assert_eq!(clock.remaining(Player::White), secs(60));
+
+    time.advance(secs(8));
+    clock.press();
+    assert_eq!(clock.remaining(Player::Black), secs(57));
+}
+
+#[test]
+fn test_multiple_stages() {
+    let (mut clock, time) = manual_clock("2/1, 1/0.5");
+    clock.start(Player::White);
+
+    for _ in 0..4 {
+        time.advance(secs(10));
+        clock.press();
+    }
+    // both players finished the first stage
+    assert_eq!(clock.remaining(Player::White), secs(40 + 30));
+    assert_eq!(clock.remaining(Player::Black), secs(40 + 30));
+
+    // the last stage repeats
+    time.advance(secs(10));
+    clock.press();
+    assert_eq!(clock.remaining(Player::White), secs(60 + 30));
+}
+
+#[test]
+fn test_flag_fall() {
+    let (mut clock, time) = manual_clock("1");
+    clock.start(Player::White);
+
+    time.advance(secs(60));
+    assert_eq!(clock.flagged(), Some(Player::White));
+    clock.press();
+    assert_eq!(clock.flagged(), Some(Player::White));
+    assert_eq!(clock.active(), Player::White);
+    assert_eq!(clock.remaining(Player::White), Duration::ZERO);
+}
+
+#[test]
+fn test_game_timeout() {
+    let (mut clock, time) = manual_clock("1");
+    let game = Game::new();
+    clock.start(Player::White);
+    assert_eq!(game.status_with_clock(&clock), GameStatus::Ongoing);
+
+    time.advance(secs(61));
+    assert_eq!(
+        game.status_with_clock(&clock),
+        GameStatus::Timeout(Player::Black)
+    );
+
+    // black only has a king, so it can't win on time
+    let mut board = Board::empty();
+    board.load_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let game = Game::from_board(board);
+    assert_eq!(
+        game.status_with_clock(&clock),
+        GameStatus::TimeoutVsInsufficientMaterial
+    );
+}
+
+#[test]
+fn test_mating_material() {
+    let cases = [
+        ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", true, false),
+        ("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", false, false),
+        // the pawn can block its own king in
+        ("4k3/4p3/8/8/8/8/8/1N2K3 w - - 0 1", true, true),
+        ("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1", true, false),
+        // bishops on the same color can't mate
+        ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", false, false),
+        ("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1", true, true),
+        ("4k3/8/8/8/8/8/8/2BBK3 w - - 0 1", true, false),
+    ];
+
+    for (fen, white, black) in cases {
+        let mut board = Board::empty();
+        board.load_fen(fen).unwrap();
+        assert_eq!(board.has_mating_material(Player::White), white, "{}", fen);
+        assert_eq!(board.has_mating_material(Player::Black), black, "{}", fen);
+    }
+}
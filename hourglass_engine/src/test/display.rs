@@ -0,0 +1,54 @@
+use std::str::FromStr;
+
+use crate::{Board, DiagramOptions, Move, Player, Variant};
+
+#[test]
+fn test_display() {
+    let board = Board::new();
+    assert_eq!(
+        board.to_string(),
+        "   a  b  c  d  e  f  g  h
+8  r  n  b  q  k  b  n  r  8
+7  p  p  p  p  p  p  p  p  7
+6  .  .  .  .  .  .  .  .  6
+5  .  .  .  .  .  .  .  .  5
+4  .  .  .  .  .  .  .  .  4
+3  .  .  .  .  .  .  .  .  3
+2  P  P  P  P  P  P  P  P  2
+1  R  N  B  Q  K  B  N  R  1
+   a  b  c  d  e  f  g  h
+White to move"
+    );
+
+    let unicode = board.to_unicode();
+    assert!(unicode.contains("8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜  8"));
+    assert!(unicode.contains("4  ·  ·  ·  ·  ·  ·  ·  ·  4"));
+}
+
+#[test]
+fn test_diagram_options() {
+    let mut board = Board::new();
+    let umove = Move::from_str("e2e4").unwrap();
+    board.try_move(umove).unwrap();
+
+    let diagram = board.diagram(DiagramOptions {
+        highlight: Some(umove),
+        ..Default::default()
+    });
+    assert!(diagram.contains("4  .  .  .  . [P] .  .  .  4"));
+    assert!(diagram.contains("2  P  P  P  P [.] P  P  P  2"));
+    assert!(diagram.ends_with("Black to move"));
+
+    let diagram = board.diagram(DiagramOptions {
+        attacked_by: Some(Player::White),
+        ..Default::default()
+    });
+    // the pawn, the bishop and the queen attack the 5th rank
+    assert!(diagram.contains("5  . (.) . (.) . (.) . (.) 5"));
+
+    let mut board = Board::new_variant(Variant::Crazyhouse);
+    for umove in ["e2e4", "d7d5", "e4d5"] {
+        board.try_move(Move::from_str(umove).unwrap()).unwrap();
+    }
+    assert!(board.to_string().contains("Pocket: [P]\nBlack to move"));
+}
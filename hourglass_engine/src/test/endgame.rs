@@ -0,0 +1,119 @@
+use super::{load, tablebase};
+use crate::{kpk, Board, MaterialSignature, Piece, Player, Variant, Wdl};
+
+#[test]
+fn test_material_signature() {
+    let board = load(Variant::Standard, "4k3/r7/8/8/8/8/3P4/R3K3 w - - 0 1");
+    let signature = MaterialSignature::of(&board);
+    assert_eq!(signature.to_string(), "KRPvKR");
+    assert_eq!(signature.flipped().to_string(), "KRvKRP");
+    assert_eq!(signature.count(Player::White, Piece::Pawn), 1);
+    assert_eq!(signature.count(Player::Black, Piece::Queen), 0);
+}
+
+#[test]
+fn test_kpk_bitbase_matches_tablebase() {
+    for white_to_move in [true, false] {
+        for white_king in 0..64 {
+            for black_king in 0..64 {
+                for pawn in (8..56).filter(|p| p % 8 < 4) {
+                    let mut board = Board::empty();
+                    if white_king == black_king || pawn == white_king || pawn == black_king {
+                        continue;
+                    }
+                    board.squares[white_king] = Piece::King | Piece::White;
+                    board.squares[black_king] = Piece::King | Piece::Black;
+                    board.squares[pawn] = Piece::Pawn | Piece::White;
+                    board.active_color = match white_to_move {
+                        true => Player::White,
+                        false => Player::Black,
+                    };
+                    if board.is_in_check(!board.active_color) {
+                        continue;
+                    }
+
+                    let wdl = tablebase().probe_wdl(&board).unwrap();
+                    let white_wins = match white_to_move {
+                        true => wdl == Wdl::Win,
+                        false => wdl == Wdl::Loss,
+                    };
+                    assert_eq!(
+                        kpk::probe(white_king, pawn, black_king, white_to_move),
+                        white_wins,
+                        "wrong result for \"{}\"",
+                        board.get_fen()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_score_endgames() {
+    // known wins are scored above their material, for the side to move
+    assert!(load(Variant::Standard, "8/8/8/3k4/8/8/8/Q3K3 w - - 0 1").score_endgames() > 19.0);
+    assert!(load(Variant::Standard, "8/8/8/3k4/8/8/8/Q3K3 b - - 0 1").score_endgames() < -19.0);
+    assert!(load(Variant::Standard, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").score_endgames() < -10.0);
+    assert!(load(Variant::Standard, "8/8/8/8/8/8/k3PK2/8 w - - 0 1").score_endgames() > 10.0);
+
+    // drawn endgames
+    assert_eq!(
+        load(Variant::Standard, "k7/8/8/8/8/8/P7/K7 w - - 0 1").score_endgames(),
+        0.0
+    );
+    assert_eq!(
+        load(Variant::Standard, "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1").score_endgames(),
+        0.0
+    );
+    assert_eq!(
+        load(Variant::Standard, "k7/8/8/8/8/8/P7/2B1K3 w - - 0 1").score_endgames(),
+        0.0
+    );
+    assert_eq!(
+        load(Variant::Standard, "k7/8/8/8/8/8/P7/3BK3 w - - 0 1").score_endgames(),
+        4.0
+    );
+
+    // opposite colored bishops
+    assert_eq!(
+        load(Variant::Standard, "4k3/5pp1/8/3b4/8/4B3/5PPP/6K1 w - - 0 1").score_endgames(),
+        0.25
+    );
+
+    // the king is driven to the corner that the bishop can cover
+    let right_corner = load(Variant::Standard, "7k/8/5K2/8/8/8/8/4BN2 w - - 0 1").score_endgames();
+    let wrong_corner = load(Variant::Standard, "k7/8/2K5/8/8/8/8/4BN2 w - - 0 1").score_endgames();
+    assert!(right_corner > wrong_corner);
+}
+
+#[test]
+fn test_insufficient_material() {
+    let cases = [
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+        ("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", true),
+        ("4k3/8/8/8/8/8/8/2B1K3 b - - 0 1", true),
+        // bishops on the same color can never attack the king on the other color
+        ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+        ("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false),
+        // the pawns are locked, and neither king can get to a pawn it could capture
+        ("8/8/4k3/p1p1p1p1/P1P1P1P1/4K3/8/8 w - - 0 1", true),
+        // the kings can go around the side of the chain
+        ("8/8/4k3/p1p1p3/P1P1P3/4K3/8/8 w - - 0 1", false),
+        ("8/8/4k3/3p4/3P4/4K3/8/8 w - - 0 1", false),
+        // a pawn that can still capture
+        ("8/8/4k3/p1pp2p1/P1PP1PP1/4K3/8/8 w - - 0 1", false),
+        ("8/8/4k3/p1p1p1p1/P1P1P1P1/4K3/8/7B w - - 0 1", false),
+    ];
+
+    for (fen, insufficient) in cases {
+        assert_eq!(
+            load(Variant::Standard, fen).is_insufficient_material(),
+            insufficient,
+            "{}",
+            fen
+        );
+    }
+}
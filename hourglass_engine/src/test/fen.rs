@@ -1,4 +1,9 @@
-use crate::Board;
+use std::str::FromStr;
+
+use crate::{
+    fen::{FenParseErr, FenPart},
+    Board, EpdOperation, Move,
+};
 
 #[test]
 fn test_fen_basic() {
@@ -13,7 +18,96 @@ fn test_fen_basic() {
 
     // Ensure that loading and getting the fen yeild the same result
     for fen in fens {
-        board.load_fen(fen);
+        board.load_fen(fen).unwrap();
         assert_eq!(fen, board.get_fen());
     }
 }
+
+#[test]
+fn test_epd_operations() {
+    let epd = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+    let mut board = Board::empty();
+    let operations = board.load_epd(epd).unwrap();
+
+    assert_eq!(
+        operations,
+        vec![
+            EpdOperation::BestMoves(vec![Move::from_str("g3g6").unwrap()]),
+            EpdOperation::Id("WAC.001".to_owned()),
+        ]
+    );
+    assert_eq!(
+        board.get_fen(),
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1"
+    );
+    assert_eq!(board.get_epd(&operations), epd);
+}
+
+#[test]
+fn test_epd_other_operations() {
+    let epd = "4k3/8/8/8/8/8/8/4K2R w K - dm 3; ce -25; c0 \"a; comment\"; hmvc 7; pv Rh8+ Kd7";
+    let mut board = Board::empty();
+    let operations = board.load_epd(epd).unwrap();
+
+    assert_eq!(
+        operations,
+        vec![
+            EpdOperation::DirectMate(3),
+            EpdOperation::CentipawnEval(-25),
+            EpdOperation::Comment(0, "a; comment".to_owned()),
+            EpdOperation::Other {
+                opcode: "pv".to_owned(),
+                operands: vec!["Rh8+".to_owned(), "Kd7".to_owned()],
+            },
+        ]
+    );
+    assert_eq!(board.halfmove(), 7);
+    assert_eq!(
+        board.get_epd(&operations),
+        "4k3/8/8/8/8/8/8/4K2R w K - dm 3; ce -25; c0 \"a; comment\"; pv Rh8+ Kd7; hmvc 7;"
+    );
+
+    // the fields can be separated by more than one space, and the counters round trip
+    let epd = "4k3/8/8/8/8/8/8/4K2R  b   K -   hmvc 12;  fmvn 40;";
+    assert_eq!(board.load_epd(epd).unwrap(), vec![]);
+    assert_eq!(board.get_fen(), "4k3/8/8/8/8/8/8/4K2R b K - 12 40");
+    assert_eq!(
+        board.get_epd(&[]),
+        "4k3/8/8/8/8/8/8/4K2R b K - hmvc 12; fmvn 40;"
+    );
+
+    assert_eq!(
+        board.load_epd("4k3/8/8/8/8/8/8/4K2R w K - bm Rh9;"),
+        Err(FenParseErr::invalid(
+            FenPart::Operations,
+            0,
+            "invalid SAN move"
+        ))
+    );
+}
+
+#[test]
+fn test_epd_quoted_strings() {
+    let mut board = Board::empty();
+    let operations = vec![
+        EpdOperation::Id("the \"Immortal\" game".to_owned()),
+        EpdOperation::Comment(1, "a \\ backslash".to_owned()),
+    ];
+    board.load_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+
+    let epd = board.get_epd(&operations);
+    assert_eq!(
+        epd,
+        "4k3/8/8/8/8/8/8/4K2R w K - id \"the \\\"Immortal\\\" game\"; c1 \"a \\\\ backslash\";"
+    );
+    assert_eq!(board.load_epd(&epd).unwrap(), operations);
+}
+
+#[test]
+fn test_fen_whitespace() {
+    let mut board = Board::empty();
+    board
+        .load_fen("4k3/8/8/8/8/8/8/4K2R  w\tK -   3 20\n")
+        .unwrap();
+    assert_eq!(board.get_fen(), "4k3/8/8/8/8/8/8/4K2R w K - 3 20");
+}
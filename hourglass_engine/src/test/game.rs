@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use crate::{Board, Game, GameResult, GameStatus, Move, PgnGame, Piece, Player};
+
+fn game_from_fen(fen: &str) -> Game {
+    let mut board = Board::empty();
+    board.load_fen(fen).unwrap();
+    Game::from_board(board)
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut game = Game::new();
+    for san in ["e4", "e5", "Nf3"] {
+        game.play_san(san).unwrap();
+    }
+    let after_nf3 = *game.board();
+
+    assert_eq!(game.undo(), Some(Move::from_str("g1f3").unwrap()));
+    assert_eq!(game.undo(), Some(Move::from_str("e7e5").unwrap()));
+    assert!(game.can_redo());
+    assert_eq!(game.moves().len(), 1);
+
+    // replaying the undone move keeps the rest to redo
+    game.play_san("e5").unwrap();
+    assert_eq!(game.redo(), Some(Move::from_str("g1f3").unwrap()));
+    assert_eq!(*game.board(), after_nf3);
+    assert!(!game.can_redo());
+
+    game.undo();
+    game.play_san("Nc3").unwrap();
+    assert!(!game.can_redo());
+    assert_eq!(game.redo(), None);
+
+    while game.undo().is_some() {}
+    assert_eq!(*game.board(), Board::new());
+    assert!(!game.can_undo());
+}
+
+#[test]
+fn test_captured_pieces() {
+    let mut game = Game::new();
+    for san in ["e4", "d5", "exd5", "c5", "dxc6"] {
+        game.play_san(san).unwrap();
+    }
+
+    let captured = game.moves().iter().map(|m| m.captured).collect::<Vec<_>>();
+    assert_eq!(
+        captured,
+        vec![
+            None,
+            None,
+            Some(Piece::Pawn | Piece::Black),
+            None,
+            Some(Piece::Pawn | Piece::Black),
+        ]
+    );
+    assert_eq!(game.moves()[4].san, "dxc6");
+}
+
+#[test]
+fn test_repetition() {
+    let mut game = Game::new();
+    for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1"] {
+        game.play_san(san).unwrap();
+        assert_eq!(game.status(), GameStatus::Ongoing);
+    }
+    assert_eq!(game.repetitions(), 2);
+
+    game.play_san("Ng8").unwrap();
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(game.status(), GameStatus::ThreefoldRepetition);
+    assert_eq!(game.status().result(), GameResult::Draw);
+
+    game.undo();
+    assert_eq!(game.status(), GameStatus::Ongoing);
+}
+
+#[test]
+fn test_status() {
+    let mut game = Game::new();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.play_san(san).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::Checkmate(Player::Black));
+    assert!(game.status().is_over());
+
+    let game = game_from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+    assert_eq!(game.status(), GameStatus::Stalemate);
+
+    let mut game = game_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+    game.play_san("Ra2").unwrap();
+    assert_eq!(game.status(), GameStatus::FiftyMoveRule);
+
+    let mut game = game_from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1");
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    game.play_san("Kxd2").unwrap();
+    assert_eq!(game.status(), GameStatus::InsufficientMaterial);
+    assert_eq!(game.status().result(), GameResult::Draw);
+}
+
+#[test]
+fn test_game_to_pgn() {
+    let mut game = Game::new();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.play_san(san).unwrap();
+    }
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Result \"0-1\"]"));
+    assert!(pgn.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+    assert!(!pgn.contains("FEN"));
+
+    let mut game = game_from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    game.play_san("e4").unwrap();
+    game.play_san("Kd7").unwrap();
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]"));
+
+    let parsed = Game::from_pgn(&PgnGame::parse(&pgn).unwrap()).unwrap();
+    assert_eq!(parsed, game);
+}
@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use super::load;
+use crate::{Board, Move, Player, Square, SquareSet, Variant};
+
+fn squares(names: &[&str]) -> SquareSet {
+    names.iter().map(|n| Square::from_str(n).unwrap()).collect()
+}
+
+#[test]
+fn test_attackers_to() {
+    let board = load(Variant::Standard, "4k3/8/8/3n4/3p4/4K2q/8/B2R4 w - - 0 1");
+    assert_eq!(
+        board.attackers_to(Square::D4, Player::White),
+        squares(&["a1", "d1", "e3"])
+    );
+    assert_eq!(
+        board.attackers_to(Square::E3, Player::Black),
+        squares(&["d5", "d4", "h3"])
+    );
+    // the king blocks the queen
+    assert_eq!(
+        board.attackers_to(Square::D3, Player::Black),
+        SquareSet::EMPTY
+    );
+    assert_eq!(board.checkers(), squares(&["d5", "d4", "h3"]));
+    assert_eq!(Board::new().checkers(), SquareSet::EMPTY);
+}
+
+#[test]
+fn test_pins() {
+    let board = load(Variant::Standard, "4r1k1/8/8/8/b7/8/2N1R3/4K3 w - - 0 1");
+    let pins = board.pinned(Player::White);
+    assert_eq!(pins.pieces(), squares(&["e2"]));
+    assert_eq!(
+        pins.ray(Square::E2),
+        Some(squares(&["e2", "e3", "e4", "e5", "e6", "e7", "e8"]))
+    );
+    assert_eq!(pins.ray(Square::C2), None);
+    assert_eq!(board.pinned(Player::Black).pieces(), SquareSet::EMPTY);
+
+    // a piece of the moving player between their slider and the other king
+    let board = load(Variant::Standard, "4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1");
+    let discovered = board.discovered_check_candidates();
+    assert_eq!(discovered, squares(&["e4"]));
+    assert!(board.gives_check(Move::from_str("e4c5").unwrap(), discovered));
+    assert!(board.gives_check(Move::from_str("e4f6").unwrap(), discovered));
+    assert!(!board.gives_check(Move::from_str("g1h1").unwrap(), discovered));
+
+    // the horde has no king to pin to
+    let board = Board::new_variant(Variant::Horde);
+    assert_eq!(board.pinned(Player::White).pieces(), SquareSet::EMPTY);
+}
+
+#[test]
+fn test_gives_check() {
+    let positions = [
+        (
+            Variant::Standard,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ),
+        (
+            Variant::Standard,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ),
+        (
+            Variant::Standard,
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ),
+        // castling into check, and en passant uncovering a check
+        (Variant::Standard, "5k2/8/8/3pP3/8/8/8/R3K2B w Q d6 0 1"),
+        (Variant::Standard, "r4k2/8/8/8/8/8/8/4K2R w K - 0 1"),
+        (
+            Variant::Crazyhouse,
+            "r1bqkbnr/ppp2ppp/2np4/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R[Pn] w KQkq - 0 4",
+        ),
+        (
+            Variant::Atomic,
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+        ),
+    ];
+
+    let mut checks = 0;
+    for (variant, fen) in positions {
+        let board = load(variant, fen);
+        let discovered = board.discovered_check_candidates();
+        for umove in board.generate_moves() {
+            let mut after = board;
+            after.make_move_unchecked(umove).unwrap();
+            let is_check = after.is_in_check(after.active_color());
+            assert_eq!(
+                board.gives_check(umove, discovered),
+                is_check,
+                "{} in \"{}\"",
+                umove,
+                fen
+            );
+            checks += is_check as usize;
+        }
+    }
+    assert!(checks >= 8);
+}
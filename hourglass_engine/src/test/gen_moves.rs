@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+use super::load;
+use crate::{Board, CrazyhouseMoveList, GenKind, Move, MoveList, Square, Variant};
+
+const PERFT_POSITIONS: [(&str, u32, u64); 5] = [
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        97862,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674624),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        3,
+        9467,
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        3,
+        62379,
+    ),
+    (
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        3,
+        89890,
+    ),
+];
+
+fn moves_of(board: &Board, moves: &[&str]) -> Vec<Move> {
+    let mut expected = moves
+        .iter()
+        .map(|m| board.resolve_move(Move::from_str(m).unwrap()).unwrap())
+        .collect::<Vec<_>>();
+    expected.sort_by_key(|m| (m.from(), m.to()));
+    let mut generated = board.generate_moves().to_vec();
+    generated.sort_by_key(|m| (m.from(), m.to()));
+    assert_eq!(
+        generated,
+        expected,
+        "wrong moves for \"{}\"\n{}",
+        board.get_fen(),
+        board
+    );
+    generated
+}
+
+#[test]
+fn test_perft() {
+    for (fen, depth, nodes) in PERFT_POSITIONS {
+        let board = load(Variant::Standard, fen);
+        assert_eq!(board.perft(depth), nodes, "perft failed for \"{}\"", fen);
+    }
+}
+
+#[test]
+fn test_pins_and_evasions() {
+    // the rook is pinned, so it can only move along the file
+    let board = load(Variant::Standard, "4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1");
+    let mut rook_moves = MoveList::new();
+    board.get_moves_for(&mut rook_moves, Square::E2);
+    assert_eq!(rook_moves.len(), 6);
+    assert!(rook_moves.iter().all(|m| m.to().file() == 4));
+
+    // only the king can move out of a double check
+    moves_of(
+        &load(Variant::Standard, "4k3/8/8/8/1b6/1N6/8/r3K3 w - - 0 1"),
+        &["e1e2", "e1f2"],
+    );
+
+    // the checker can be captured, but the king can't step back along the line of the check
+    moves_of(
+        &load(Variant::Standard, "4k3/8/8/8/8/8/7R/3K3r w - - 0 1"),
+        &["d1c2", "d1d2", "d1e2", "h2h1"],
+    );
+
+    // capturing en passant would expose the king along the rank
+    moves_of(
+        &load(Variant::Standard, "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1"),
+        &["a5a4", "a5b4", "a5a6", "a5b6", "a5b5", "e5e6"],
+    );
+}
+
+#[test]
+fn test_pseudo_legal() {
+    let board = load(Variant::Standard, "k7/8/8/8/8/8/4R3/4K2r w - - 0 1");
+    let pseudo_legal = board.generate_pseudo_legal();
+    let legal = board.generate_moves();
+    assert!(pseudo_legal.len() > legal.len());
+    assert_eq!(
+        pseudo_legal
+            .into_iter()
+            .filter(|m| board.is_legal(*m))
+            .collect::<MoveList>(),
+        legal
+    );
+
+    // the fast filter agrees with playing out every move
+    for (fen, _, _) in PERFT_POSITIONS {
+        let board = load(Variant::Standard, fen);
+        for umove in board.generate_moves() {
+            let mut after = board;
+            after.make_move_unchecked(umove).unwrap();
+            let slow = after
+                .generate_pseudo_legal()
+                .into_iter()
+                .filter(|m| after.is_legal(*m))
+                .collect::<MoveList>();
+            assert_eq!(after.generate_moves(), slow, "for \"{}\"", after.get_fen());
+        }
+    }
+}
+
+/// Checks that each kind of move generation adds the legal moves of its kind.
+pub(super) fn check_gen_kinds(board: &Board) {
+    // with room for the drops, as the variants are checked too
+    let mut legal = CrazyhouseMoveList::default();
+    let mut captures = CrazyhouseMoveList::default();
+    let mut quiets = CrazyhouseMoveList::default();
+    let mut quiet_checks = CrazyhouseMoveList::default();
+    let mut evasions = CrazyhouseMoveList::default();
+
+    board.generate_moves_into(&mut legal);
+    board.generate(GenKind::Captures, &mut captures);
+    board.generate(GenKind::Quiets, &mut quiets);
+    board.generate(GenKind::QuietChecks, &mut quiet_checks);
+    board.generate(GenKind::Evasions, &mut evasions);
+
+    assert!(captures.iter().all(|m| m.is_capture() || m.is_promotion()));
+    let mut union = captures
+        .iter()
+        .chain(quiets.iter())
+        .copied()
+        .collect::<Vec<_>>();
+    union.sort_by_key(|m| (m.from(), m.to(), m.promote().map(|p| p.bits())));
+    let mut expected = legal.to_vec();
+    expected.sort_by_key(|m| (m.from(), m.to(), m.promote().map(|p| p.bits())));
+    assert_eq!(union, expected, "for \"{}\"", board.get_fen());
+
+    for umove in quiet_checks.iter() {
+        assert!(quiets.contains(umove));
+        let mut after = *board;
+        after.make_move_unchecked(*umove).unwrap();
+        assert!(after.is_in_check(after.active_color()));
+    }
+    let quiet_check_count = quiets
+        .iter()
+        .filter(|m| {
+            let mut after = *board;
+            after.make_move_unchecked(**m).unwrap();
+            after.is_in_check(after.active_color())
+        })
+        .count();
+    assert_eq!(
+        quiet_checks.len(),
+        quiet_check_count,
+        "for \"{}\"",
+        board.get_fen()
+    );
+
+    match board.is_in_check(board.active_color()) {
+        true => assert_eq!(evasions, legal, "for \"{}\"", board.get_fen()),
+        false => assert!(evasions.is_empty()),
+    }
+}
+
+#[test]
+fn test_gen_kinds() {
+    // the positions one move into each perft position include some checks
+    let mut positions = Vec::new();
+    for (fen, _, _) in PERFT_POSITIONS {
+        let board = load(Variant::Standard, fen);
+        positions.push(board);
+        for umove in board.generate_moves() {
+            let mut after = board;
+            after.make_move_unchecked(umove).unwrap();
+            positions.push(after);
+        }
+    }
+    assert!(positions.iter().any(|b| b.is_in_check(b.active_color())));
+
+    for board in positions {
+        check_gen_kinds(&board);
+    }
+
+    let mut all = MoveList::new();
+    Board::new().generate(GenKind::All, &mut all);
+    assert_eq!(all, Board::new().generate_moves());
+}
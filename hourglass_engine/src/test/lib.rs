@@ -1,8 +1,8 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use chess::MoveGen;
 
-use crate::{idx_to_square_name, square_name_to_idx, squares_to_edge, Direction};
+use crate::{squares_to_edge, Board, Direction, Move, Piece, SearchLimits, Square};
 
 #[test]
 fn test_squares_to_edge() {
@@ -53,7 +53,6 @@ fn test_move_gen(my_board: crate::Board, depth: usize) {
     let chess_board = chess::Board::from_str(&my_board.get_fen())
         .expect("My chess board produced an invalid fen");
     let mut chess_move_gen = MoveGen::new_legal(&chess_board)
-        .into_iter()
         .map(|c_move| (c_move, false))
         .collect::<Vec<_>>();
     let my_moves = my_board.generate_moves();
@@ -61,9 +60,9 @@ fn test_move_gen(my_board: crate::Board, depth: usize) {
     for my_move in my_moves.iter() {
         let mut good_move = false;
         for (chess_move, accounted_for) in chess_move_gen.iter_mut() {
-            let sources_match = chess_move.get_source().to_index() == my_move.from;
-            let destinations_match = chess_move.get_dest().to_index() == my_move.to;
-            let promotions_match = compare_pieces(my_move.promote, chess_move.get_promotion());
+            let sources_match = chess_move.get_source().to_index() == my_move.origin_idx();
+            let destinations_match = chess_move.get_dest().to_index() == my_move.target_idx();
+            let promotions_match = compare_pieces(my_move.promote(), chess_move.get_promotion());
             let matches = sources_match && destinations_match && promotions_match;
 
             if matches {
@@ -73,23 +72,23 @@ fn test_move_gen(my_board: crate::Board, depth: usize) {
         }
 
         if !good_move {
-            let promoting_to = match my_move.promote {
+            let promoting_to = match my_move.promote() {
                 None => "".to_owned(),
                 Some(piece) => format!(" promoting to {:?}", piece),
             };
             panic!(
                 "My chess engine came up with the creative move {} {}{}.\n Here is the fen :\"{}\"",
-                idx_to_square_name(my_move.from).unwrap(),
-                idx_to_square_name(my_move.to).unwrap(),
+                my_move.from(),
+                my_move.to(),
                 promoting_to,
                 my_board.get_fen(),
             );
         }
     }
 
-    for missed_chess_move in chess_move_gen
+    if let Some(missed_chess_move) = chess_move_gen
         .iter()
-        .filter(|(_, accounted_for)| !accounted_for)
+        .find(|(_, accounted_for)| !accounted_for)
     {
         let promoting_to = match missed_chess_move.0.get_promotion() {
             None => "".to_owned(),
@@ -97,8 +96,8 @@ fn test_move_gen(my_board: crate::Board, depth: usize) {
         };
         panic!(
             "My chess engine missed the move {} {}{}.\n Here is the fen :\"{}\"",
-            idx_to_square_name(missed_chess_move.0.get_source().to_index()).unwrap(),
-            idx_to_square_name(missed_chess_move.0.get_dest().to_index()).unwrap(),
+            Square::new(missed_chess_move.0.get_source().to_index()).unwrap(),
+            Square::new(missed_chess_move.0.get_dest().to_index()).unwrap(),
             promoting_to,
             my_board.get_fen(),
         );
@@ -113,7 +112,7 @@ fn test_move_gen(my_board: crate::Board, depth: usize) {
 
     // Now make check every move and check those positions
     for my_move in my_moves {
-        let mut board = my_board.clone();
+        let mut board = my_board;
         board
             .try_move(my_move)
             .expect("A generated move should be legal");
@@ -144,12 +143,99 @@ fn compare_pieces(my_piece: Option<crate::Piece>, other: Option<chess::Piece>) -
 }
 
 #[test]
-fn test_idx_to_square_name() {
-    let square_names = ["a1", "b1", "c3", "h8"];
-    let square_idxs = [0, 1, 18, 63];
+fn test_packed_move() {
+    assert_eq!(std::mem::size_of::<Move>(), 2);
+
+    let umove = Move::new(Square::E7, Square::E8, Some(Piece::Queen));
+    assert_eq!(umove.from(), Square::E7);
+    assert_eq!(umove.to(), Square::E8);
+    assert_eq!(umove.promote(), Some(Piece::Queen));
+    assert_eq!(umove.to_string(), "e7e8q");
+    assert_eq!(Move::from_str("e7e8q").unwrap(), umove);
+    assert!(!umove.is_capture());
+
+    let drop = Move::new_drop(Piece::Knight, Square::F3);
+    assert_eq!(drop.dropped(), Some(Piece::Knight));
+    assert_eq!(drop.from(), Square::F3);
+    assert_eq!(drop.to_string(), "N@f3");
+}
+
+#[test]
+fn test_move_flags() {
+    let mut board = Board::empty();
+    board
+        .load_fen("r3k3/1P6/8/3pP3/8/8/8/R3K2R w KQq d6 0 1")
+        .unwrap();
+    let moves = board.generate_moves();
+    let find = |s: &str| {
+        let parsed = Move::from_str(s).unwrap();
+        let resolved = board.resolve_move(parsed).unwrap();
+        assert!(moves.contains(&resolved));
+        resolved
+    };
+
+    assert!(!find("e5e6").is_capture());
+    let en_passant = find("e5d6");
+    assert!(en_passant.is_capture() && en_passant.is_en_passant());
+    assert!(find("e1g1").is_castle());
+    assert!(find("a1a8").is_capture());
+    let promote_capture = find("b7a8n");
+    assert!(promote_capture.is_capture());
+    assert_eq!(promote_capture.promote(), Some(Piece::Knight));
+    assert!(!find("b7b8q").is_capture());
+
+    assert_eq!(
+        board.resolve_move(Move::from_str("b7b8").unwrap()),
+        Err(crate::InvalidMoveErr::NoPromotion)
+    );
+    assert_eq!(
+        board.resolve_move(Move::from_str("a8a7").unwrap()),
+        Err(crate::InvalidMoveErr::NotYourPiece)
+    );
+}
+
+#[test]
+fn test_search_limited() {
+    // white mates with Rd8
+    let mut board = Board::empty();
+    board
+        .load_fen("6k1/5pp1/7Q/8/8/8/6PP/3R2K1 w - - 0 1")
+        .unwrap();
+
+    let deep = board.search_limited(&SearchLimits::depth(3), Board::score_material, None);
+    assert_eq!(deep.depth, 3);
+    assert_eq!(deep.score, f32::INFINITY);
+    let moves = board.generate_moves();
+    let (idx, score) = board.search(3, &moves, Board::score_material);
+    assert_eq!(deep.best_move, Some(moves[idx]));
+    assert_eq!(deep.score, score);
+
+    // the node limit stops the search after the first depth that reaches it
+    let limits = SearchLimits {
+        nodes: Some(1),
+        ..SearchLimits::depth(3)
+    };
+    let shallow = board.search_limited(&limits, Board::score_material, None);
+    assert_eq!(shallow.depth, 1);
+    assert!(shallow.nodes > 1 && shallow.nodes < deep.nodes);
 
-    for (square_name, square_idx) in square_names.iter().zip(square_idxs) {
-        assert_eq!(*square_name, idx_to_square_name(square_idx).unwrap());
-        assert_eq!(square_idx, square_name_to_idx(square_name).unwrap());
+    let mut mated = Board::new();
+    for umove in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+        mated.try_move(Move::from_str(umove).unwrap()).unwrap();
     }
+    let info = mated.search_limited(&SearchLimits::depth(2), Board::score_material, None);
+    assert_eq!(info.best_move, None);
+    assert_eq!(info.score, f32::NEG_INFINITY);
+
+    // no time to start the second depth
+    let limits = SearchLimits {
+        time: Some(Duration::ZERO),
+        ..SearchLimits::depth(3)
+    };
+    assert_eq!(
+        board
+            .search_limited(&limits, Board::score_material, None)
+            .depth,
+        1
+    );
 }
@@ -1,3 +1,49 @@
+mod chess960;
+mod clock;
+mod display;
+mod endgame;
 mod fen;
+mod game;
+mod gen_attacks;
+mod gen_moves;
 mod lib;
+mod move_list;
+mod packed;
+mod pgn;
 mod pieces;
+mod polyglot;
+mod san;
+#[cfg(feature = "serde")]
+mod serialize;
+mod square;
+#[cfg(feature = "svg")]
+mod svg;
+mod syzygy;
+mod tablebase;
+mod validate;
+mod variant;
+
+use std::sync::OnceLock;
+
+use crate::{Board, FenParseErr, Tablebase, Variant};
+
+/// Sets up a board of the variant from a fen.
+pub(super) fn try_load(variant: Variant, fen: &str) -> Result<Board, FenParseErr> {
+    let mut board = Board::empty();
+    board.set_variant(variant);
+    board.load_fen(fen).map(|_| board)
+}
+
+/// Sets up a board of the variant from a fen, which has to be valid.
+pub(super) fn load(variant: Variant, fen: &str) -> Board {
+    try_load(variant, fen).expect(fen)
+}
+
+/// The directory with the three piece Syzygy tables that the tests read.
+const SYZYGY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test/syzygy");
+
+/// The tables are read when they are first probed, so the tablebase is shared between tests.
+fn tablebase() -> &'static Tablebase {
+    static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+    TABLEBASE.get_or_init(|| Tablebase::open(SYZYGY_PATH).unwrap())
+}
@@ -0,0 +1,113 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    str::FromStr,
+};
+
+use crate::{Board, CrazyhouseMoveList, Move, MoveList, Square, Variant};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations of each thread, so that tests running at the same time don't interfere.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+fn moves(moves: &[&str]) -> Vec<Move> {
+    moves.iter().map(|m| Move::from_str(m).unwrap()).collect()
+}
+
+#[test]
+fn test_move_list() {
+    let mut list = MoveList::new();
+    assert!(list.is_empty());
+    for (umove, score) in moves(&["e2e4", "d2d4", "g1f3", "c2c4"])
+        .into_iter()
+        .zip([1, 5, 1, 3])
+    {
+        list.push_scored(umove, score);
+    }
+    assert_eq!(list.len(), 4);
+
+    list.sort_by_score();
+    assert_eq!(list.to_vec(), moves(&["d2d4", "c2c4", "e2e4", "g1f3"]));
+    assert_eq!(list.score(1), 3);
+
+    list.retain(|m| m.from() != Square::D2);
+    assert_eq!(list.to_vec(), moves(&["c2c4", "e2e4", "g1f3"]));
+    assert_eq!(list.score(0), 3);
+
+    let collected = list.into_iter().collect::<MoveList>();
+    assert_eq!(collected, list);
+    assert_eq!(collected.score(0), 0);
+    assert!(list.contains(&Move::from_str("g1f3").unwrap()));
+
+    list.clear();
+    assert!(list.is_empty());
+}
+
+#[test]
+fn test_drops_fit() {
+    assert_eq!(<MoveList>::CAPACITY, 256);
+    assert_eq!(CrazyhouseMoveList::CAPACITY, 512);
+
+    let mut board = Board::empty();
+    board.set_variant(Variant::Crazyhouse);
+    board
+        .load_fen("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1")
+        .unwrap();
+    let mut moves = CrazyhouseMoveList::default();
+    board.generate_pseudo_legal_into(&mut moves);
+    assert!(moves.len() > <MoveList>::CAPACITY);
+
+    // the search and the rules use a list that fits the variant
+    assert_eq!(board.perft(1), moves.len() as u64);
+    assert!(board.get_best_move(1, Board::score_material).is_some());
+    let last = moves[moves.len() - 1];
+    assert_eq!(board.parse_san(&board.move_to_san(last)), Ok(last));
+}
+
+#[test]
+fn test_search_does_not_allocate() {
+    // make sure that allocations are counted at all
+    let before = allocations();
+    let moves = Board::new().generate_moves().to_vec();
+    assert!(allocations() > before && !moves.is_empty());
+
+    for variant in [Variant::Standard, Variant::Crazyhouse, Variant::Atomic] {
+        let mut board = Board::new_variant(variant);
+        for umove in ["e2e4", "d7d5", "e4d5"] {
+            board.try_move(Move::from_str(umove).unwrap()).unwrap();
+        }
+        let moves = board.generate_moves();
+
+        let before = allocations();
+        let mut list = MoveList::new();
+        board.generate_moves_into(&mut list);
+        board.search(3, &moves, Board::score_material);
+        assert_eq!(
+            allocations(),
+            before,
+            "searching {} allocated",
+            variant.pgn_name()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::load;
+use crate::{
+    Board, GameResult, MoveList, PackErr, PackedPosition, PackedReader, PackedWriter, Variant,
+    PACKED_POSITION_SIZE,
+};
+
+fn round_trip(board: Board) {
+    let position = PackedPosition {
+        board,
+        score: -123,
+        result: GameResult::Draw,
+    };
+    let bytes = position.to_bytes().unwrap();
+    let unpacked = PackedPosition::from_bytes(&bytes).unwrap();
+    assert_eq!(unpacked.board.get_fen(), board.get_fen());
+    assert_eq!(unpacked.board.variant(), board.variant());
+    assert_eq!(unpacked, position, "{}", board.get_fen());
+}
+
+#[test]
+fn test_packed_round_trip() {
+    let positions = [
+        (
+            Variant::Standard,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        ),
+        (
+            Variant::Standard,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ),
+        (Variant::Standard, "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 13 40"),
+        (
+            Variant::Standard,
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ),
+        (Variant::Standard, "8/8/8/8/8/8/8/K6k b - - 99 300"),
+        (
+            Variant::ThreeCheck,
+            "rnbqkbnr/ppp2ppp/8/3pp3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3 +2+1",
+        ),
+        (Variant::KingOfTheHill, "8/8/8/3k4/8/8/8/4K3 w - - 0 1"),
+        (Variant::RacingKings, Variant::RacingKings.starting_fen()),
+        (Variant::Atomic, Variant::Atomic.starting_fen()),
+        (Variant::Antichess, Variant::Antichess.starting_fen()),
+        (Variant::Horde, "4k3/8/8/8/8/8/PPPPPPPP/PPPPPPPP w - - 0 1"),
+    ];
+    for (variant, fen) in positions {
+        round_trip(load(variant, fen));
+    }
+
+    let mut chess960 = Board::empty();
+    chess960.set_chess960(true);
+    chess960
+        .load_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+        .unwrap();
+    round_trip(chess960);
+
+    // positions from random games
+    let mut rng = StdRng::seed_from_u64(48);
+    let mut list = MoveList::new();
+    for _ in 0..20 {
+        let mut board = Board::new();
+        for _ in 0..80 {
+            round_trip(board);
+            board.generate_moves_into(&mut list);
+            let Some(umove) = list.as_slice().choose(&mut rng).copied() else {
+                break;
+            };
+            board.make_move_unchecked(umove).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_packed_layout() {
+    let position = PackedPosition {
+        board: Board::new(),
+        score: 35,
+        result: GameResult::WhiteWins,
+    };
+    let bytes = position.to_bytes().unwrap();
+    assert_eq!(bytes.len(), PACKED_POSITION_SIZE);
+    assert_eq!(&bytes[0..8], &0xFFFF_0000_0000_FFFFu64.to_le_bytes());
+    // the rooks on a1 and h1 can castle, so they have the castle rook code
+    assert_eq!(bytes[8], 0x37);
+    assert_eq!(bytes[23], 0xFB);
+    assert_eq!(bytes[24], 64);
+    assert_eq!(&bytes[26..28], &[1, 0]);
+    assert_eq!(&bytes[28..30], &[35, 0]);
+    assert_eq!(bytes[30], 2);
+    assert_eq!(bytes[31], 0);
+}
+
+#[test]
+fn test_packed_errors() {
+    let crazyhouse = PackedPosition {
+        board: Board::new_variant(Variant::Crazyhouse),
+        score: 0,
+        result: GameResult::Ongoing,
+    };
+    assert!(matches!(
+        crazyhouse.to_bytes(),
+        Err(PackErr::UnsupportedVariant(Variant::Crazyhouse))
+    ));
+
+    let horde = PackedPosition {
+        board: Board::new_variant(Variant::Horde),
+        ..crazyhouse
+    };
+    assert!(matches!(horde.to_bytes(), Err(PackErr::TooManyPieces(52))));
+
+    let mut bytes = PackedPosition {
+        board: Board::new(),
+        ..crazyhouse
+    }
+    .to_bytes()
+    .unwrap();
+    bytes[30] = 4;
+    assert!(matches!(
+        PackedPosition::from_bytes(&bytes),
+        Err(PackErr::InvalidResult(4))
+    ));
+    bytes[30] = 3;
+    bytes[8] = 0;
+    assert!(matches!(
+        PackedPosition::from_bytes(&bytes),
+        Err(PackErr::InvalidPieceCode(0))
+    ));
+    // a pawn on a1
+    bytes[8] = 0x32;
+    assert!(matches!(
+        PackedPosition::from_bytes(&bytes),
+        Err(PackErr::InvalidPosition(_))
+    ));
+}
+
+#[test]
+fn test_packed_stream() {
+    let boards = [
+        Board::new(),
+        load(Variant::Standard, "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 13 40"),
+        load(Variant::Atomic, "8/8/8/3k4/8/8/8/4K3 w - - 0 1"),
+    ];
+    let mut writer = PackedWriter::new(vec![]);
+    for (idx, board) in boards.iter().enumerate() {
+        let position = PackedPosition {
+            board: *board,
+            score: idx as i16 * 100,
+            result: GameResult::BlackWins,
+        };
+        writer.write(&position).unwrap();
+    }
+    writer.flush().unwrap();
+    let bytes = writer.into_inner();
+    assert_eq!(bytes.len(), 3 * PACKED_POSITION_SIZE);
+
+    let read = PackedReader::new(bytes.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(read.len(), 3);
+    for (idx, position) in read.iter().enumerate() {
+        assert_eq!(position.board, boards[idx]);
+        assert_eq!(position.score, idx as i16 * 100);
+        assert_eq!(position.result, GameResult::BlackWins);
+    }
+
+    // a stream that ends partway through a position is an error, and stops the reader
+    let mut reader = PackedReader::new(&bytes[..PACKED_POSITION_SIZE + 5]);
+    assert!(matches!(reader.next(), Some(Ok(_))));
+    assert!(matches!(reader.next(), Some(Err(PackErr::Truncated(5)))));
+    assert!(reader.next().is_none());
+}
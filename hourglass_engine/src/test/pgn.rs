@@ -0,0 +1,114 @@
+use crate::{Game, GameResult, InvalidMoveErr, PgnGame, PgnParseErr, SanParseErr};
+
+const TWO_GAMES: &str = r#"[Event "Casual Game"]
+[Site "Berlin GER"]
+[Date "1852.??.??"]
+[Round "?"]
+[White "Adolf Anderssen"]
+[Black "Jean Dufresne"]
+[Result "1-0"]
+
+1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.b4 Bxb4 5.c3 Ba5 6.d4 exd4 7.O-O d3 8.Qb3 Qf6
+9.e5 Qg6 10.Re1 Nge7 11.Ba3 b5 12.Qxb5 Rb8 13.Qa4 Bb6 14.Nbd2 Bb7 15.Ne4 Qf5
+16.Bxd3 Qh5 17.Nf6+ gxf6 18.exf6 Rg8 19.Rad1 Qxf3 20.Rxe7+ Nxe7 21.Qxd7+ Kxd7
+22.Bf5+ Ke8 23.Bd7+ Kf8 24.Bxe7# 1-0
+
+[Event "?"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+[SetUp "1"]
+
+{Starting comment} 1. e4 $1 {A good move} (1. e3 Kd7 (1... Ke7) 2. e4) 1... Kd7 2. Kd2!? *
+"#;
+
+#[test]
+fn test_parse_multiple_games() {
+    let games = PgnGame::parse_all(TWO_GAMES).unwrap();
+    assert_eq!(games.len(), 2);
+
+    let immortal = &games[0];
+    assert_eq!(immortal.tag("White"), Some("Adolf Anderssen"));
+    assert_eq!(immortal.result, GameResult::WhiteWins);
+    assert_eq!(immortal.moves.len(), 47);
+    assert_eq!(immortal.moves.last().unwrap().san, "Bxe7#");
+
+    let annotated = &games[1];
+    assert_eq!(annotated.result, GameResult::Ongoing);
+    assert_eq!(annotated.comments, vec!["Starting comment".to_owned()]);
+    assert_eq!(annotated.moves.len(), 3);
+    assert_eq!(annotated.moves[0].nags, vec![1]);
+    assert_eq!(annotated.moves[0].comments, vec!["A good move".to_owned()]);
+    assert_eq!(annotated.moves[2].nags, vec![5]);
+
+    let variation = &annotated.moves[0].variations[0];
+    assert_eq!(variation.len(), 3);
+    assert_eq!(variation[1].san, "Kd7");
+    assert_eq!(variation[1].variations[0][0].san, "Ke7");
+    assert_eq!(
+        annotated.final_board().unwrap().get_fen(),
+        "8/3k4/8/8/4P3/8/3K4/8 b - - 2 2"
+    );
+}
+
+#[test]
+fn test_write_round_trip() {
+    for game in PgnGame::parse_all(TWO_GAMES).unwrap() {
+        let pgn = game.to_pgn();
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+
+        let reparsed = PgnGame::parse(&pgn).unwrap();
+        assert_eq!(reparsed.moves, game.moves);
+        assert_eq!(reparsed.comments, game.comments);
+        assert_eq!(reparsed.result, game.result);
+        assert_eq!(reparsed.tag("FEN"), game.tag("FEN"));
+    }
+
+    let annotated = &PgnGame::parse_all(TWO_GAMES).unwrap()[1];
+    let pgn = annotated.to_pgn();
+    assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n"));
+    assert!(pgn.ends_with(
+        "{Starting comment} 1. e4 $1 {A good move} (1. e3 Kd7 (1... Ke7) 2. e4) 1... Kd7\n2. Kd2 $5 *\n"
+    ));
+
+    // a closing brace would end the comment early, so it is written as a bracket
+    let mut braces = PgnGame::parse("1. e4 *").unwrap();
+    braces.moves[0]
+        .comments
+        .push("a {set} of braces".to_owned());
+    let reparsed = PgnGame::parse(&braces.to_pgn()).unwrap();
+    assert_eq!(
+        reparsed.moves[0].comments,
+        vec!["a {set] of braces".to_owned()]
+    );
+}
+
+#[test]
+fn test_illegal_move() {
+    let err = PgnGame::parse("1. e4 e5 2. Ke3 *").unwrap_err();
+    assert_eq!(
+        err,
+        PgnParseErr::InvalidMove {
+            line: 1,
+            san: "Ke3".to_owned(),
+            err: SanParseErr::IllegalMove,
+        }
+    );
+    assert_eq!(
+        PgnGame::parse("1. e4 (1. d4 *").unwrap_err(),
+        PgnParseErr::UnbalancedVariation { line: 1 }
+    );
+}
+
+#[test]
+fn test_edited_move_is_illegal() {
+    let mut game = PgnGame::parse("1. e4 e5 2. Nf3 *").unwrap();
+    // the knight on g1 has already moved
+    game.moves.push(game.moves[2].clone());
+
+    let err = PgnParseErr::IllegalMove {
+        ply: 3,
+        san: "Nf3".to_owned(),
+        err: InvalidMoveErr::NotYourPiece,
+    };
+    assert_eq!(game.final_board(), Err(err.clone()));
+    assert_eq!(Game::from_pgn(&game), Err(err));
+}
@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use crate::{Board, BookEntry, BookSelection, Move, PgnGame, PolyglotBook};
+
+// From http://hgm.nubati.net/book_format.html
+const KEY_SEQUENCES: [(&str, u64); 9] = [
+    ("", 0x463b96181691fc9c),
+    ("e2e4", 0x823c9b50fd114196),
+    ("e2e4 d7d5", 0x0756b94461c50fb0),
+    ("e2e4 d7d5 e4e5", 0x662fafb965db29d4),
+    ("e2e4 d7d5 e4e5 f7f5", 0x22a48b5a8e47ff78),
+    ("e2e4 d7d5 e4e5 f7f5 e1e2", 0x652a607ca3f242c1),
+    ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", 0x00fdd303c946bdd9),
+    ("a2a4 b7b5 h2h4 b5b4 c2c4", 0x3c8123ea7b067637),
+    ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", 0x5c3f9b829b279560),
+];
+
+fn play(moves: &str) -> Board {
+    let mut board = Board::new();
+    for umove in moves.split_whitespace() {
+        board.try_move(Move::from_str(umove).unwrap()).unwrap();
+    }
+    board
+}
+
+#[test]
+fn test_polyglot_keys() {
+    for (moves, key) in KEY_SEQUENCES {
+        assert_eq!(
+            play(moves).polyglot_key(),
+            key,
+            "wrong key after \"{}\"",
+            moves
+        );
+    }
+}
+
+#[test]
+fn test_polyglot_move_encoding() {
+    let mut board = Board::empty();
+    board
+        .load_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1")
+        .unwrap();
+
+    // castles are stored as the king taking its own rook
+    let castle = board.resolve_move(Move::from_str("e1g1").unwrap()).unwrap();
+    assert!(castle.is_castle());
+    let raw = board.encode_polyglot_move(castle);
+    assert_eq!(raw, (7 | (4 << 6)) as u16);
+    assert_eq!(board.decode_polyglot_move(raw), Some(castle));
+
+    let promote = board
+        .resolve_move(Move::from_str("b7a8q").unwrap())
+        .unwrap();
+    let raw = board.encode_polyglot_move(promote);
+    assert_eq!(board.decode_polyglot_move(raw), Some(promote));
+
+    // e1e3 is not legal
+    assert_eq!(board.decode_polyglot_move(20 | (4 << 6)), None);
+}
+
+#[test]
+fn test_book_round_trip() {
+    let start_key = Board::new().polyglot_key();
+    let e4 = Board::new().encode_polyglot_move(Move::from_str("e2e4").unwrap());
+    let d4 = Board::new().encode_polyglot_move(Move::from_str("d2d4").unwrap());
+    let book = PolyglotBook::from_entries(vec![
+        BookEntry {
+            key: start_key,
+            raw_move: d4,
+            weight: 1,
+            learn: 0,
+        },
+        BookEntry {
+            key: 1,
+            raw_move: 0,
+            weight: 1,
+            learn: 0,
+        },
+        BookEntry {
+            key: start_key,
+            raw_move: e4,
+            weight: 10,
+            learn: 0,
+        },
+    ]);
+
+    let bytes = book.to_bytes();
+    assert_eq!(bytes.len(), 48);
+    assert_eq!(PolyglotBook::from_bytes(&bytes).unwrap(), book);
+    assert!(PolyglotBook::from_bytes(&bytes[1..]).is_err());
+
+    let board = Board::new();
+    assert_eq!(book.entries_for(&board).len(), 2);
+    assert_eq!(
+        book.pick_move(&board, BookSelection::BestWeight),
+        Some(Move::from_str("e2e4").unwrap())
+    );
+    assert!(book
+        .pick_move(&board, BookSelection::WeightedRandom)
+        .is_some());
+    assert_eq!(
+        book.pick_move(&play("e2e4"), BookSelection::BestWeight),
+        None
+    );
+}
+
+#[test]
+fn test_book_from_games() {
+    let games =
+        PgnGame::parse_all("1. e4 e5 1-0\n\n1. e4 c5 0-1\n\n1. d4 d5 1/2-1/2\n\n1. c4 c5 0-1\n")
+            .unwrap();
+    let book = PolyglotBook::from_games(&games, 1);
+
+    // losing moves score nothing, and only the first ply is used
+    let moves = book.moves_for(&Board::new());
+    assert_eq!(moves.len(), 2);
+    assert_eq!(moves[0], (Move::from_str("e2e4").unwrap(), 2));
+    assert_eq!(moves[1], (Move::from_str("d2d4").unwrap(), 1));
+    assert!(book.entries_for(&play("e2e4")).is_empty());
+}
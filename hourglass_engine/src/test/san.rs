@@ -0,0 +1,47 @@
+use std::str::FromStr;
+
+use crate::{Board, Move, SanParseErr};
+
+#[test]
+fn test_san_round_trip() {
+    let mut board = Board::new();
+    let sans = [
+        "e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O", "Bc5", "d3", "d6",
+    ];
+
+    for san in sans {
+        let umove = board.parse_san(san).unwrap();
+        assert_eq!(board.move_to_san(umove), san);
+        board.try_move(umove).unwrap();
+    }
+}
+
+#[test]
+fn test_san_disambiguation() {
+    let mut board = Board::empty();
+    board.load_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+
+    let umove = board.parse_san("Rad1").unwrap();
+    assert_eq!(umove, Move::from_str("a1d1").unwrap());
+    assert_eq!(board.move_to_san(umove), "Rad1");
+    assert_eq!(board.parse_san("Rd1"), Err(SanParseErr::AmbiguousMove));
+    assert_eq!(board.parse_san("Ra2"), Ok(Move::from_str("a1a2").unwrap()));
+
+    board.load_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(board.parse_san("Rd1"), Ok(Move::from_str("a1d1").unwrap()));
+    let castle = board.parse_san("0-0-0").unwrap();
+    assert_eq!(castle.to_string(), "e1c1");
+    assert!(castle.is_castle());
+}
+
+#[test]
+fn test_san_promotion_and_mate() {
+    let mut board = Board::empty();
+    board.load_fen("7k/4P3/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+
+    let umove = board.parse_san("e8=Q#").unwrap();
+    assert_eq!(umove, Move::from_str("e7e8q").unwrap());
+    assert_eq!(board.move_to_san(umove), "e8=Q#");
+    assert_eq!(board.parse_san("e8"), Err(SanParseErr::IllegalMove));
+    assert_eq!(board.parse_san("e8R"), Ok(Move::from_str("e7e8r").unwrap()));
+}
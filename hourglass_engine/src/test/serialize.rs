@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use serde_json::json;
+
+use crate::{
+    Board, CastleRights, Game, GameResult, GameStatus, Move, Piece, Player, Square, Variant,
+};
+
+#[test]
+fn test_serialize_values() {
+    let umove = Move::from_str("e7e8q").unwrap();
+    assert_eq!(serde_json::to_value(umove).unwrap(), json!("e7e8q"));
+    assert_eq!(
+        serde_json::from_value::<Move>(json!("e7e8q")).unwrap(),
+        umove
+    );
+    let drop = Move::new_drop(Piece::Knight, Square::F3);
+    assert_eq!(serde_json::to_value(drop).unwrap(), json!("N@f3"));
+    assert!(serde_json::from_value::<Move>(json!("e9e8")).is_err());
+
+    assert_eq!(serde_json::to_value(Square::E4).unwrap(), json!("e4"));
+    assert_eq!(
+        serde_json::to_value(Piece::Knight | Piece::White).unwrap(),
+        json!("N")
+    );
+    assert_eq!(
+        serde_json::from_value::<Piece>(json!("q")).unwrap(),
+        Piece::Queen | Piece::Black
+    );
+    assert!(serde_json::from_value::<Piece>(json!("x")).is_err());
+    assert_eq!(serde_json::to_value(Player::Black).unwrap(), json!("Black"));
+
+    let rights = CastleRights::WhiteKingSide | CastleRights::BlackQueenSide;
+    assert_eq!(serde_json::to_value(rights).unwrap(), json!("Kq"));
+    assert_eq!(
+        serde_json::from_value::<CastleRights>(json!("Kq")).unwrap(),
+        rights
+    );
+    assert_eq!(
+        serde_json::from_value::<CastleRights>(json!("-")).unwrap(),
+        CastleRights::empty()
+    );
+
+    assert_eq!(
+        serde_json::to_value(GameResult::Draw).unwrap(),
+        json!("1/2-1/2")
+    );
+    assert_eq!(
+        serde_json::to_value(GameStatus::Checkmate(Player::White)).unwrap(),
+        json!({ "Checkmate": "White" })
+    );
+    assert_eq!(
+        serde_json::from_value::<Variant>(json!("King of the Hill")).unwrap(),
+        Variant::KingOfTheHill
+    );
+}
+
+#[test]
+fn test_serialize_board() {
+    let board = Board::new();
+    let value = serde_json::to_value(board).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "variant": "chess",
+            "chess960": false,
+        })
+    );
+    assert_eq!(serde_json::from_value::<Board>(value).unwrap(), board);
+
+    // only the fen is needed
+    let fen = "4k3/8/8/8/8/8/8/4K2R w K - 0 1";
+    let loaded: Board = serde_json::from_value(json!({ "fen": fen })).unwrap();
+    assert_eq!(loaded.get_fen(), fen);
+    assert!(serde_json::from_value::<Board>(json!({ "fen": "8/8 w - - 0 1" })).is_err());
+
+    for variant in [Variant::Crazyhouse, Variant::ThreeCheck, Variant::Horde] {
+        let board = Board::new_variant(variant);
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+    }
+
+    let mut chess960 = Board::empty();
+    chess960.set_chess960(true);
+    chess960
+        .load_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+        .unwrap();
+    let json = serde_json::to_string(&chess960).unwrap();
+    assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), chess960);
+}
+
+#[test]
+fn test_serialize_game() {
+    let mut game = Game::new();
+    for san in ["e4", "e5", "Nf3", "Nc6", "Bb5"] {
+        game.play_san(san).unwrap();
+    }
+    game.undo();
+    game.undo();
+
+    let value = serde_json::to_value(&game).unwrap();
+    assert_eq!(value["moves"], json!(["e2e4", "e7e5", "g1f3"]));
+    assert_eq!(value["undone"], json!(["b8c6", "f1b5"]));
+
+    let loaded: Game = serde_json::from_value(value).unwrap();
+    assert_eq!(loaded, game);
+    assert_eq!(loaded.moves()[2].san, "Nf3");
+
+    let illegal =
+        json!({ "start": serde_json::to_value(Board::new()).unwrap(), "moves": ["e2e5"] });
+    assert!(serde_json::from_value::<Game>(illegal).is_err());
+
+    let played = serde_json::to_value(&game.moves()[0]).unwrap();
+    assert_eq!(
+        played,
+        json!({ "umove": "e2e4", "san": "e4", "captured": null })
+    );
+}
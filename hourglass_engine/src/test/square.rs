@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use crate::{Board, Piece, Square, SquareParseErr, SquareSet};
+
+#[test]
+fn test_square_names() {
+    let square_names = ["a1", "b1", "c3", "h8"];
+    let square_idxs = [0, 1, 18, 63];
+
+    for (square_name, square_idx) in square_names.iter().zip(square_idxs) {
+        let square = Square::new(square_idx).unwrap();
+        assert_eq!(*square_name, square.to_string());
+        assert_eq!(Ok(square), Square::from_str(square_name));
+    }
+
+    assert_eq!(Square::from_str("e"), Err(SquareParseErr::InvalidLength));
+    assert_eq!(Square::from_str("e44"), Err(SquareParseErr::InvalidLength));
+    assert_eq!(Square::from_str("i4"), Err(SquareParseErr::InvalidFile));
+    assert_eq!(Square::from_str("e9"), Err(SquareParseErr::InvalidRank));
+}
+
+#[test]
+fn test_square_coordinates() {
+    assert_eq!(Square::E4.file(), 4);
+    assert_eq!(Square::E4.rank(), 3);
+    assert_eq!(Square::from_file_rank(4, 3), Some(Square::E4));
+    assert_eq!(Square::from_file_rank(8, 0), None);
+    assert_eq!(Square::new(64), None);
+    assert_eq!(usize::from(Square::H8), 63);
+    assert_eq!(Square::all().count(), 64);
+    assert_eq!(Square::all().last(), Some(Square::H8));
+
+    let board = Board::new();
+    assert_eq!(board.piece_at(0, 4), Some(Piece::King | Piece::White));
+    assert_eq!(board.piece_at(7, 7), Some(Piece::Rook | Piece::Black));
+    // out of range, even though rank * 8 + file is on the board
+    assert_eq!(board.piece_at(0, 10), None);
+    assert_eq!(board.piece_at(8, 0), None);
+}
+
+#[test]
+fn test_square_set() {
+    let mut set = SquareSet::EMPTY;
+    assert!(set.is_empty());
+    set.insert(Square::H8);
+    set.insert(Square::A1);
+    set.insert(Square::E4);
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(Square::E4));
+    assert_eq!(set.first(), Some(Square::A1));
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        vec![Square::A1, Square::E4, Square::H8]
+    );
+
+    set.remove(Square::E4);
+    assert!(!set.contains(Square::E4));
+    assert_eq!(
+        set & SquareSet::from(Square::A1),
+        SquareSet::from(Square::A1)
+    );
+    assert_eq!((set | !set), SquareSet::FULL);
+    assert_eq!(format!("{:?}", set), "{a1, h8}");
+}
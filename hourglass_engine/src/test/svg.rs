@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use super::load;
+use crate::{Board, Move, Square, SvgOptions, Variant};
+
+/// Set to regenerate the golden files after changing how boards are drawn.
+const BLESS: bool = false;
+
+fn check_golden(svg: &str, golden: &str, name: &str) {
+    if BLESS {
+        let path = format!("{}/src/test/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::write(path, svg).unwrap();
+        return;
+    }
+    assert_eq!(svg, golden, "{} differs from the golden file", name);
+}
+
+#[test]
+fn test_svg_start_position() {
+    let svg = Board::new().to_svg(&SvgOptions::default());
+    check_golden(&svg, include_str!("golden/start.svg"), "start.svg");
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(r##"fill="#3399E5""##));
+    assert!(svg.contains(r##"fill="#E5F2FF""##));
+    // each piece is defined once, and used on each of its squares
+    assert_eq!(svg.matches(r#"id="white-pawn""#).count(), 1);
+    assert_eq!(svg.matches("#white-pawn").count(), 8);
+    assert_eq!(svg.matches("<use ").count(), 32);
+}
+
+#[test]
+fn test_svg_annotations() {
+    let board = load(
+        Variant::Standard,
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    );
+    let options = SvgOptions {
+        flipped: true,
+        last_move: Some(Move::from_str("g8f6").unwrap()),
+        arrows: vec![(Square::H5, Square::F7)],
+        circles: vec![Square::F7],
+        ..Default::default()
+    };
+    let svg = board.to_svg(&options);
+    check_golden(&svg, include_str!("golden/annotated.svg"), "annotated.svg");
+
+    assert_eq!(svg.matches("fill-opacity").count(), 2);
+    assert_eq!(svg.matches("<polygon ").count(), 1);
+    assert_eq!(svg.matches(r#"stroke-opacity="0.8""#).count(), 1);
+}
+
+#[test]
+fn test_svg_flipped() {
+    let board = load(Variant::Standard, "8/8/8/8/8/8/8/K6k w - - 0 1");
+    let options = SvgOptions {
+        coordinates: false,
+        ..Default::default()
+    };
+    let svg = board.to_svg(&options);
+    assert!(svg.contains(r#"viewBox="0 0 360 360""#));
+    assert!(svg.contains(r##"<use xlink:href="#white-king" transform="translate(0, 315)"/>"##));
+    assert!(!svg.contains("<text"));
+
+    let flipped = board.to_svg(&SvgOptions {
+        flipped: true,
+        ..options
+    });
+    assert!(flipped.contains(r##"<use xlink:href="#white-king" transform="translate(315, 0)"/>"##));
+    assert!(flipped.contains(r##"<use xlink:href="#black-king" transform="translate(0, 0)"/>"##));
+}
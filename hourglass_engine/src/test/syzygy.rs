@@ -0,0 +1,247 @@
+//! Checks the Syzygy tables in `test/syzygy` against a retrograde analysis of every
+//!     three piece position.
+
+use super::tablebase;
+use crate::{Board, Piece, Player, Wdl};
+
+/// The tables, with the piece white has besides its king.
+const TABLES: [(&str, Piece); 5] = [
+    ("KQvK", Piece::Queen),
+    ("KRvK", Piece::Rook),
+    ("KBvK", Piece::Bishop),
+    ("KNvK", Piece::Knight),
+    ("KPvK", Piece::Pawn),
+];
+
+/// White to move or not, the white king, the black king, and white's other piece.
+const POSITIONS: usize = 2 * 64 * 64 * 64;
+
+/// A position that a move leads to, while solving a table.
+#[derive(Clone, Copy, Debug)]
+enum Successor {
+    /// A position in the same table, reached by a move that doesn't reset the fifty move counter.
+    Internal(u32),
+    /// A position in the same table, reached by a pawn move.
+    Zeroing(u32),
+    /// A position with different material, reached by a capture or promotion.
+    ///     Holds the outcome for the side to move in that position.
+    Resolved(Wdl),
+}
+
+/// The outcome of every legal position in a table, by its index in `POSITIONS`.
+struct Solution {
+    wdl: Vec<Option<Wdl>>,
+    /// The plies until a capture, pawn move or mate, with the winning side hurrying
+    ///     and the losing side delaying. Checkmated positions are 0.
+    dtz: Vec<u8>,
+}
+
+impl Solution {
+    /// Gets the distance to zeroing the way the tablebase gives it.
+    fn signed_dtz(&self, idx: usize) -> i32 {
+        let dtz = self.dtz[idx] as i32;
+        match self.wdl[idx].unwrap() {
+            Wdl::Win => dtz,
+            Wdl::Loss => -dtz.max(1),
+            _ => 0,
+        }
+    }
+}
+
+/// Sets up a position from its index, or returns `None` if it is not legal.
+fn position_board(idx: usize, piece: Piece) -> Option<Board> {
+    let (white_to_move, white_king) = (idx / (64 * 64 * 64), idx / (64 * 64) % 64);
+    let (black_king, square) = (idx / 64 % 64, idx % 64);
+    if white_king == black_king || square == white_king || square == black_king {
+        return None;
+    }
+    if piece == Piece::Pawn && !(8..56).contains(&square) {
+        return None;
+    }
+
+    let mut board = Board::empty();
+    board.squares[white_king] = Piece::King | Piece::White;
+    board.squares[black_king] = Piece::King | Piece::Black;
+    board.squares[square] = piece | Piece::White;
+    board.active_color = match white_to_move {
+        1 => Player::White,
+        _ => Player::Black,
+    };
+
+    // this also rules out the kings being next to each other
+    if board.is_in_check(!board.active_color) {
+        return None;
+    }
+    Some(board)
+}
+
+/// Gets the index of a position, or `None` if it doesn't have the same material.
+fn position_index(board: &Board, piece: Piece) -> Option<usize> {
+    let mut squares = [None; 3];
+    for (square, p) in board.squares.iter().enumerate() {
+        let slot = match *p {
+            p if p == Piece::King | Piece::White => 0,
+            p if p == Piece::King | Piece::Black => 1,
+            p if p == piece | Piece::White => 2,
+            p if p == Piece::empty() => continue,
+            _ => return None,
+        };
+        squares[slot] = Some(square);
+    }
+    let [Some(white_king), Some(black_king), Some(square)] = squares else {
+        return None;
+    };
+    let white_to_move = (board.active_color == Player::White) as usize;
+    Some(((white_to_move * 64 + white_king) * 64 + black_king) * 64 + square)
+}
+
+/// Solves the positions with two kings and a white piece. The positions after captures
+///     and promotions are looked up in the tablebase.
+fn solve(piece: Piece) -> Solution {
+    let mut solution = Solution {
+        wdl: vec![None; POSITIONS],
+        dtz: vec![0; POSITIONS],
+    };
+
+    // every legal position, with the range of its successors
+    let mut positions = Vec::new();
+    let mut successors = Vec::new();
+    for idx in 0..POSITIONS {
+        let Some(board) = position_board(idx, piece) else {
+            continue;
+        };
+
+        let start = successors.len();
+        for umove in board.generate_moves() {
+            let mut next = board;
+            next.make_move_unchecked(umove).unwrap();
+            let is_pawn_move = board.squares[umove.origin_idx()] & Piece::PieceType == Piece::Pawn;
+            successors.push(match position_index(&next, piece) {
+                Some(next) if is_pawn_move => Successor::Zeroing(next as u32),
+                Some(next) => Successor::Internal(next as u32),
+                // two kings have no table
+                None if board.is_capture(umove) => Successor::Resolved(Wdl::Draw),
+                None => Successor::Resolved(
+                    tablebase()
+                        .probe_wdl(&next)
+                        .expect("the tables for promotions should be there"),
+                ),
+            });
+        }
+
+        if start == successors.len() {
+            solution.wdl[idx] = match board.is_in_check(board.active_color) {
+                true => Some(Wdl::Loss),
+                false => Some(Wdl::Draw),
+            };
+        }
+        positions.push((idx, start..successors.len()));
+    }
+
+    // solve the outcomes, treating any position not won or lost as a draw
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (idx, range) in positions.iter() {
+            if solution.wdl[*idx].is_some() {
+                continue;
+            }
+
+            let mut outcomes = successors[range.clone()].iter().map(|s| match s {
+                Successor::Internal(next) | Successor::Zeroing(next) => {
+                    solution.wdl[*next as usize]
+                }
+                Successor::Resolved(wdl) => Some(*wdl),
+            });
+            if outcomes.clone().any(|wdl| wdl == Some(Wdl::Loss)) {
+                solution.wdl[*idx] = Some(Wdl::Win);
+                changed = true;
+            } else if outcomes.all(|wdl| wdl == Some(Wdl::Win)) {
+                solution.wdl[*idx] = Some(Wdl::Loss);
+                changed = true;
+            }
+        }
+    }
+    for (idx, _) in positions.iter() {
+        solution.wdl[*idx].get_or_insert(Wdl::Draw);
+    }
+
+    // solve the distances one ply at a time
+    const UNKNOWN: u8 = u8::MAX;
+    let mut unsolved = Vec::new();
+    for (idx, range) in positions.iter() {
+        if solution.wdl[*idx] != Some(Wdl::Draw) && !range.is_empty() {
+            solution.dtz[*idx] = UNKNOWN;
+            unsolved.push((*idx, range.clone()));
+        }
+    }
+
+    let mut plies = 1;
+    while !unsolved.is_empty() {
+        assert!(plies < 100, "three piece tables have no cursed wins");
+        unsolved.retain(|(idx, range)| {
+            let wdl = solution.wdl[*idx].unwrap();
+            let keeps = |next: u32| solution.wdl[next as usize] == Some(-wdl);
+
+            // the plies until zeroing through each move that keeps the outcome
+            let mut distances = successors[range.clone()].iter().filter_map(|s| match s {
+                Successor::Internal(next) if keeps(*next) => match solution.dtz[*next as usize] {
+                    UNKNOWN => Some(UNKNOWN),
+                    dtz => Some(dtz + 1),
+                },
+                Successor::Zeroing(next) if keeps(*next) => Some(1),
+                Successor::Resolved(next) if *next == -wdl => Some(1),
+                _ => None,
+            });
+
+            let solved = match wdl {
+                Wdl::Win => distances.any(|d| d <= plies),
+                _ => distances.all(|d| d <= plies),
+            };
+            if solved {
+                solution.dtz[*idx] = plies;
+            }
+            !solved
+        });
+        plies += 1;
+    }
+
+    solution
+}
+
+#[test]
+fn test_tables_match_retrograde_analysis() {
+    for (name, piece) in TABLES {
+        let solution = solve(piece);
+        let mut longest_win = 0;
+        for idx in (0..POSITIONS).filter(|idx| solution.wdl[*idx].is_some()) {
+            let board = position_board(idx, piece).unwrap();
+            assert_eq!(
+                tablebase().probe_wdl(&board),
+                solution.wdl[idx],
+                "wrong wdl for \"{}\"",
+                board.get_fen()
+            );
+
+            // tables that store the distance in moves give one ply more for odd distances
+            let expected = solution.signed_dtz(idx);
+            let dtz = tablebase().probe_dtz(&board).unwrap();
+            assert!(
+                dtz == expected || dtz == expected + expected.signum(),
+                "wrong dtz {} for \"{}\", expected {}",
+                dtz,
+                board.get_fen(),
+                expected
+            );
+            longest_win = longest_win.max(expected);
+        }
+
+        // the longest mates are 10 moves with a queen and 16 moves with a rook
+        match name {
+            "KQvK" => assert_eq!(longest_win, 19),
+            "KRvK" => assert_eq!(longest_win, 31),
+            "KBvK" | "KNvK" => assert_eq!(longest_win, 0),
+            _ => {}
+        }
+    }
+}
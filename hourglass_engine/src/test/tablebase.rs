@@ -0,0 +1,124 @@
+use super::{load, tablebase, SYZYGY_PATH};
+use crate::{Board, Tablebase, TablebaseErr, Variant, Wdl};
+
+#[test]
+fn test_probe_wdl() {
+    let positions = [
+        ("8/8/8/3k4/8/8/8/Q3K3 w - - 0 1", Some(Wdl::Win)),
+        ("8/8/8/4k3/8/8/8/R3K3 b - - 0 1", Some(Wdl::Loss)),
+        ("4k3/8/8/8/8/8/1q6/4K3 b - - 0 1", Some(Wdl::Win)),
+        // the queen hangs
+        ("8/8/8/8/8/8/1k6/Q3K3 b - - 0 1", Some(Wdl::Draw)),
+        ("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", Some(Wdl::Draw)),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Some(Wdl::Draw)),
+        // the king in front of the pawn wins, whoever is to move
+        ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Some(Wdl::Win)),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Some(Wdl::Loss)),
+        // the defending king reaches the corner of a rook pawn
+        ("k7/8/8/8/8/8/P7/K7 w - - 0 1", Some(Wdl::Draw)),
+        ("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1", None),
+        ("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", None),
+    ];
+
+    for (fen, wdl) in positions {
+        assert_eq!(
+            tablebase().probe_wdl(&load(Variant::Standard, fen)),
+            wdl,
+            "wrong wdl for \"{}\"",
+            fen
+        );
+    }
+}
+
+#[test]
+fn test_probe_dtz() {
+    // mate in one
+    assert_eq!(
+        tablebase().probe_dtz(&load(Variant::Standard, "k7/8/1K6/8/8/8/7Q/8 w - - 0 1")),
+        Some(1)
+    );
+    assert_eq!(
+        tablebase().probe_dtz(&load(Variant::Standard, "k7/7Q/1K6/8/8/8/8/8 b - - 0 1")),
+        Some(-2)
+    );
+    // checkmated
+    assert_eq!(
+        tablebase().probe_dtz(&load(Variant::Standard, "k6Q/8/1K6/8/8/8/8/8 b - - 0 1")),
+        Some(-1)
+    );
+    // black has the queen, so the table is read with the colors swapped
+    assert_eq!(
+        tablebase().probe_dtz(&load(Variant::Standard, "K7/8/1k6/8/8/8/7q/8 b - - 0 1")),
+        Some(1)
+    );
+    // promoting wins right away
+    assert_eq!(
+        tablebase().probe_dtz(&load(Variant::Standard, "8/4P3/8/8/8/8/k7/4K3 w - - 0 1")),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_open_tablebase() {
+    assert_eq!(tablebase().max_pieces(), 3);
+    assert_eq!(
+        Tablebase::new().probe_wdl(&load(Variant::Standard, "8/8/8/3k4/8/8/8/Q3K3 w - - 0 1")),
+        None
+    );
+
+    let empty = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test/golden");
+    assert!(matches!(
+        Tablebase::open(empty),
+        Err(TablebaseErr::NoTables(_))
+    ));
+    let missing = format!("{}/missing", SYZYGY_PATH);
+    assert!(matches!(Tablebase::open(missing), Err(TablebaseErr::Io(_))));
+}
+
+#[test]
+fn test_root_moves() {
+    // only the mate is kept
+    let moves = tablebase()
+        .root_moves(&load(Variant::Standard, "k7/8/1K6/8/8/8/7Q/8 w - - 0 1"))
+        .unwrap();
+    assert_eq!(
+        moves.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+        ["h2h8"]
+    );
+
+    // every move of the bare king loses, so the ones that last the longest are kept
+    let board = load(Variant::Standard, "8/8/8/3k4/8/8/8/Q3K3 b - - 0 1");
+    let moves = tablebase().root_moves(&board).unwrap();
+    let dtz = tablebase().probe_dtz(&board).unwrap();
+    for umove in moves.iter() {
+        let mut next = board;
+        next.try_move(*umove).unwrap();
+        assert_eq!(tablebase().probe_dtz(&next), Some(-dtz - 1));
+    }
+}
+
+#[test]
+fn test_tablebase_converts_win() {
+    for fen in [
+        "8/8/8/3k4/8/8/8/Q3K3 w - - 0 1",
+        "8/8/8/3k4/8/8/8/R3K3 w - - 0 1",
+        "8/8/8/8/8/8/k3PK2/8 w - - 0 1",
+    ] {
+        let mut board = load(Variant::Standard, fen);
+        for _ in 0..100 {
+            let umove =
+                board.get_best_move_with_tablebase(1, Board::score_material, Some(tablebase()));
+            let Some(umove) = umove else {
+                break;
+            };
+            board.try_move(umove).unwrap();
+        }
+
+        assert!(
+            board.generate_moves().is_empty() && board.is_in_check(board.active_color()),
+            "failed to mate from \"{}\", reached \"{}\"",
+            fen,
+            board.get_fen()
+        );
+    }
+}
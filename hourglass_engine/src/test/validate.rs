@@ -0,0 +1,93 @@
+use super::try_load;
+use crate::{Board, CastleRights, FenParseErr, Player, PositionErr, Square, Variant};
+
+#[test]
+fn test_validate_kings() {
+    assert_eq!(
+        try_load(Variant::Standard, "8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::MissingKing(
+            Player::Black
+        )))
+    );
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::TooManyKings(
+            Player::White
+        )))
+    );
+    assert_eq!(
+        Board::empty().validate(),
+        Err(PositionErr::MissingKing(Player::White))
+    );
+    assert_eq!(Board::new().validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_pieces() {
+    assert_eq!(
+        try_load(Variant::Standard, "4k2P/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::PawnOnBackRank(
+            Square::H8
+        )))
+    );
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/8/4K2R w Q - 0 1"),
+        Err(FenParseErr::InvalidPosition(
+            PositionErr::InvalidCastleRights(CastleRights::WhiteQueenSide)
+        ))
+    );
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/8/4K2R w - - 0 1").map(|b| b.castle_rights()),
+        Ok(CastleRights::empty())
+    );
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::OppositeCheck))
+    );
+}
+
+#[test]
+fn test_validate_en_passant() {
+    assert!(try_load(Variant::Standard, "4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").is_ok());
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::InvalidEnPassant(
+            Square::E3
+        )))
+    );
+    assert_eq!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/8/4K3 b - e3 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::InvalidEnPassant(
+            Square::E3
+        )))
+    );
+}
+
+#[test]
+fn test_malformed_board() {
+    assert!(matches!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidData { .. })
+    ));
+    assert!(matches!(
+        try_load(Variant::Standard, "4k3/9/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidData { .. })
+    ));
+    assert!(matches!(
+        try_load(Variant::Standard, "4k3/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(FenParseErr::InvalidData { .. })
+    ));
+}
+
+#[test]
+fn test_failed_load_keeps_board() {
+    let mut board = Board::new();
+    assert!(board.load_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert_eq!(board, Board::new());
+    assert!(board
+        .load_epd("4k3/4R3/8/8/8/8/8/4K3 w - - id \"x\";")
+        .is_err());
+    assert_eq!(board, Board::new());
+    assert!(board.load_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Qh8;").is_err());
+    assert_eq!(board, Board::new());
+}
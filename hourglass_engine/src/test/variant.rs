@@ -0,0 +1,439 @@
+use std::str::FromStr;
+
+use super::{gen_moves::check_gen_kinds, load};
+use crate::{
+    Board, CastleRights, CrazyhouseMoveList, FenParseErr, FenPart, Game, GameResult, GameStatus,
+    Move, MoveList, PgnGame, Piece, Player, PositionErr, Square, Variant,
+};
+
+// From the perft tests of shakmaty, with the Three-check counts converted to checks given
+const PERFT_POSITIONS: [(Variant, &str, u32, u64); 20] = [
+    (
+        Variant::ThreeCheck,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 +2+2",
+        2,
+        2039,
+    ),
+    (
+        Variant::ThreeCheck,
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 +2+2",
+        3,
+        13410,
+    ),
+    (
+        Variant::RacingKings,
+        "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+        3,
+        11264,
+    ),
+    (
+        Variant::RacingKings,
+        "4brn1/2K2k2/8/8/8/8/8/8 w - - 0 1",
+        4,
+        3151,
+    ),
+    (
+        Variant::Atomic,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        3,
+        8902,
+    ),
+    (
+        Variant::Atomic,
+        "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1",
+        2,
+        1238,
+    ),
+    (
+        Variant::Atomic,
+        "rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1",
+        3,
+        23353,
+    ),
+    (Variant::Atomic, "8/8/8/8/8/8/2k5/rR4KR w KQ - 0 1", 3, 4364),
+    (
+        Variant::Atomic,
+        "r3k1rR/5K2/8/8/8/8/8/8 b kq - 0 1",
+        3,
+        6753,
+    ),
+    (
+        Variant::Atomic,
+        "Rr2k1rR/3K4/3p4/8/8/8/7P/8 w kq - 0 1",
+        1,
+        21,
+    ),
+    (
+        Variant::Antichess,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+        3,
+        8067,
+    ),
+    (Variant::Antichess, "8/1p6/8/8/8/8/P7/8 w - - 0 1", 6, 0),
+    (Variant::Antichess, "8/2p5/8/8/8/8/P7/8 w - - 0 1", 10, 36),
+    (
+        Variant::Crazyhouse,
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+        1,
+        301,
+    ),
+    (
+        Variant::Crazyhouse,
+        "2k5/8/8/8/8/8/8/4K3[Qn] w - - 0 1",
+        3,
+        88634,
+    ),
+    (
+        Variant::Crazyhouse,
+        "r1bqk2r/pppp1ppp/2n1p3/4P3/1b1Pn3/2NB1N2/PPP2PPP/R1BQK2R[] b KQkq - 0 1",
+        2,
+        1347,
+    ),
+    (
+        Variant::Crazyhouse,
+        "4k3/1Q~6/8/8/4b3/8/Kpp5/8[] b - - 0 1",
+        3,
+        5445,
+    ),
+    (
+        Variant::Horde,
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1",
+        4,
+        23310,
+    ),
+    (
+        Variant::Horde,
+        "4k3/pp4q1/3P2p1/8/P3PP2/PPP2r2/PPP5/PPPP4 b - - 0 1",
+        3,
+        6633,
+    ),
+    (
+        Variant::Horde,
+        "k7/5p2/4p2P/3p2P1/2p2P2/1p2P2P/p2P2P1/2P2P2 w - - 0 1",
+        4,
+        33781,
+    ),
+];
+
+#[test]
+fn test_variant_perft() {
+    for (variant, fen, depth, nodes) in PERFT_POSITIONS {
+        // the atomic position with its king on g1 is a chess960 one. The mode only changes
+        //     how castles are written, not how many there are, so it is on for all of them.
+        let mut board = Board::empty();
+        board.set_variant(variant);
+        board.set_chess960(true);
+        board.load_fen(fen).unwrap();
+        assert_eq!(board.perft(depth), nodes, "perft failed for \"{}\"", fen);
+        assert_eq!(board.get_fen(), fen);
+    }
+}
+
+#[test]
+fn test_variant_gen_kinds() {
+    for (variant, fen, _, _) in PERFT_POSITIONS {
+        let mut board = Board::empty();
+        board.set_variant(variant);
+        board.set_chess960(true);
+        board.load_fen(fen).unwrap();
+        check_gen_kinds(&board);
+        let mut moves = CrazyhouseMoveList::default();
+        board.generate_moves_into(&mut moves);
+        for umove in moves {
+            let mut after = board;
+            after.make_move_unchecked(umove).unwrap();
+            check_gen_kinds(&after);
+        }
+    }
+}
+
+#[test]
+fn test_names() {
+    for variant in Variant::ALL {
+        assert_eq!(Variant::from_name(variant.pgn_name()), Some(variant));
+        assert_eq!(Variant::from_name(variant.uci_name()), Some(variant));
+    }
+    assert_eq!(Variant::from_name("threeCheck"), Some(Variant::ThreeCheck));
+    assert_eq!(Variant::from_name("shogi"), None);
+
+    assert_eq!(
+        Board::new_variant(Variant::RacingKings).perft(1),
+        21,
+        "racing kings should start from its own position"
+    );
+}
+
+#[test]
+fn test_king_of_the_hill() {
+    let mut board = load(Variant::KingOfTheHill, "4k3/8/8/8/8/3K4/8/8 w - - 0 1");
+    assert!(board.has_mating_material(Player::White));
+    board.try_move(Move::from_str("d3e4").unwrap()).unwrap();
+
+    assert!(board.is_variant_end());
+    assert_eq!(board.variant_result(), GameResult::WhiteWins);
+    assert!(board.generate_moves().is_empty());
+
+    // the same position is not over in standard chess
+    let mut standard = board;
+    standard.set_variant(Variant::Standard);
+    assert!(!standard.is_variant_end());
+    assert!(!standard.generate_moves().is_empty());
+}
+
+#[test]
+fn test_three_check() {
+    let mut board = Board::new_variant(Variant::ThreeCheck);
+    assert!(board.get_fen().ends_with(" 0 1 +0+0"));
+
+    // a fen without the check count has no checks given
+    let no_count = load(Variant::ThreeCheck, "4k3/8/8/8/8/8/8/4K2R w - - 0 1");
+    assert_eq!(no_count.checks_given(Player::White), 0);
+
+    for san in ["e4", "f6", "Qh5+"] {
+        let umove = board.parse_san(san).unwrap();
+        board.try_move(umove).unwrap();
+    }
+    assert_eq!(board.checks_given(Player::White), 1);
+    assert_eq!(board.checks_given(Player::Black), 0);
+
+    let mut game = Game::from_board(load(
+        Variant::ThreeCheck,
+        "4k3/8/8/8/8/8/8/4K2R w - - 0 1 +2+1",
+    ));
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    game.play_san("Rh8+").unwrap();
+    assert_eq!(game.board().checks_given(Player::White), 3);
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::White));
+
+    let mut board = Board::empty();
+    assert_eq!(
+        board.load_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1 +0+0"),
+        Err(FenParseErr::TooManyComponents),
+        "only three-check has a check count"
+    );
+    board.set_variant(Variant::ThreeCheck);
+    assert!(matches!(
+        board.load_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1 +4+0"),
+        Err(FenParseErr::InvalidData {
+            part: FenPart::CheckCount,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_racing_kings() {
+    // the rook can't move to the 7th rank or the h-file, as that would give check
+    let board = load(Variant::RacingKings, "8/7k/8/8/8/8/1R6/K7 w - - 0 1");
+    let rook_moves = board
+        .generate_moves()
+        .into_iter()
+        .filter(|m| m.from() == Square::B2)
+        .collect::<Vec<_>>();
+    assert!(!rook_moves.is_empty());
+    assert!(rook_moves
+        .iter()
+        .all(|m| m.to().rank() != 6 && m.to().file() != 7));
+
+    // black can still catch up after white reaches the goal
+    let mut board = load(Variant::RacingKings, "8/K5k1/8/8/8/8/8/8 w - - 0 1");
+    board.try_move(Move::from_str("a7a8").unwrap()).unwrap();
+    assert_eq!(board.variant_result(), GameResult::Ongoing);
+    board.try_move(Move::from_str("g7g8").unwrap()).unwrap();
+    assert_eq!(board.variant_result(), GameResult::Draw);
+
+    // but not when black is too far away
+    let mut board = load(Variant::RacingKings, "8/K7/6k1/8/8/8/8/8 w - - 0 1");
+    board.try_move(Move::from_str("a7a8").unwrap()).unwrap();
+    assert_eq!(board.variant_result(), GameResult::WhiteWins);
+    assert!(board.generate_moves().is_empty());
+
+    let mut board = Board::empty();
+    board.set_variant(Variant::RacingKings);
+    assert!(matches!(
+        board.load_fen("8/8/8/8/8/8/k1P5/7K w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(
+            PositionErr::InvalidForVariant(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_variant_pgn() {
+    let mut game = Game::from_board(Board::new_variant(Variant::KingOfTheHill));
+    for san in ["e4", "d5", "Ke2", "dxe4", "Ke3", "Kd7", "Kxe4"] {
+        game.play_san(san).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::White));
+
+    let pgn = game.to_pgn_game();
+    assert_eq!(pgn.tag("Variant"), Some("King of the Hill"));
+    assert_eq!(pgn.tag("FEN"), None);
+    assert_eq!(pgn.result, GameResult::WhiteWins);
+
+    let parsed = PgnGame::parse(&game.to_pgn()).unwrap();
+    let replayed = Game::from_pgn(&parsed).unwrap();
+    assert_eq!(replayed.board().variant(), Variant::KingOfTheHill);
+    assert_eq!(replayed.status(), GameStatus::VariantWin(Player::White));
+}
+
+#[test]
+fn test_atomic() {
+    // the rook explodes along with the knight and the rook next to it
+    let mut board = load(Variant::Atomic, "r3k2r/7n/8/8/8/8/8/4K2R w Kkq - 0 1");
+    board.try_move(Move::from_str("h1h7").unwrap()).unwrap();
+    assert_eq!(board.piece_on(Square::H7), Piece::empty());
+    assert_eq!(board.piece_on(Square::H8), Piece::empty());
+    assert_eq!(board.castle_rights(), CastleRights::BlackQueenSide);
+
+    // you can't explode your own king
+    let mut board = load(Variant::Atomic, "4k3/8/8/8/8/8/R2n4/4K3 w - - 0 1");
+    assert!(board.try_move(Move::from_str("a2d2").unwrap()).is_err());
+
+    // kings can't capture
+    let mut board = load(Variant::Atomic, "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1");
+    assert!(board.is_in_check(Player::White));
+    assert!(board.try_move(Move::from_str("e1d2").unwrap()).is_err());
+
+    // a king next to the other king can't be captured, so it is not in check
+    let mut board = load(Variant::Atomic, "8/8/8/8/8/8/3qk3/4K3 w - - 0 1");
+    assert!(!board.is_in_check(Player::White));
+    board.try_move(Move::from_str("e1f1").unwrap()).unwrap();
+
+    let mut game = Game::from_board(load(Variant::Atomic, "4k3/4p3/8/8/8/8/8/4RK2 w - - 0 1"));
+    game.play_san("Rxe7").unwrap();
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::White));
+    assert_eq!(game.moves()[0].san, "Rxe7");
+}
+
+#[test]
+fn test_antichess() {
+    let mut board = Board::new_variant(Variant::Antichess);
+    for umove in ["e2e4", "d7d5"] {
+        board.try_move(Move::from_str(umove).unwrap()).unwrap();
+    }
+    // the capture is compulsory
+    assert_eq!(
+        board.generate_moves().to_vec(),
+        vec![board.resolve_move(Move::from_str("e4d5").unwrap()).unwrap()]
+    );
+    let mut knight_moves = MoveList::new();
+    board.get_moves_for(&mut knight_moves, Square::G1);
+    assert!(knight_moves.is_empty());
+    assert!(board.try_move(Move::from_str("g1f3").unwrap()).is_err());
+
+    // the king can be captured, and pawns can promote to kings
+    let mut board = load(Variant::Antichess, "8/P7/8/8/8/8/8/k6K w - - 0 1");
+    board.try_move(Move::from_str("a7a8k").unwrap()).unwrap();
+    assert_eq!(board.piece_on(Square::A8), Piece::King | Piece::White);
+
+    let mut game = Game::from_board(load(Variant::Antichess, "8/8/8/8/8/8/1p6/K7 w - - 0 1"));
+    game.play_san("Kxb2").unwrap();
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::Black));
+
+    // a player with no legal moves wins
+    let game = Game::from_board(load(Variant::Antichess, "8/8/8/8/8/p7/P7/8 w - - 0 1"));
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::White));
+}
+
+#[test]
+fn test_crazyhouse() {
+    let mut board = Board::new_variant(Variant::Crazyhouse);
+    for san in ["e4", "d5", "exd5", "Qxd5"] {
+        let umove = board.parse_san(san).unwrap();
+        board.try_move(umove).unwrap();
+    }
+    assert_eq!(board.pocket_count(Player::White, Piece::Pawn), 1);
+    assert_eq!(board.pocket_count(Player::Black, Piece::Pawn), 1);
+    assert_eq!(
+        board.get_fen(),
+        "rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR[Pp] w KQkq - 0 3"
+    );
+
+    let drop = board.parse_san("@e4").unwrap();
+    assert_eq!(drop, Move::new_drop(Piece::Pawn, Square::E4));
+    assert_eq!(board.move_to_san(drop), "@e4");
+    assert_eq!(drop.to_string(), "P@e4");
+    board.try_move(drop).unwrap();
+    assert_eq!(board.pocket_count(Player::White, Piece::Pawn), 0);
+    assert!(board.try_move(Move::from_str("N@f3").unwrap()).is_err());
+
+    // a promoted piece goes back to being a pawn when it is captured,
+    //     and the pocket may be written as a 9th rank
+    let mut board = load(Variant::Crazyhouse, "4k3/1Q~6/8/8/4b3/8/Kpp5/8/ b - - 0 1");
+    assert!(board.is_promoted(Square::B7));
+    assert_eq!(board.get_fen(), "4k3/1Q~6/8/8/4b3/8/Kpp5/8[] b - - 0 1");
+    board.try_move(Move::from_str("e4b7").unwrap()).unwrap();
+    assert_eq!(board.pocket_count(Player::Black, Piece::Pawn), 1);
+    assert_eq!(board.pocket_count(Player::Black, Piece::Queen), 0);
+
+    // pieces can't be dropped on a square that doesn't block a check
+    let board = load(Variant::Crazyhouse, "4k3/8/8/8/8/8/8/K3R3[n] b - - 0 1");
+    let drops = board
+        .generate_moves()
+        .into_iter()
+        .filter(|m| m.is_drop())
+        .collect::<Vec<_>>();
+    assert_eq!(drops.len(), 6);
+    assert!(drops.iter().all(|m| m.to().file() == 4));
+    let mut blocked = board;
+    assert!(blocked.try_move(Move::from_str("N@d7").unwrap()).is_err());
+    blocked.try_move(Move::from_str("N@e7").unwrap()).unwrap();
+
+    let mut board = Board::empty();
+    assert!(board.load_fen("4k3/8/8/8/8/8/8/4K3[Q] w - - 0 1").is_err());
+    board.set_variant(Variant::Crazyhouse);
+    assert!(board.load_fen("4k3/8/8/8/8/8/8/4K3[K] w - - 0 1").is_err());
+}
+
+#[test]
+fn test_horde() {
+    let mut board = Board::new_variant(Variant::Horde);
+    assert_eq!(board.king_square(Player::White), None);
+    assert!(!board.is_in_check(Player::White));
+
+    // pawns on the back rank can move 2, but can't be taken en passant
+    board.try_move(Move::from_str("b5b6").unwrap()).unwrap();
+    board.try_move(Move::from_str("a7b6").unwrap()).unwrap();
+    let mut board = load(Variant::Horde, "4k3/8/8/8/8/1p6/8/P7 w - - 0 1");
+    board.try_move(Move::from_str("a1a3").unwrap()).unwrap();
+    assert_eq!(board.en_passant(), None);
+
+    // the horde can't be checkmated, but it can be stalemated
+    let game = Game::from_board(load(Variant::Horde, "4k3/8/8/8/8/p7/P7/8 w - - 0 1"));
+    assert_eq!(game.status(), GameStatus::Stalemate);
+
+    let mut game = Game::from_board(load(Variant::Horde, "4k3/8/8/8/8/8/3P4/8 b - - 0 1"));
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    for san in ["Kd7", "d4", "Kd6", "d5", "Kxd5"] {
+        game.play_san(san).unwrap();
+    }
+    assert_eq!(game.status(), GameStatus::VariantWin(Player::Black));
+
+    let mut board = Board::empty();
+    assert_eq!(
+        board.load_fen("4k3/8/8/8/8/8/8/P7 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::MissingKing(
+            Player::White
+        )))
+    );
+    board.set_variant(Variant::Horde);
+    assert!(matches!(
+        board.load_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(
+            PositionErr::InvalidForVariant(_)
+        ))
+    ));
+    assert_eq!(
+        board.load_fen("4k3/8/8/8/8/8/8/P7 w K - 0 1"),
+        Err(FenParseErr::InvalidPosition(
+            PositionErr::InvalidCastleRights(CastleRights::WhiteKingSide)
+        ))
+    );
+    assert_eq!(
+        board.load_fen("p3k3/8/8/8/8/8/8/P7 w - - 0 1"),
+        Err(FenParseErr::InvalidPosition(PositionErr::PawnOnBackRank(
+            Square::A8
+        )))
+    );
+}
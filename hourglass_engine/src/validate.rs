@@ -0,0 +1,199 @@
+use std::fmt::Display;
+
+use crate::{back_rank, Board, CastleRights, Piece, Player, Square, Variant};
+
+/// A reason that a position cannot be played from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PositionErr {
+    MissingKing(Player),
+    TooManyKings(Player),
+    /// There is a pawn on the first or last rank, at the given square.
+    PawnOnBackRank(Square),
+    /// The en passant square could not have been created by a double pawn push.
+    InvalidEnPassant(Square),
+    /// The castle right does not have its king and rook on their starting squares.
+    InvalidCastleRights(CastleRights),
+    /// The side that is not to move is in check.
+    OppositeCheck,
+    /// The position breaks a rule of the board's [`Variant`].
+    InvalidForVariant(&'static str),
+}
+
+impl Display for PositionErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionErr::MissingKing(player) => write!(f, "{:?} has no king", player),
+            PositionErr::TooManyKings(player) => write!(f, "{:?} has more than one king", player),
+            PositionErr::PawnOnBackRank(square) => {
+                write!(f, "there is a pawn on the back rank at {}", square)
+            }
+            PositionErr::InvalidEnPassant(square) => {
+                write!(f, "{} is not a possible en passant square", square)
+            }
+            PositionErr::InvalidCastleRights(right) => {
+                write!(f, "the castle right {} has no king or rook", right.to_fen())
+            }
+            PositionErr::OppositeCheck => write!(f, "the side not to move is in check"),
+            PositionErr::InvalidForVariant(err_msg) => {
+                write!(f, "invalid position for the variant: {}", err_msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionErr {}
+
+impl Board {
+    /// Checks that the position is one that can be played from without issue.
+    pub fn validate(&self) -> Result<(), PositionErr> {
+        match self.variant {
+            // in antichess the king is an ordinary piece, so there can be any number of them
+            Variant::Antichess => {}
+            Variant::Horde => self.validate_horde_kings()?,
+            _ => self.validate_kings()?,
+        }
+
+        for idx in (0..8).chain(56..64) {
+            let piece = self.squares[idx];
+            // the horde's pawns may start on its own back rank
+            let horde_back_rank = self.variant == Variant::Horde
+                && [Player::White, Player::Black].into_iter().any(|player| {
+                    piece.is_color(player)
+                        && self.king_square(player).is_none()
+                        && idx / 8 == back_rank(player)
+                });
+            if piece & Piece::PieceType == Piece::Pawn && !horde_back_rank {
+                return Err(PositionErr::PawnOnBackRank(Square::from_idx(idx)));
+            }
+        }
+
+        if let Some(en_passant) = self.en_passant() {
+            self.validate_en_passant(en_passant)?;
+        }
+
+        if self.variant == Variant::Antichess && !self.castle_rights.is_empty() {
+            return Err(PositionErr::InvalidForVariant(
+                "castling is not allowed in antichess",
+            ));
+        }
+
+        for right in CastleRights::EACH {
+            if self.castle_rights.has_right(right) && !self.castle_right_is_valid(right) {
+                return Err(PositionErr::InvalidCastleRights(right));
+            }
+        }
+
+        if self.is_in_check(!self.active_color) {
+            return Err(PositionErr::OppositeCheck);
+        }
+
+        self.validate_variant()
+    }
+
+    fn validate_variant(&self) -> Result<(), PositionErr> {
+        if self.variant != Variant::RacingKings {
+            return Ok(());
+        }
+
+        if self
+            .squares
+            .iter()
+            .any(|p| *p & Piece::PieceType == Piece::Pawn)
+        {
+            return Err(PositionErr::InvalidForVariant(
+                "racing kings is played without pawns",
+            ));
+        }
+        if self.is_in_check(self.active_color) {
+            return Err(PositionErr::InvalidForVariant(
+                "checks are not allowed in racing kings",
+            ));
+        }
+        let in_goal = |player: Player| self.find_king(player).rank() == 7;
+        if self.active_color == Player::Black && in_goal(Player::White) && in_goal(Player::Black) {
+            return Err(PositionErr::InvalidForVariant(
+                "black can't reach the goal on white's move",
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_kings(&self) -> Result<(), PositionErr> {
+        for player in [Player::White, Player::Black] {
+            let king = Piece::King | player.to_piece_color();
+            match self.squares.iter().filter(|p| **p == king).count() {
+                0 => return Err(PositionErr::MissingKing(player)),
+                1 => {}
+                _ => return Err(PositionErr::TooManyKings(player)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that exactly one player has a king, and that it is the only one.
+    fn validate_horde_kings(&self) -> Result<(), PositionErr> {
+        let count = |player: Player| {
+            let king = Piece::King | player.to_piece_color();
+            self.squares.iter().filter(|p| **p == king).count()
+        };
+        match (count(Player::White), count(Player::Black)) {
+            (0, 1) | (1, 0) => Ok(()),
+            (0, 0) => Err(PositionErr::MissingKing(Player::Black)),
+            (white, _) if white > 1 => Err(PositionErr::TooManyKings(Player::White)),
+            (_, black) if black > 1 => Err(PositionErr::TooManyKings(Player::Black)),
+            _ => Err(PositionErr::InvalidForVariant(
+                "only one side has a king in horde",
+            )),
+        }
+    }
+
+    /// Checks that the king and rook of a castle right are on the back rank,
+    ///     with the rook on the correct side of the king.
+    fn castle_right_is_valid(&self, right: CastleRights) -> bool {
+        let player = right.player();
+        let Some(king_square) = self.king_square(player) else {
+            return false;
+        };
+        let rook_square = self.castle_rook_square(right);
+
+        let king_file = king_square.file();
+        let rook_file = rook_square.file();
+        let king_on_back_rank = king_square.rank() == back_rank(player);
+        let rook_on_correct_side = (rook_file > king_file) == right.is_king_side();
+        let standard_king = self.chess960 || king_file == 4;
+
+        king_on_back_rank
+            && standard_king
+            && rook_file != king_file
+            && rook_on_correct_side
+            && self.piece_on(rook_square) == Piece::Rook | player.to_piece_color()
+    }
+
+    fn validate_en_passant(&self, square: Square) -> Result<(), PositionErr> {
+        let err = Err(PositionErr::InvalidEnPassant(square));
+
+        // the player that double pushed is the one that is not to move
+        let pusher = !self.active_color;
+        let expected_rank = match pusher {
+            Player::White => 2,
+            Player::Black => 5,
+        };
+        if square.rank() != expected_rank {
+            return err;
+        }
+
+        let en_passant = square.idx();
+        let forward = pusher.forward_value() * 8;
+        let pawn_square = (en_passant as isize + forward) as usize;
+        let start_square = (en_passant as isize - forward) as usize;
+
+        if self.squares[en_passant] != Piece::empty()
+            || self.squares[start_square] != Piece::empty()
+            || self.squares[pawn_square] != Piece::Pawn | pusher.to_piece_color()
+        {
+            return err;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt::Display;
+
+use crate::{
+    square_distance, squares_to_edge, Board, CastleRights, Direction, GameResult, Move, Piece,
+    Player,
+};
+
+/// The squares that a king wins on in King of the Hill: d4, e4, d5 and e5.
+const HILL: [usize; 4] = [27, 28, 35, 36];
+
+/// The number of checks that wins a game of Three-check.
+pub(crate) const THREE_CHECK_LIMIT: u8 = 3;
+
+/// A set of rules that the game is played with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// A player also wins by moving their king to one of the four center squares.
+    KingOfTheHill,
+    /// A player also wins by checking the other player three times.
+    ThreeCheck,
+    /// Giving check is not allowed, and the first king to reach the 8th rank wins.
+    /// If black can reach it right after white, the game is a draw.
+    RacingKings,
+    /// Captures explode, removing the capturing piece and every piece other than pawns
+    ///     around it. Exploding the other king wins.
+    Atomic,
+    /// Also called Giveaway. Captures are compulsory and the king is an ordinary piece.
+    /// A player wins by losing all of their pieces, or by having no legal moves.
+    Antichess,
+    /// Captured pieces go to the capturer's pocket, and can be dropped back onto the board
+    ///     as their own piece instead of moving.
+    Crazyhouse,
+    /// White has a horde of 36 pawns and no king, and wins by checkmating black.
+    /// Black wins by capturing every piece of the horde.
+    Horde,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 8] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::RacingKings,
+        Variant::Atomic,
+        Variant::Antichess,
+        Variant::Crazyhouse,
+        Variant::Horde,
+    ];
+
+    /// The name of the variant used in the `Variant` tag of a PGN.
+    pub fn pgn_name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "Standard",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::ThreeCheck => "Three-check",
+            Variant::RacingKings => "Racing Kings",
+            Variant::Atomic => "Atomic",
+            Variant::Antichess => "Antichess",
+            Variant::Crazyhouse => "Crazyhouse",
+            Variant::Horde => "Horde",
+        }
+    }
+
+    /// The name of the variant used by the `UCI_Variant` option.
+    pub fn uci_name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "chess",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::ThreeCheck => "3check",
+            Variant::RacingKings => "racingkings",
+            Variant::Atomic => "atomic",
+            Variant::Antichess => "antichess",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Horde => "horde",
+        }
+    }
+
+    /// Gets a variant from its PGN or UCI name, ignoring case, spaces and dashes.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let simplified = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .collect::<String>()
+            .to_lowercase();
+        match simplified.as_str() {
+            "standard" | "chess" => Some(Variant::Standard),
+            "kingofthehill" | "koth" => Some(Variant::KingOfTheHill),
+            "threecheck" | "3check" => Some(Variant::ThreeCheck),
+            "racingkings" => Some(Variant::RacingKings),
+            "atomic" => Some(Variant::Atomic),
+            "antichess" | "giveaway" => Some(Variant::Antichess),
+            "crazyhouse" => Some(Variant::Crazyhouse),
+            "horde" => Some(Variant::Horde),
+            _ => None,
+        }
+    }
+
+    /// The fen of the starting position.
+    pub fn starting_fen(&self) -> &'static str {
+        match self {
+            Variant::Standard | Variant::KingOfTheHill | Variant::Atomic => {
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+            }
+            Variant::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0",
+            Variant::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Variant::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pgn_name())
+    }
+}
+
+impl Board {
+    /// Creates the starting position of a variant.
+    pub fn new_variant(variant: Variant) -> Self {
+        let mut board = Board::empty();
+        board.set_variant(variant);
+        board
+            .load_fen(variant.starting_fen())
+            .expect("a variant's starting position should be valid");
+        board
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Sets the rules that the position is played with.
+    ///
+    /// Like [`Board::set_chess960`], this should be done before loading a fen,
+    ///     as the fen may have fields that only some variants use.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// The number of times a player has given check, for Three-check.
+    pub fn checks_given(&self, player: Player) -> u8 {
+        self.checks[player.index()]
+    }
+
+    /// Checks if the game was ended by a rule of the variant,
+    ///     as opposed to checkmate, stalemate or a draw.
+    ///
+    /// There are no legal moves in a position where the game has ended.
+    pub fn is_variant_end(&self) -> bool {
+        self.variant_end_result() != GameResult::Ongoing
+    }
+
+    /// Gets the result of a game that was ended by a rule of the variant,
+    ///     or [`GameResult::Ongoing`] if no such rule applies.
+    ///
+    /// This includes having no legal moves in antichess, which wins.
+    pub fn variant_result(&self) -> GameResult {
+        let result = self.variant_end_result();
+        if self.variant == Variant::Antichess
+            && result == GameResult::Ongoing
+            && self.generate_moves().is_empty()
+        {
+            return GameResult::win_for(self.active_color);
+        }
+        result
+    }
+
+    /// Gets the result of the rules that end the game without looking at the legal moves.
+    fn variant_end_result(&self) -> GameResult {
+        match self.variant {
+            Variant::Standard | Variant::Crazyhouse => GameResult::Ongoing,
+            Variant::KingOfTheHill => {
+                for player in [Player::White, Player::Black] {
+                    let king = Piece::King | player.to_piece_color();
+                    if HILL.iter().any(|idx| self.squares[*idx] == king) {
+                        return GameResult::win_for(player);
+                    }
+                }
+                GameResult::Ongoing
+            }
+            Variant::ThreeCheck => {
+                for player in [Player::White, Player::Black] {
+                    if self.checks_given(player) >= THREE_CHECK_LIMIT {
+                        return GameResult::win_for(player);
+                    }
+                }
+                GameResult::Ongoing
+            }
+            Variant::RacingKings => self.racing_kings_result(),
+            Variant::Atomic => {
+                for player in [Player::White, Player::Black] {
+                    if self.king_square(player).is_none() {
+                        return GameResult::win_for(!player);
+                    }
+                }
+                GameResult::Ongoing
+            }
+            Variant::Antichess => {
+                for player in [Player::White, Player::Black] {
+                    if !self.squares.iter().any(|p| p.is_color(player)) {
+                        return GameResult::win_for(player);
+                    }
+                }
+                GameResult::Ongoing
+            }
+            Variant::Horde => {
+                for player in [Player::White, Player::Black] {
+                    if !self.squares.iter().any(|p| p.is_color(player)) {
+                        return GameResult::win_for(!player);
+                    }
+                }
+                GameResult::Ongoing
+            }
+        }
+    }
+
+    fn racing_kings_result(&self) -> GameResult {
+        let in_goal = |player: Player| self.find_king(player).rank() == 7;
+        match (in_goal(Player::White), in_goal(Player::Black)) {
+            (true, true) => GameResult::Draw,
+            (false, true) => GameResult::BlackWins,
+            (true, false) if self.active_color == Player::White => GameResult::WhiteWins,
+            // black gets one more move to catch up
+            (true, false) if self.can_king_reach_goal(Player::Black) => GameResult::Ongoing,
+            (true, false) => GameResult::WhiteWins,
+            (false, false) => GameResult::Ongoing,
+        }
+    }
+
+    /// Checks if the player's king can safely move onto the 8th rank with its next move.
+    fn can_king_reach_goal(&self, player: Player) -> bool {
+        let king = self.find_king(player).idx();
+        if king / 8 != 6 {
+            return false;
+        }
+
+        let file = king % 8;
+        (file.saturating_sub(1)..=(file + 1).min(7))
+            .map(|file| 56 + file)
+            .filter(|target| !self.squares[*target].is_color(player))
+            .any(|target| {
+                let mut board = *self;
+                board.squares[target] = board.squares[king];
+                board.squares[king] = Piece::empty();
+                !board.is_in_check(player)
+            })
+    }
+
+    /// Updates the variant's state after a move was made, given the position before the move.
+    pub(crate) fn update_variant_state(&mut self, before: &Board, umove: Move) {
+        let mover = before.active_color;
+        match self.variant {
+            Variant::ThreeCheck if self.is_in_check(!mover) => {
+                self.checks[mover.index()] += 1;
+            }
+            Variant::Atomic if before.is_capture(umove) => self.explode(umove.target_idx()),
+            Variant::Crazyhouse => self.update_pockets(before, umove),
+            _ => {}
+        }
+    }
+
+    /// Removes the piece that captured on the square, and every piece around it but pawns.
+    fn explode(&mut self, center: usize) {
+        self.remove_exploded(center);
+        for dir in Direction::ALL {
+            if squares_to_edge(center, dir) >= 1 {
+                let target = (center as isize + dir.offset()) as usize;
+                if self.squares[target] & Piece::PieceType != Piece::Pawn {
+                    self.remove_exploded(target);
+                }
+            }
+        }
+    }
+
+    fn remove_exploded(&mut self, idx: usize) {
+        let piece = self.squares[idx];
+        if piece == Piece::empty() {
+            return;
+        }
+
+        for right in CastleRights::EACH {
+            if self.castle_rook_square(right).idx() == idx {
+                self.castle_rights.revoke(right);
+            }
+        }
+        if piece & Piece::PieceType == Piece::King {
+            let player = match piece.is_color(Player::White) {
+                true => Player::White,
+                false => Player::Black,
+            };
+            self.castle_rights.revoke_all(player);
+        }
+        self.squares[idx] = Piece::empty();
+    }
+
+    /// Checks if a square that the king passes over while castling is attacked, in atomic chess.
+    ///
+    /// Squares next to the other king are safe, and the king does not block attacks.
+    pub(crate) fn is_atomic_king_path_attacked(&self, idx: usize, king_square: usize) -> bool {
+        let player = self.active_color;
+        match self.king_square(!player) {
+            Some(other_king) if square_distance(idx, other_king.idx()) > 1 => {}
+            _ => return false,
+        }
+
+        let mut board = *self;
+        board.squares[king_square] = Piece::empty();
+        board.generate_attacks(!player)[idx]
+    }
+
+    /// Sets the number of checks each player has given, for Three-check.
+    pub(crate) fn set_checks_given(&mut self, white: u8, black: u8) {
+        self.checks = [white, black];
+    }
+}
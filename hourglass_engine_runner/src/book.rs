@@ -0,0 +1,44 @@
+use hourglass_engine::{PgnGame, PolyglotBook};
+
+/// How many plies of each game go into the book when not specified.
+const DEFAULT_MAX_PLY: usize = 16;
+
+/// Builds a Polyglot opening book from a PGN collection.
+///
+/// Arguments: `<games.pgn> <book.bin> [max ply]`
+pub fn run(args: &[String]) {
+    if let Err(e) = make_book(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn make_book(args: &[String]) -> Result<(), String> {
+    let [pgn_path, book_path, rest @ ..] = args else {
+        return Err(
+            "usage: hourglass_engine_runner book <games.pgn> <book.bin> [max ply]".to_owned(),
+        );
+    };
+    let max_ply = match rest.first() {
+        Some(ply) => ply
+            .parse()
+            .map_err(|_| format!("invalid max ply \"{}\"", ply))?,
+        None => DEFAULT_MAX_PLY,
+    };
+
+    let pgn = std::fs::read_to_string(pgn_path)
+        .map_err(|e| format!("failed to read \"{}\": {}", pgn_path, e))?;
+    let games = PgnGame::parse_all(&pgn).map_err(|e| format!("invalid pgn: {}", e))?;
+
+    let book = PolyglotBook::from_games(&games, max_ply);
+    book.save(book_path)
+        .map_err(|e| format!("failed to write \"{}\": {}", book_path, e))?;
+
+    println!(
+        "wrote {} entries from {} games to \"{}\"",
+        book.len(),
+        games.len(),
+        book_path
+    );
+    Ok(())
+}
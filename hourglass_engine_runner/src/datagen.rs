@@ -0,0 +1,372 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+use hourglass_engine::{
+    Board, GameResult, Move, PackedPosition, PackedWriter, Player, PolyglotBook, SearchLimits,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::opening::play_opening;
+
+const USAGE: &str = "usage: hourglass_engine_runner datagen <positions.bin> [--games N] \
+[--depth N] [--nodes N] [--random-plies N] [--book <book.bin>] [--threads N] [--seed N] \
+[--max-plies N] [--win-score CP] [--win-plies N] [--draw-score CP] [--draw-plies N] \
+[--draw-after N]";
+
+/// The settings for generating games, from the command line.
+struct DatagenOptions {
+    out_path: String,
+    games: usize,
+    depth: u32,
+    /// Searches each move until it has used this many nodes, up to `depth`.
+    nodes: Option<u64>,
+    /// The number of random moves played after the book, if any.
+    random_plies: usize,
+    book: Option<PolyglotBook>,
+    threads: usize,
+    seed: u64,
+    /// Games that reach this many plies are drawn.
+    max_plies: usize,
+    /// A game is won when the score stays at least this many centipawns
+    ///     for one player for `win_plies` plies in a row.
+    win_score: i32,
+    win_plies: usize,
+    /// A game is drawn when the score stays within this many centipawns of 0
+    ///     for `draw_plies` plies in a row, after `draw_after` plies.
+    draw_score: i32,
+    draw_plies: usize,
+    draw_after: usize,
+}
+
+impl DatagenOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let [out_path, rest @ ..] = args else {
+            return Err(USAGE.to_owned());
+        };
+        let mut options = DatagenOptions {
+            out_path: out_path.clone(),
+            games: 100,
+            depth: 3,
+            nodes: None,
+            random_plies: 8,
+            book: None,
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+            seed: rand::random(),
+            max_plies: 400,
+            win_score: 1000,
+            win_plies: 4,
+            draw_score: 10,
+            draw_plies: 12,
+            draw_after: 60,
+        };
+
+        let mut rest = rest.iter();
+        while let Some(name) = rest.next() {
+            let value = rest
+                .next()
+                .ok_or_else(|| format!("missing value for {}", name))?;
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid value \"{}\" for {}", value, name))
+            };
+            match name.as_str() {
+                "--games" => options.games = number()? as usize,
+                "--depth" => options.depth = number()? as u32,
+                "--nodes" => options.nodes = Some(number()?),
+                "--random-plies" => options.random_plies = number()? as usize,
+                "--book" => {
+                    let book = PolyglotBook::open(value)
+                        .map_err(|e| format!("failed to read \"{}\": {}", value, e))?;
+                    options.book = Some(book);
+                }
+                "--threads" => options.threads = (number()? as usize).max(1),
+                "--seed" => options.seed = number()?,
+                "--max-plies" => options.max_plies = number()? as usize,
+                "--win-score" => options.win_score = number()? as i32,
+                "--win-plies" => options.win_plies = number()? as usize,
+                "--draw-score" => options.draw_score = number()? as i32,
+                "--draw-plies" => options.draw_plies = number()? as usize,
+                "--draw-after" => options.draw_after = number()? as usize,
+                _ => return Err(format!("unknown option \"{}\"\n{}", name, USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Plays the engine against itself from random openings, writing the quiet positions
+///     of each game with their scores and the game's result as packed positions.
+///
+/// Arguments: `<positions.bin> [options]`, see [`USAGE`].
+pub fn run(args: &[String]) {
+    if let Err(e) = generate(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn generate(args: &[String]) -> Result<(), String> {
+    let options = DatagenOptions::parse(args)?;
+    let file = File::create(&options.out_path)
+        .map_err(|e| format!("failed to create \"{}\": {}", options.out_path, e))?;
+    let mut writer = PackedWriter::new(BufWriter::new(file));
+    let write_err = |e| format!("failed to write \"{}\": {}", options.out_path, e);
+
+    eprintln!(
+        "playing {} games on {} threads with seed {}",
+        options.games, options.threads, options.seed
+    );
+
+    let next_game = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut positions = 0;
+    let mut results = [0; 3];
+    std::thread::scope(|scope| {
+        for _ in 0..options.threads {
+            let sender = sender.clone();
+            let (options, next_game) = (&options, &next_game);
+            scope.spawn(move || loop {
+                let game_idx = next_game.fetch_add(1, Ordering::Relaxed);
+                if game_idx >= options.games {
+                    break;
+                }
+                let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(game_idx as u64));
+                if sender.send(play_game(options, &mut rng)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        for (played, (game_positions, result)) in receiver.iter().enumerate() {
+            for position in &game_positions {
+                writer.write(position).map_err(write_err)?;
+            }
+            positions += game_positions.len();
+            results[match result {
+                GameResult::WhiteWins => 0,
+                GameResult::BlackWins => 1,
+                _ => 2,
+            }] += 1;
+            if (played + 1) % 10 == 0 || played + 1 == options.games {
+                eprintln!(
+                    "{}/{} games, {} positions, +{} -{} ={}",
+                    played + 1,
+                    options.games,
+                    positions,
+                    results[0],
+                    results[1],
+                    results[2]
+                );
+            }
+        }
+        Ok::<_, String>(())
+    })?;
+    writer.flush().map_err(write_err)?;
+
+    println!(
+        "wrote {} positions from {} games to \"{}\"",
+        positions, options.games, options.out_path
+    );
+    Ok(())
+}
+
+/// Plays a game, returning its quiet positions and its result.
+fn play_game(options: &DatagenOptions, rng: &mut StdRng) -> (Vec<PackedPosition>, GameResult) {
+    let mut game = play_opening(options.book.as_ref(), options.random_plies, rng);
+    let mut positions = vec![];
+    let mut adjudication = Adjudication::default();
+
+    let result = loop {
+        let status = game.status();
+        if status.is_over() {
+            break status.result();
+        }
+        if game.moves().len() >= options.max_plies {
+            break GameResult::Draw;
+        }
+
+        let board = *game.board();
+        let limits = SearchLimits {
+            depth: options.depth,
+            nodes: options.nodes,
+            time: None,
+        };
+        let info = board.search_limited(&limits, Board::score_endgames, None);
+        let Some(best_move) = info.best_move else {
+            break status.result();
+        };
+        let centipawns = white_centipawns(&board, info.score);
+        if is_quiet(&board, best_move, info.score) {
+            positions.push(PackedPosition {
+                board,
+                score: centipawns,
+                result: GameResult::Ongoing,
+            });
+        }
+
+        let plies = game.moves().len();
+        if let Some(result) = adjudication.update(options, plies, centipawns as i32) {
+            break result;
+        }
+
+        game.play(best_move).expect("the best move should be legal");
+    };
+
+    for position in positions.iter_mut() {
+        position.result = result;
+    }
+    (positions, result)
+}
+
+/// Converts a score for the player to move into centipawns for white, as the positions
+///     are stored.
+fn white_centipawns(board: &Board, score: f32) -> i16 {
+    let score = match board.active_color() {
+        Player::White => score,
+        Player::Black => -score,
+    };
+    (score * 100.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Whether a position is kept. A position is quiet when the player to move is not in
+///     check and the best move is not a capture or promotion, so its score doesn't hinge
+///     on an exchange. Mate scores are left out too.
+fn is_quiet(board: &Board, best_move: Move, score: f32) -> bool {
+    !board.is_in_check(board.active_color())
+        && !best_move.is_capture()
+        && !best_move.is_promotion()
+        && score.is_finite()
+}
+
+/// The scores in a row that end a game before it is over.
+#[derive(Clone, Copy, Debug, Default)]
+struct Adjudication {
+    /// The plies in a row that white and black have been winning.
+    win_streak: [usize; 2],
+    /// The plies in a row that the score has been close to 0.
+    draw_streak: usize,
+}
+
+impl Adjudication {
+    /// Adds the score for white after the given number of plies, and returns the result
+    ///     once the game is decided.
+    fn update(
+        &mut self,
+        options: &DatagenOptions,
+        plies: usize,
+        centipawns: i32,
+    ) -> Option<GameResult> {
+        self.win_streak = match centipawns {
+            cp if cp >= options.win_score => [self.win_streak[0] + 1, 0],
+            cp if cp <= -options.win_score => [0, self.win_streak[1] + 1],
+            _ => [0, 0],
+        };
+        match self.win_streak.iter().position(|s| *s >= options.win_plies) {
+            Some(0) => return Some(GameResult::WhiteWins),
+            Some(_) => return Some(GameResult::BlackWins),
+            None => {}
+        }
+
+        self.draw_streak =
+            match plies >= options.draw_after && centipawns.abs() <= options.draw_score {
+                true => self.draw_streak + 1,
+                false => 0,
+            };
+        (self.draw_streak >= options.draw_plies).then_some(GameResult::Draw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hourglass_engine::{Board, GameResult};
+
+    use super::{is_quiet, white_centipawns, Adjudication, DatagenOptions};
+
+    fn board(fen: &str) -> Board {
+        let mut board = Board::empty();
+        board.load_fen(fen).unwrap();
+        board
+    }
+
+    fn options() -> DatagenOptions {
+        let args = ["positions.bin", "--win-score", "500", "--win-plies", "3"];
+        let args = args.map(str::to_owned);
+        DatagenOptions::parse(&args).unwrap()
+    }
+
+    #[test]
+    fn test_white_centipawns() {
+        let white = board("4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+        let black = board("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+        assert_eq!(white_centipawns(&white, 9.0), 900);
+        assert_eq!(white_centipawns(&black, -9.0), 900);
+        assert_eq!(white_centipawns(&black, 1.5), -150);
+        assert_eq!(white_centipawns(&white, f32::INFINITY), i16::MAX);
+        assert_eq!(white_centipawns(&black, f32::INFINITY), i16::MIN);
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        let quiet = |fen: &str, umove: &str, score: f32| {
+            let board = board(fen);
+            let moves = board.generate_moves();
+            let umove = moves.iter().find(|m| m.to_string() == umove).unwrap();
+            is_quiet(&board, *umove, score)
+        };
+        assert!(quiet("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e1d2", 0.5));
+        assert!(!quiet("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", 0.5));
+        assert!(!quiet("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", 9.0));
+        assert!(!quiet("4k3/8/8/8/8/8/8/r3K3 w - - 0 1", "e1e2", 0.0));
+        // mate scores
+        assert!(!quiet(
+            "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1",
+            "e1d2",
+            f32::NEG_INFINITY
+        ));
+    }
+
+    #[test]
+    fn test_adjudicate_win() {
+        let options = options();
+        let mut adjudication = Adjudication::default();
+        // a streak that is broken starts over
+        for cp in [600, 500, 499, 500, 700] {
+            assert_eq!(adjudication.update(&options, 10, cp), None);
+        }
+        assert_eq!(
+            adjudication.update(&options, 10, 800),
+            Some(GameResult::WhiteWins)
+        );
+
+        let mut adjudication = Adjudication::default();
+        for cp in [-500, -900] {
+            assert_eq!(adjudication.update(&options, 10, cp), None);
+        }
+        assert_eq!(
+            adjudication.update(&options, 10, -600),
+            Some(GameResult::BlackWins)
+        );
+    }
+
+    #[test]
+    fn test_adjudicate_draw() {
+        let options = options();
+        let mut adjudication = Adjudication::default();
+        // the default is 12 plies within 10 centipawns, after 60 plies
+        for plies in 50..71 {
+            assert_eq!(adjudication.update(&options, plies, 5), None);
+        }
+        assert_eq!(
+            adjudication.update(&options, 71, -10),
+            Some(GameResult::Draw)
+        );
+    }
+}
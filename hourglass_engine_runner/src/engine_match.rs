@@ -0,0 +1,450 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+use hourglass_engine::{
+    Board, Clock, Game, GameResult, Player, PolyglotBook, SearchLimits, Tablebase, TimeControl,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::opening::play_opening;
+
+const USAGE: &str = "usage: hourglass_engine_runner match --engine1 <config> --engine2 <config> \
+[--games N] [--tc <time control>] [--random-plies N] [--book <book.bin>] [--threads N] \
+[--seed N] [--max-plies N] [--elo0 N] [--elo1 N] [--alpha N] [--beta N] [--syzygy <directory>]
+an engine config is a list of settings, ex. depth=4,nodes=20000,eval=material,tablebase=true";
+
+/// A configuration of the engine to play in a match, ex. `depth=4,eval=material`.
+#[derive(Clone, Debug)]
+struct EngineConfig {
+    /// The config as it was written, to name the engine in the output.
+    name: String,
+    /// The deepest depth to search, which is also the limit under a time control.
+    depth: u32,
+    nodes: Option<u64>,
+    scoring: fn(&Board) -> f32,
+    tablebase: bool,
+}
+
+impl FromStr for EngineConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = EngineConfig {
+            name: s.to_owned(),
+            depth: 3,
+            nodes: None,
+            scoring: Board::score_endgames,
+            tablebase: false,
+        };
+        for setting in s.split(',').filter(|setting| !setting.is_empty()) {
+            let invalid = || format!("invalid engine setting \"{}\"", setting);
+            let (name, value) = setting.split_once('=').ok_or_else(invalid)?;
+            match name {
+                "depth" => config.depth = value.parse().map_err(|_| invalid())?,
+                "nodes" => config.nodes = Some(value.parse().map_err(|_| invalid())?),
+                "eval" => {
+                    config.scoring = match value {
+                        "material" => Board::score_material,
+                        "endgames" => Board::score_endgames,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "tablebase" => config.tablebase = value.parse().map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The settings for a match, from the command line.
+struct MatchOptions {
+    engines: [EngineConfig; 2],
+    /// Rounded up to a whole number of pairs.
+    games: usize,
+    /// Without a time control, each engine searches to its own depth.
+    time_control: Option<TimeControl>,
+    random_plies: usize,
+    book: Option<PolyglotBook>,
+    /// The Syzygy tables for the engines with `tablebase=true`.
+    tablebase: Option<Tablebase>,
+    threads: usize,
+    seed: u64,
+    /// Games that reach this many plies are drawn.
+    max_plies: usize,
+    /// The SPRT tests whether engine 1 is `elo1` stronger than engine 2 (H1),
+    ///     against it being `elo0` stronger (H0).
+    elo0: f64,
+    elo1: f64,
+    /// The chances of accepting H1 when H0 is true, and H0 when H1 is true.
+    alpha: f64,
+    beta: f64,
+}
+
+impl MatchOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let default_engine = EngineConfig::from_str("")?;
+        let mut options = MatchOptions {
+            engines: [default_engine.clone(), default_engine],
+            games: 100,
+            time_control: None,
+            random_plies: 8,
+            book: None,
+            tablebase: None,
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+            seed: rand::random(),
+            max_plies: 400,
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        };
+
+        let mut args = args.iter();
+        while let Some(name) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for {}\n{}", name, USAGE))?;
+            let invalid = || format!("invalid value \"{}\" for {}", value, name);
+            let integer = || value.parse::<u64>().map_err(|_| invalid());
+            let float = || value.parse::<f64>().map_err(|_| invalid());
+            match name.as_str() {
+                "--engine1" => options.engines[0] = value.parse()?,
+                "--engine2" => options.engines[1] = value.parse()?,
+                "--games" => options.games = integer()? as usize,
+                "--tc" => {
+                    let control = value
+                        .parse()
+                        .map_err(|e| format!("invalid time control: {}", e))?;
+                    options.time_control = Some(control);
+                }
+                "--random-plies" => options.random_plies = integer()? as usize,
+                "--book" => {
+                    let book = PolyglotBook::open(value)
+                        .map_err(|e| format!("failed to read \"{}\": {}", value, e))?;
+                    options.book = Some(book);
+                }
+                "--syzygy" => {
+                    let tablebase = Tablebase::open(value)
+                        .map_err(|e| format!("failed to open \"{}\": {}", value, e))?;
+                    options.tablebase = Some(tablebase);
+                }
+                "--threads" => options.threads = (integer()? as usize).max(1),
+                "--seed" => options.seed = integer()?,
+                "--max-plies" => options.max_plies = integer()? as usize,
+                "--elo0" => options.elo0 = float()?,
+                "--elo1" => options.elo1 = float()?,
+                "--alpha" => options.alpha = float()?,
+                "--beta" => options.beta = float()?,
+                _ => return Err(format!("unknown option \"{}\"\n{}", name, USAGE)),
+            }
+        }
+
+        let uses_tablebase = options.engines.iter().any(|engine| engine.tablebase);
+        if uses_tablebase && options.tablebase.is_none() {
+            return Err(format!("tablebase=true needs --syzygy\n{}", USAGE));
+        }
+        Ok(options)
+    }
+}
+
+/// The games won, drawn and lost by engine 1.
+#[derive(Clone, Copy, Debug, Default)]
+struct MatchScore {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl MatchScore {
+    fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    fn add(&mut self, result: GameResult, engine1: Player) {
+        match result {
+            GameResult::Draw | GameResult::Ongoing => self.draws += 1,
+            result if result == GameResult::win_for(engine1) => self.wins += 1,
+            _ => self.losses += 1,
+        }
+    }
+
+    /// The average points per game and their variance, counting a draw as half a point.
+    fn mean_and_variance(&self) -> Option<(f64, f64)> {
+        let games = self.games() as f64;
+        if games == 0.0 {
+            return None;
+        }
+        let mean = (self.wins as f64 + self.draws as f64 / 2.0) / games;
+        let variance = (self.wins as f64 * (1.0 - mean).powi(2)
+            + self.draws as f64 * (0.5 - mean).powi(2)
+            + self.losses as f64 * mean.powi(2))
+            / games;
+        Some((mean, variance))
+    }
+
+    /// The Elo difference, along with the bounds of its 95% confidence interval.
+    fn elo(&self) -> Option<(f64, f64, f64)> {
+        let (mean, variance) = self.mean_and_variance()?;
+        let deviation = (variance / self.games() as f64).sqrt();
+        Some((
+            score_to_elo(mean),
+            score_to_elo(mean - 1.96 * deviation),
+            score_to_elo(mean + 1.96 * deviation),
+        ))
+    }
+
+    /// The log-likelihood ratio of H1 against H0, using the normal approximation
+    ///     of the generalized SPRT.
+    fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        let Some((mean, variance)) = self.mean_and_variance() else {
+            return 0.0;
+        };
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (elo_to_score(elo0), elo_to_score(elo1));
+        self.games() as f64 * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+}
+
+/// The log-likelihood ratios below which H0 is accepted and above which H1 is accepted,
+///     for the given rates of false positives and false negatives.
+fn sprt_bounds(alpha: f64, beta: f64) -> (f64, f64) {
+    ((beta / (1.0 - alpha)).ln(), ((1.0 - beta) / alpha).ln())
+}
+
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(0.0, 1.0);
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+/// Plays two engine configurations against each other, reporting the score, the Elo
+///     difference and an SPRT after each game. Each opening is played twice, with each
+///     engine taking white once. The match stops early once the SPRT is decided.
+///
+/// Arguments: see [`USAGE`].
+pub fn run(args: &[String]) {
+    if let Err(e) = play_match(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn play_match(args: &[String]) -> Result<(), String> {
+    let options = MatchOptions::parse(args)?;
+    let bounds = sprt_bounds(options.alpha, options.beta);
+    let pairs = options.games.div_ceil(2);
+
+    println!("engine 1: {}", options.engines[0].name);
+    println!("engine 2: {}", options.engines[1].name);
+    match &options.time_control {
+        Some(control) => println!("time control: {}", control),
+        None => println!("time control: fixed depth"),
+    }
+    println!(
+        "playing {} games on {} threads with seed {}, SPRT elo0 {} elo1 {}",
+        pairs * 2,
+        options.threads,
+        options.seed,
+        options.elo0,
+        options.elo1
+    );
+
+    let next_pair = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    let mut score = MatchScore::default();
+    let mut decision = None;
+    std::thread::scope(|scope| {
+        for _ in 0..options.threads {
+            let sender = sender.clone();
+            let (options, next_pair, stop) = (&options, &next_pair, &stop);
+            scope.spawn(move || loop {
+                let pair_idx = next_pair.fetch_add(1, Ordering::Relaxed);
+                if pair_idx >= pairs || stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let mut rng = StdRng::seed_from_u64(options.seed.wrapping_add(pair_idx as u64));
+                let opening = play_opening(options.book.as_ref(), options.random_plies, &mut rng);
+                for engine1 in [Player::White, Player::Black] {
+                    let result = play_game(options, &opening, engine1, options.tablebase.as_ref());
+                    if sender.send((result, engine1)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (result, engine1) in receiver.iter() {
+            score.add(result, engine1);
+            let llr = score.llr(options.elo0, options.elo1);
+            let elo = match score.elo() {
+                Some((elo, lower, upper)) => {
+                    format!("{:.1} [{:.1}, {:.1}]", elo, lower, upper)
+                }
+                None => "-".to_owned(),
+            };
+            println!(
+                "games {}: +{} -{} ={}, elo {}, llr {:.2} ({:.2}, {:.2})",
+                score.games(),
+                score.wins,
+                score.losses,
+                score.draws,
+                elo,
+                llr,
+                bounds.0,
+                bounds.1
+            );
+
+            // the games being played are still finished, so the pairs are complete
+            if decision.is_none() && (llr <= bounds.0 || llr >= bounds.1) {
+                decision = Some(llr >= bounds.1);
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+
+    match decision {
+        Some(true) => println!(
+            "H1 accepted: engine 1 is stronger by about {} elo or more",
+            options.elo1
+        ),
+        Some(false) => println!(
+            "H0 accepted: engine 1 is not stronger by more than about {} elo",
+            options.elo0
+        ),
+        None => println!("the SPRT is undecided"),
+    }
+    Ok(())
+}
+
+/// Plays a game from an opening, with engine 1 playing the given color.
+fn play_game(
+    options: &MatchOptions,
+    opening: &Game,
+    engine1: Player,
+    tablebase: Option<&Tablebase>,
+) -> GameResult {
+    let mut game = opening.clone();
+    let mut clock = options.time_control.clone().map(Clock::new);
+    if let Some(clock) = &mut clock {
+        clock.start(game.board().active_color());
+    }
+
+    loop {
+        let status = match &clock {
+            Some(clock) => game.status_with_clock(clock),
+            None => game.status(),
+        };
+        if status.is_over() {
+            return status.result();
+        }
+        if game.moves().len() >= options.max_plies {
+            return GameResult::Draw;
+        }
+
+        let board = *game.board();
+        let player = board.active_color();
+        let engine = match player == engine1 {
+            true => &options.engines[0],
+            false => &options.engines[1],
+        };
+        let limits = SearchLimits {
+            depth: engine.depth,
+            nodes: engine.nodes,
+            // a share of the time that is left, as the number of moves to go isn't known
+            time: clock.as_ref().map(|clock| clock.remaining(player) / 20),
+        };
+        let tablebase = tablebase.filter(|_| engine.tablebase);
+        let info = board.search_limited(&limits, engine.scoring, tablebase);
+        let Some(best_move) = info.best_move else {
+            return status.result();
+        };
+        game.play(best_move).expect("the best move should be legal");
+        if let Some(clock) = &mut clock {
+            clock.press();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sprt_bounds, MatchScore};
+
+    fn score(wins: u32, draws: u32, losses: u32) -> MatchScore {
+        MatchScore {
+            wins,
+            draws,
+            losses,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_elo() {
+        assert_eq!(score(0, 0, 0).elo(), None);
+
+        // cutechess reports 34.86 +/- 34.16 for this score
+        let (elo, lower, upper) = score(60, 100, 40).elo().unwrap();
+        assert_close(elo, 34.86);
+        assert_close(lower, 1.04);
+        assert_close(upper, 69.36);
+        assert_close((upper - lower) / 2.0, 34.16);
+
+        let (elo, lower, upper) = score(50, 100, 50).elo().unwrap();
+        assert_close(elo, 0.0);
+        assert_close(lower, -34.16);
+        assert_close(upper, 34.16);
+    }
+
+    #[test]
+    fn test_llr() {
+        assert_eq!(score(0, 0, 0).llr(0.0, 5.0), 0.0);
+        // there is no variance when every game is drawn
+        assert_eq!(score(0, 100, 0).llr(0.0, 5.0), 0.0);
+
+        assert_close(score(3000, 4200, 2800).llr(0.0, 5.0), 3.18);
+        assert_close(score(2900, 4200, 2900).llr(0.0, 5.0), -1.79);
+        assert_close(score(2800, 4200, 3000).llr(0.0, 5.0), -6.75);
+        assert_close(score(1000, 1400, 900).llr(0.0, 10.0), 2.63);
+    }
+
+    #[test]
+    fn test_sprt_bounds() {
+        let (lower, upper) = sprt_bounds(0.05, 0.05);
+        assert_close(lower, -2.94);
+        assert_close(upper, 2.94);
+        let (lower, upper) = sprt_bounds(0.05, 0.1);
+        assert_close(lower, -2.25);
+        assert_close(upper, 2.89);
+
+        // accepts H1
+        let llr = score(3000, 4200, 2800).llr(0.0, 5.0);
+        assert!(llr >= upper);
+        // undecided
+        let llr = score(1000, 1400, 900).llr(0.0, 5.0);
+        assert!(lower < llr && llr < upper);
+        // accepts H0
+        let llr = score(2900, 4200, 2900).llr(0.0, 10.0);
+        assert!(llr <= lower);
+    }
+}
@@ -1,3 +1,24 @@
+mod book;
+mod datagen;
+mod engine_match;
+mod opening;
+mod uci;
+
 fn main() {
-    println!("Hello, world!");
+    let args = std::env::args().collect::<Vec<_>>();
+
+    match args.get(1).map(String::as_str) {
+        None | Some("uci") => uci::run(),
+        Some("book") => book::run(&args[2..]),
+        Some("datagen") => datagen::run(&args[2..]),
+        Some("match") => engine_match::run(&args[2..]),
+        Some(command) => {
+            eprintln!("unknown command \"{}\"", command);
+            eprintln!(
+                "usage: hourglass_engine_runner [uci | book <games.pgn> <book.bin> [max ply] \
+                 | datagen <positions.bin> [options] | match [options]]"
+            );
+            std::process::exit(1);
+        }
+    }
 }
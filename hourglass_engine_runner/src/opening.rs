@@ -0,0 +1,38 @@
+use hourglass_engine::{Game, PolyglotBook};
+use rand::{rngs::StdRng, seq::SliceRandom};
+
+/// How many times to try again when a random opening ends the game,
+///     before starting from the standard position instead.
+const MAX_OPENING_TRIES: usize = 100;
+
+/// The most moves played from a book, as a book with a cycle in it could go on forever.
+const MAX_BOOK_PLIES: usize = 100;
+
+/// Plays the opening from the book, if any, and then random moves.
+/// Tries again if the opening ends the game.
+pub fn play_opening(book: Option<&PolyglotBook>, random_plies: usize, rng: &mut StdRng) -> Game {
+    for _ in 0..MAX_OPENING_TRIES {
+        let mut game = Game::new();
+        if let Some(book) = book {
+            while game.moves().len() < MAX_BOOK_PLIES {
+                let Some(umove) = book.weighted_move(game.board(), rng) else {
+                    break;
+                };
+                if game.play(umove).is_err() {
+                    break;
+                }
+            }
+        }
+        for _ in 0..random_plies {
+            let moves = game.board().generate_moves();
+            let Some(umove) = moves.choose(rng) else {
+                break;
+            };
+            game.play(*umove).expect("a generated move should be legal");
+        }
+        if !game.status().is_over() {
+            return game;
+        }
+    }
+    Game::new()
+}
@@ -0,0 +1,202 @@
+use std::{
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+use hourglass_engine::{Board, BookSelection, Game, Move, PolyglotBook, Tablebase, Variant};
+
+/// The search depth used when `go` is not given a depth.
+const DEFAULT_DEPTH: u32 = 3;
+
+struct UciState {
+    /// The game from the last `position` command.
+    game: Game,
+    chess960: bool,
+    variant: Variant,
+    /// Whether to play moves from the book while the position is in it.
+    own_book: bool,
+    book: Option<PolyglotBook>,
+    /// The Syzygy tables in the directories of the `SyzygyPath` option.
+    tablebase: Option<Tablebase>,
+}
+
+impl UciState {
+    fn new() -> Self {
+        UciState {
+            game: Game::new(),
+            chess960: false,
+            variant: Variant::Standard,
+            own_book: false,
+            book: None,
+            tablebase: None,
+        }
+    }
+
+    fn set_option<'a>(&mut self, mut words: impl Iterator<Item = &'a str>) {
+        if words.next() != Some("name") {
+            return;
+        }
+
+        let name = words
+            .by_ref()
+            .take_while(|w| *w != "value")
+            .collect::<Vec<_>>();
+        let value = words.collect::<Vec<_>>().join(" ");
+
+        match name.join(" ").as_str() {
+            // applies from the next position
+            "UCI_Chess960" => self.chess960 = value == "true",
+            "UCI_Variant" => match Variant::from_name(&value) {
+                Some(variant) => self.variant = variant,
+                None => println!("info string unknown variant \"{}\"", value),
+            },
+            "OwnBook" => self.own_book = value == "true",
+            "BookFile" if value.is_empty() || value == "<empty>" => self.book = None,
+            "BookFile" => match PolyglotBook::open(&value) {
+                Ok(book) => self.book = Some(book),
+                Err(e) => {
+                    self.book = None;
+                    println!("info string could not load book \"{}\": {}", value, e);
+                }
+            },
+            "SyzygyPath" if value.is_empty() || value == "<empty>" => self.tablebase = None,
+            "SyzygyPath" => self.tablebase = open_tablebase(&value),
+            name => println!("info string unknown option \"{}\"", name),
+        }
+    }
+
+    fn set_position<'a>(&mut self, words: impl Iterator<Item = &'a str>) -> Result<(), String> {
+        let words = words.collect::<Vec<_>>();
+        let moves_idx = words.iter().position(|w| *w == "moves");
+        let (setup, moves) = match moves_idx {
+            Some(idx) => (&words[..idx], &words[idx + 1..]),
+            None => (&words[..], &[][..]),
+        };
+
+        let mut board = Board::new_variant(self.variant);
+        match setup {
+            ["startpos"] => {}
+            ["fen", fen @ ..] => {
+                board = Board::empty();
+                board.set_variant(self.variant);
+                board.set_chess960(self.chess960);
+                board
+                    .load_fen(&fen.join(" "))
+                    .map_err(|e| format!("invalid fen: {}", e))?;
+            }
+            _ => return Err("expected \"startpos\" or \"fen\"".to_owned()),
+        }
+        board.set_chess960(self.chess960);
+
+        let mut game = Game::from_board(board);
+        for umove in moves {
+            let parsed = Move::from_str(umove).map_err(|_| format!("invalid move {}", umove))?;
+            game.play(parsed)
+                .map_err(|e| format!("illegal move {}: {:?}", umove, e))?;
+        }
+
+        self.game = game;
+        Ok(())
+    }
+
+    fn go<'a>(&mut self, mut words: impl Iterator<Item = &'a str>) {
+        let mut depth = DEFAULT_DEPTH;
+        while let Some(word) = words.next() {
+            if word == "depth" {
+                depth = words.next().and_then(|d| d.parse().ok()).unwrap_or(depth);
+            }
+        }
+
+        let book_move = match (&self.book, self.own_book) {
+            (Some(book), true) => book.pick_move(self.game.board(), BookSelection::WeightedRandom),
+            _ => None,
+        };
+        if let Some(umove) = book_move {
+            println!("bestmove {}", umove);
+            return;
+        }
+
+        let best_move = self.game.board().get_best_move_with_tablebase(
+            depth,
+            Board::score_endgames,
+            self.tablebase.as_ref(),
+        );
+        match best_move {
+            Some(umove) => println!("bestmove {}", umove),
+            None => println!("bestmove 0000"),
+        }
+    }
+}
+
+/// Opens the tables in a list of directories, separated like the `PATH` environment variable.
+fn open_tablebase(paths: &str) -> Option<Tablebase> {
+    let mut tablebase = Tablebase::new();
+    for path in std::env::split_paths(paths) {
+        if let Err(e) = tablebase.add_directory(&path) {
+            println!(
+                "info string could not load tables from \"{}\": {}",
+                path.display(),
+                e
+            );
+        }
+    }
+    match tablebase.max_pieces() {
+        0 => None,
+        _ => Some(tablebase),
+    }
+}
+
+/// Runs the engine using the Universal Chess Interface protocol over stdin and stdout.
+pub fn run() {
+    let mut state = UciState::new();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("uci") => {
+                println!("id name Hourglass");
+                println!("id author the Hourglass developers");
+                println!("option name UCI_Chess960 type check default false");
+                let variants = Variant::ALL
+                    .iter()
+                    .map(|v| format!(" var {}", v.uci_name()))
+                    .collect::<String>();
+                println!(
+                    "option name UCI_Variant type combo default chess{}",
+                    variants
+                );
+                println!("option name OwnBook type check default false");
+                println!("option name BookFile type string default <empty>");
+                println!("option name SyzygyPath type string default <empty>");
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("setoption") => state.set_option(words),
+            Some("ucinewgame") => {
+                let mut board = Board::new_variant(state.variant);
+                board.set_chess960(state.chess960);
+                state.game = Game::from_board(board);
+            }
+            Some("position") => {
+                if let Err(e) = state.set_position(words) {
+                    println!("info string {}", e);
+                }
+            }
+            Some("go") => state.go(words),
+            // not part of UCI, but a common way to look at the position while debugging
+            Some("d") => {
+                let board = state.game.board();
+                println!("{}", board);
+                println!("Fen: {}", board.get_fen());
+            }
+            Some("quit") => break,
+            _ => {}
+        }
+
+        io::stdout().flush().expect("failed to flush stdout");
+    }
+}
@@ -4,10 +4,10 @@ mod setup;
 use bevy::prelude::*;
 use bevy_editor_pls::EditorPlugin;
 use bevy_mod_picking::low_latency_window_plugin;
-use hourglass_engine::Move;
+use hourglass_engine::{Move, MoveList, Square};
 use piece::PieceExt;
 use setup::{
-    Board, BoardPiece, BoardSquare, MoveHint, MoveHintAssets, PickedPiece, PromotionMenu,
+    BoardPiece, BoardSquare, Game, MoveHint, MoveHintAssets, PickedPiece, PromotionMenu,
     SetupPlugin,
 };
 
@@ -33,18 +33,20 @@ fn main() {
 }
 
 fn show_moves(
-    board: Res<Board>,
+    game: Res<Game>,
     move_hint_assets: Res<MoveHintAssets>,
     mut q_move_hits: Query<(&mut Handle<Image>, &MoveHint)>,
     q_picked_piece: Query<&BoardPiece, (With<PickedPiece>, Added<PickedPiece>)>,
 ) {
     if let Ok(picked_piece) = q_picked_piece.get_single() {
-        let mut moves = Vec::new();
-        board.get_moves_for(&mut moves, picked_piece.idx);
+        let board = game.board();
+        let mut moves = MoveList::new();
+        board.get_moves_for(&mut moves, Square::new(picked_piece.idx).unwrap());
 
         for (mut image, hint) in q_move_hits.iter_mut() {
-            if moves.iter().any(|m| m.to() == hint.idx) {
-                let new_image = if board.piece_at_idx(hint.idx).is_color(!board.active_color()) {
+            let square = Square::new(hint.idx).unwrap();
+            if moves.iter().any(|m| m.to() == square) {
+                let new_image = if board.piece_on(square).is_color(!board.active_color()) {
                     move_hint_assets.take.clone()
                 } else {
                     move_hint_assets.open.clone()
@@ -55,14 +57,12 @@ fn show_moves(
     }
 }
 
-fn show_attacked_squares(
-    board: Res<Board>,
-    mut q_board_squares: Query<(&BoardSquare, &mut Sprite)>,
-) {
-    if !board.is_changed() {
+fn show_attacked_squares(game: Res<Game>, mut q_board_squares: Query<(&BoardSquare, &mut Sprite)>) {
+    if !game.is_changed() {
         return;
     }
 
+    let board = game.board();
     let attacked_squares = board.generate_attacks(!board.active_color());
 
     for (square, mut sprite) in q_board_squares.iter_mut() {
@@ -92,13 +92,16 @@ fn clear_moves(
     }
 }
 
-fn update_pieces(board: Res<Board>, mut q_piece: Query<(&BoardPiece, &mut TextureAtlasSprite)>) {
-    if !board.is_changed() {
+fn update_pieces(game: Res<Game>, mut q_piece: Query<(&BoardPiece, &mut TextureAtlasSprite)>) {
+    if !game.is_changed() {
         return;
     }
 
     for (piece, mut texture) in q_piece.iter_mut() {
-        texture.index = board.piece_at_idx(piece.idx).get_texture_idx();
+        texture.index = game
+            .board()
+            .piece_on(Square::new(piece.idx).unwrap())
+            .get_texture_idx();
     }
 }
 
@@ -1,19 +1,32 @@
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
+use hourglass_engine::BookSelection;
+use hourglass_engine::Clock;
 use hourglass_engine::InvalidMoveErr;
-use hourglass_engine::Move;
 use hourglass_engine::Piece;
 use hourglass_engine::Player;
+use hourglass_engine::PolyglotBook;
+use hourglass_engine::Tablebase;
+use hourglass_engine::TimeControl;
+use hourglass_engine::{Move, Square};
 
 use crate::piece::PieceExt;
 use crate::PromotingPiece;
 
 const SQUARE_SIZE: f32 = 100.;
+const BOOK_PATH: &str = "assets/book.bin";
+/// Only the table names are read at startup, each table is read when it is first probed.
+const SYZYGY_PATH: &str = "assets/syzygy";
+const TIME_CONTROL: &str = "5+3";
 
 pub(crate) enum InputSource {
     Bot {
         score: fn(&hourglass_engine::Board) -> f32,
         depth: u32,
+        /// Book moves are played until the game leaves the book.
+        book: Option<PolyglotBook>,
+        /// Used to convert won endgames, and at the leaves of the search.
+        tablebase: Option<Tablebase>,
     },
     Human,
 }
@@ -30,22 +43,30 @@ impl Plugin for SetupPlugin {
         app.add_startup_system(setup)
             .insert_resource(InputSourceWhite(InputSource::Human))
             .insert_resource(InputSourceBlack(InputSource::Bot {
-                score: hourglass_engine::Board::score_material,
+                score: hourglass_engine::Board::score_endgames,
                 depth: 4,
+                book: PolyglotBook::open(BOOK_PATH).ok(),
+                tablebase: Tablebase::open(SYZYGY_PATH).ok(),
             }))
-            .add_system(bot_move);
+            .insert_resource(GameClock(Clock::new(TIME_CONTROL.parse().unwrap())))
+            .add_system(bot_move)
+            .add_system(undo_redo)
+            .add_system(update_clock);
     }
 }
 
-#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Deref, DerefMut)]
-pub(crate) struct Board(hourglass_engine::Board);
+#[derive(Resource, Clone, PartialEq, Eq, Debug, Deref, DerefMut)]
+pub(crate) struct Game(hourglass_engine::Game);
 
-impl Board {
+impl Game {
     pub fn new() -> Self {
-        Board(hourglass_engine::Board::new())
+        Game(hourglass_engine::Game::new())
     }
 }
 
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub(crate) struct GameClock(Clock);
+
 #[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) struct BoardPiece {
     pub(crate) idx: usize,
@@ -96,7 +117,7 @@ fn setup(
     let dark_color = Color::hex("#3399E5").unwrap();
     let light_color = Color::hex("#E5F2FF").unwrap();
 
-    commands.insert_resource(Board::new());
+    commands.insert_resource(Game::new());
 
     spawn_promotion_menu(&mut commands, atlas_handle.clone(), &mut meshes);
     spawn_board(&mut commands, light_color, dark_color, &mut meshes);
@@ -104,36 +125,116 @@ fn setup(
     spawn_move_hints(&mut commands, move_hint_assets);
 }
 
+fn input_source<'a>(
+    player: Player,
+    input_white: &'a InputSourceWhite,
+    input_black: &'a InputSourceBlack,
+) -> &'a InputSource {
+    match player {
+        Player::White => &input_white.0,
+        Player::Black => &input_black.0,
+    }
+}
+
 fn bot_move(
-    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    clock: Res<GameClock>,
     input_white: Res<InputSourceWhite>,
     input_black: Res<InputSourceBlack>,
 ) {
-    if board.active_color() == Player::White {
-        match input_white.0 {
-            InputSource::Human => {}
-            InputSource::Bot { score, depth } => {
-                let umove = board.get_best_move(depth, score).unwrap();
-                match board.try_move(umove) {
-                    Ok(()) => {}
-                    Err(e) => error!("Error playing best move {:?}", e),
-                }
+    if game.status_with_clock(&clock).is_over() {
+        return;
+    }
+
+    let board = *game.board();
+    let input = input_source(board.active_color(), &input_white, &input_black);
+
+    match input {
+        InputSource::Human => {}
+        InputSource::Bot {
+            score,
+            depth,
+            book,
+            tablebase,
+        } => {
+            let book_move = book
+                .as_ref()
+                .and_then(|book| book.pick_move(&board, BookSelection::WeightedRandom));
+            let umove = match book_move {
+                Some(umove) => umove,
+                None => board
+                    .get_best_move_with_tablebase(*depth, *score, tablebase.as_ref())
+                    .unwrap(),
+            };
+            match game.play(umove) {
+                Ok(()) => {}
+                Err(e) => error!("Error playing best move {:?}", e),
             }
         }
-    } else {
-        match input_black.0 {
-            InputSource::Human => {}
-            InputSource::Bot { score, depth } => {
-                let umove = board.get_best_move(depth, score).unwrap();
-                match board.try_move(umove) {
-                    Ok(()) => {}
-                    Err(e) => error!("Error playing best move {:?}", e),
-                }
-            }
+    }
+}
+
+/// Undoes moves with the left arrow key and redoes them with the right arrow key.
+///
+/// Moves are undone or redone until it is a human's turn again, so the bot doesn't
+///     immediately replay its move.
+fn undo_redo(
+    keys: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    input_white: Res<InputSourceWhite>,
+    input_black: Res<InputSourceBlack>,
+) {
+    let is_bot_turn = |game: &Game| {
+        let player = game.board().active_color();
+        matches!(
+            input_source(player, &input_white, &input_black),
+            InputSource::Bot { .. }
+        )
+    };
+
+    if keys.just_pressed(KeyCode::Left) {
+        game.undo();
+        while is_bot_turn(&game) && game.can_undo() {
+            game.undo();
+        }
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        game.redo();
+        while is_bot_turn(&game) && game.can_redo() {
+            game.redo();
         }
     }
 }
 
+/// Runs the clock for the player to move, starting it after the first move,
+///     and stops it when the game ends.
+fn update_clock(game: Res<Game>, mut clock: ResMut<GameClock>, mut moves_played: Local<usize>) {
+    if !clock.is_running() && clock.flagged().is_none() && *moves_played > 0 {
+        // the clock was stopped because the game ended, but undo may have resumed it
+        if !game.status().is_over() {
+            clock.start(game.board().active_color());
+        }
+    }
+
+    let moves = game.moves().len();
+    if moves > *moves_played {
+        match clock.is_running() {
+            true => clock.press(),
+            false => clock.start(game.board().active_color()),
+        }
+    } else if moves < *moves_played && clock.is_running() {
+        // an undo gives the turn back without adding time
+        clock.start(game.board().active_color());
+    }
+    *moves_played = moves;
+
+    let status = game.status_with_clock(&clock);
+    if status.is_over() && clock.is_running() {
+        clock.stop();
+        info!("Game over: {:?}", status);
+    }
+}
+
 fn spawn_promotion_menu(
     commands: &mut Commands,
     texture_atlas: Handle<TextureAtlas>,
@@ -178,11 +279,11 @@ fn select_promotion(
     In(event): In<ListenedEvent<Click>>,
     q_promotion_menu: Query<&PromotionMenu>,
     mut promoting_piece: ResMut<PromotingPiece>,
-    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
 ) -> Bubble {
     let piece = q_promotion_menu.get(event.target).unwrap().0;
 
-    let move_result = board.try_move(
+    let move_result = game.play(
         promoting_piece
             .o_move
             .unwrap()
@@ -377,9 +478,14 @@ fn move_piece(
 fn drop_piece_on(
     In(event): In<ListenedEvent<Drop>>,
     mut promoting_piece: ResMut<PromotingPiece>,
-    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    clock: Res<GameClock>,
     q_board_square: Query<&BoardSquare>,
 ) -> Bubble {
+    if game.status_with_clock(&clock).is_over() {
+        return Bubble::Burst;
+    }
+
     let from_square = match q_board_square.get(event.dropped) {
         Ok(dp) => dp,
         Err(_) => {
@@ -391,8 +497,12 @@ fn drop_piece_on(
         .get(event.target)
         .expect("this should be called on a board square");
 
-    let umove = Move::from_idxs(from_square.idx, this.idx);
-    match board.try_move(umove) {
+    let umove = Move::new(
+        Square::new(from_square.idx).unwrap(),
+        Square::new(this.idx).unwrap(),
+        None,
+    );
+    match game.play(umove) {
         Ok(()) => {}
         Err(InvalidMoveErr::NoPromotion) => {
             promoting_piece.o_move = Some(umove);